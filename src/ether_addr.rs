@@ -51,6 +51,7 @@ impl core::fmt::Debug for EtherAddr {
 }
 impl FromStr for EtherAddr {
     type Err = ();
+    #[allow(clippy::manual_pattern_char_comparison)]
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let input = input.trim();
         let mut addr = [0u8; EtherAddr::SIZE];
@@ -81,6 +82,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn properties() {
         assert_eq!(EtherAddr::BROADCAST.is_unicast(), false);
         assert_eq!(EtherAddr::BROADCAST.is_multicast(), true);
@@ -0,0 +1,96 @@
+use super::*;
+use crate::AsyncEtherSocket;
+
+/// Asynchronous version of [`discover_devices`]
+pub async fn discover_devices_async<T: AsyncEtherSocket>(
+    socket: &mut T,
+    mut callback: impl FnMut(EtherAddr, DiscoverList),
+) -> Result<(), T::Error> {
+    let mut buffer = [0; ETHERNET_MTU];
+    let request = DiscoverListRequest;
+    let bytes = request.encode(&mut buffer).unwrap();
+    socket.sendto(EtherAddr::BROADCAST, bytes).await?;
+
+    let timeout = Some(Duration::from_millis(100));
+    while let Some((addr, data)) = socket.recvfrom(&mut buffer, timeout).await? {
+        if let Some(list) = discovery_reply(addr, data) {
+            callback(addr, list);
+        }
+    }
+    Ok(())
+}
+
+/// Asynchronous version of [`send_request`]
+pub async fn send_request_async<'a, M, T, B>(
+    socket: &mut T,
+    buffer: &'a mut B,
+    destination: EtherAddr,
+    request: M,
+) -> Result<Option<M::Response>, RequestError<T::Error>>
+where
+    M: MessageTX<'a>,
+    T: AsyncEtherSocket,
+    B: AsMut<[u8]> + ?Sized,
+    M::Response: TryFrom<&'a [u8], Error = ParseError>,
+{
+    let buffer = buffer.as_mut();
+    let bytes = request.encode(buffer).map_err(RequestError::Encode)?;
+    socket.sendto(destination, bytes).await?;
+
+    let timeout = Some(Duration::from_millis(100));
+    let mut received = None;
+    while let Some((addr, data)) = socket.recvfrom(buffer, timeout).await? {
+        if is_response::<M>(destination, addr, data) {
+            received = Some((addr, data.len()));
+            break;
+        }
+    }
+
+    let buffer: &'a [u8] = buffer;
+    match received {
+        Some((addr, size)) => parse_response::<M, T::Error>(addr, &buffer[..size]).map(Some),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::homeplug::emulator::*;
+    use crate::memory::*;
+    use crate::*;
+    use std::vec::Vec;
+
+    const HOST: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x01]);
+
+    #[tokio::test]
+    async fn concurrent_interfaces() {
+        let mut tasks = Vec::new();
+        let mut devices = Vec::new();
+        for i in 0..4 {
+            let device = EtherAddr([0x00, 0xb0, 0x52, 0, 0, i]);
+            let wire = MemoryWire::new();
+            let emulator = Emulator::new(DeviceState::qualcomm(device));
+            devices.push(emulator.spawn(&wire.interface("qca", device)).unwrap());
+            let host = wire.interface("host", HOST);
+            let mut socket = host.open(EtherType::HOMEPLUG_AV).unwrap().into_async();
+            tasks.push(tokio::spawn(async move {
+                let mut found = Vec::new();
+                discover_devices_async(&mut socket, |addr, _| found.push(addr))
+                    .await
+                    .unwrap();
+                assert_eq!(found, [device]);
+                let mut buffer = [0; 1500];
+                let request = qualcomm::SoftwareVersionRequest;
+                let response = send_request_async(&mut socket, &mut buffer, device, request);
+                let version = response.await.unwrap().unwrap();
+                assert_eq!(version.device(), qualcomm::DeviceId::QCA7420);
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+    }
+}
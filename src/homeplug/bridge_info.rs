@@ -9,7 +9,7 @@ impl<'a> MessageTX<'a> for BridgeInfoRequest {
 }
 
 #[derive(Eq, PartialEq, Hash)]
-pub struct BridgeInfo<'a>(pub &'a [u8]);
+pub struct BridgeInfo<'a>(pub(crate) &'a [u8]);
 impl BridgeInfo<'_> {
     pub fn is_bridge(&self) -> bool {
         self.payload()[0] != 0
@@ -17,6 +17,7 @@ impl BridgeInfo<'_> {
     pub fn tei(&self) -> u8 {
         self.payload()[1]
     }
+    #[allow(clippy::implied_bounds_in_impls, clippy::unnecessary_cast)]
     pub fn destinations(&self) -> impl ExactSizeIterator + Iterator<Item = EtherAddr> + '_ {
         let count = if self.is_bridge() {
             self.payload()[2] as usize
@@ -48,8 +49,45 @@ impl core::fmt::Debug for BridgeInfo<'_> {
         Ok(())
     }
 }
-impl<'a> From<&'a [u8]> for BridgeInfo<'a> {
-    fn from(data: &'a [u8]) -> Self {
-        Self(data)
+impl<'a> TryFrom<&'a [u8]> for BridgeInfo<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 2)?;
+        if payload[0] != 0 {
+            expect_payload(payload, 3)?;
+            expect_payload(payload, 3 + payload[2] as usize * 6)?;
+        }
+        Ok(Self(data))
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use owned::*;
+
+#[cfg(feature = "alloc")]
+mod owned {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Decoded copy of a [`BridgeInfo`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedBridgeInfo {
+        pub is_bridge: bool,
+        pub tei: u8,
+        pub destinations: Vec<EtherAddr>,
+    }
+
+    impl BridgeInfo<'_> {
+        pub fn to_owned(&self) -> OwnedBridgeInfo {
+            OwnedBridgeInfo {
+                is_bridge: self.is_bridge(),
+                tei: self.tei(),
+                destinations: self.destinations().collect(),
+            }
+        }
     }
 }
@@ -11,15 +11,18 @@ impl<'a> MessageTX<'a> for GetPropertyRequest {
     const OUI: OUI = OUI::BROADCOM;
     type Response = GetProperty<'a>;
 
-    fn set_payload(&self, bytes: &mut [u8]) -> usize {
-        bytes[0] = self.seq;
-        bytes[1] = self.property.0;
-        2
+    fn sequence(&self) -> Option<u8> {
+        Some(self.seq)
+    }
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.seq)?;
+        frame.push_u8(self.property.0)
     }
 }
 
 #[derive(Eq, PartialEq, Hash)]
-pub struct GetProperty<'a>(pub &'a [u8]);
+pub struct GetProperty<'a>(pub(crate) &'a [u8]);
 impl GetProperty<'_> {
     pub fn seq(&self) -> u8 {
         self.payload()[0]
@@ -30,6 +33,7 @@ impl GetProperty<'_> {
     pub fn record_size(&self) -> usize {
         u16::from_le_bytes([self.payload()[2], self.payload()[3]]) as usize
     }
+    #[allow(clippy::implied_bounds_in_impls)]
     pub fn records(&self) -> impl ExactSizeIterator + Iterator<Item = &[u8]> {
         self.payload()[4..]
             .chunks_exact(self.record_size())
@@ -54,8 +58,50 @@ impl core::fmt::Debug for GetProperty<'_> {
         Ok(())
     }
 }
-impl<'a> From<&'a [u8]> for GetProperty<'a> {
-    fn from(data: &'a [u8]) -> Self {
-        Self(data)
+impl<'a> TryFrom<&'a [u8]> for GetProperty<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 4)?;
+        let count = payload[1] as usize;
+        let record_size = u16::from_le_bytes([payload[2], payload[3]]) as usize;
+        if count > 0 && record_size == 0 {
+            return Err(ParseError::InvalidRecordSize(record_size));
+        }
+        expect_payload(payload, 4 + count * record_size)?;
+        Ok(Self(data))
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use owned::*;
+
+#[cfg(feature = "alloc")]
+mod owned {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Decoded copy of a [`GetProperty`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedGetProperty {
+        pub seq: u8,
+        pub records: Vec<Vec<u8>>,
+    }
+    impl OwnedGetProperty {
+        pub fn first(&self) -> Option<&[u8]> {
+            self.records.first().map(Vec::as_slice)
+        }
+    }
+
+    impl GetProperty<'_> {
+        pub fn to_owned(&self) -> OwnedGetProperty {
+            OwnedGetProperty {
+                seq: self.seq(),
+                records: self.records().map(<[u8]>::to_vec).collect(),
+            }
+        }
     }
 }
@@ -15,3 +15,14 @@ impl Property {
     pub const HFID_USER: Property = Property(0x25);
     pub const NAME_B1: Property = Property(0x26);
 }
+impl core::fmt::Debug for Property {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::NAME_A0 => write!(f, "NAME_A0"),
+            Self::NAME_B0 => write!(f, "NAME_B0"),
+            Self::HFID_USER => write!(f, "HFID_USER"),
+            Self::NAME_B1 => write!(f, "NAME_B1"),
+            _ => write!(f, "Property{:02x}", self.0),
+        }
+    }
+}
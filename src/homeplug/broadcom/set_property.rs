@@ -1,16 +1,6 @@
 use super::*;
 use crate::*;
 
-mod offset {
-    pub const SEQ: usize = 0;
-    pub const PROPERTY: usize = 1;
-    pub const UNKNOWN: usize = 2;
-    pub const COUNT: usize = 3;
-    pub const SIZE0: usize = 4;
-    pub const SIZE1: usize = 5;
-    pub const DATA: usize = 6;
-}
-
 pub struct SetPropertyRequest {
     pub seq: u8,
     pub property: broadcom::Property,
@@ -22,25 +12,31 @@ impl<'a> MessageTX<'a> for SetPropertyRequest {
     const OUI: OUI = OUI::BROADCOM;
     type Response = SetProperty<'a>;
 
-    fn set_payload(&self, bytes: &mut [u8]) -> usize {
-        let record_size_bytes = (self.data.len() as u16).to_le_bytes();
-        bytes[offset::SEQ] = self.seq;
-        bytes[offset::PROPERTY] = self.property.0;
-        bytes[offset::UNKNOWN] = 0x00;
-        bytes[offset::COUNT] = 1;
-        bytes[offset::SIZE0] = record_size_bytes[0];
-        bytes[offset::SIZE1] = record_size_bytes[1];
-        bytes[offset::DATA..]
-            .iter_mut()
-            .zip(self.data)
-            .for_each(|(dest, src)| *dest = src);
-        9
+    fn sequence(&self) -> Option<u8> {
+        Some(self.seq)
+    }
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.seq)?;
+        frame.push_u8(self.property.0)?;
+        frame.zeros(1)?;
+        // A single record holding all of the data
+        frame.push_u8(1)?;
+        frame.push_u16_le(length_u16(&self.data)?)?;
+        frame.push(&self.data)
     }
 }
 
 #[derive(Eq, PartialEq, Hash)]
-pub struct SetProperty<'a>(pub &'a [u8]);
-impl SetProperty<'_> {}
+pub struct SetProperty<'a>(pub(crate) &'a [u8]);
+impl SetProperty<'_> {
+    pub fn seq(&self) -> u8 {
+        self.payload()[0]
+    }
+    pub fn is_success(&self) -> bool {
+        self.payload()[1] == 0
+    }
+}
 impl MessageReader for SetProperty<'_> {
     fn bytes(&self) -> &[u8] {
         self.0
@@ -51,8 +47,10 @@ impl core::fmt::Debug for SetProperty<'_> {
         write!(f, "{:02x?}", self.payload())
     }
 }
-impl<'a> From<&'a [u8]> for SetProperty<'a> {
-    fn from(data: &'a [u8]) -> Self {
-        Self(data)
+impl<'a> TryFrom<&'a [u8]> for SetProperty<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 2)?;
+        Ok(Self(data))
     }
 }
@@ -0,0 +1,445 @@
+//! Request/response correlation for pipelining many requests over one socket
+
+extern crate std;
+
+use super::*;
+use crate::*;
+use log::debug;
+use std::time::Instant;
+use std::vec::Vec;
+
+/// How long requests are kept by default, so unclaimed broadcasts don't pile up
+const DEFAULT_EXPIRY: Duration = Duration::from_secs(30);
+
+/// Handle for a request sent through a [`Client`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(u64);
+
+/// A received reply, copied out of the socket buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub source: EtherAddr,
+    pub data: Vec<u8>,
+}
+impl Reply {
+    /// Decode the reply as message type `R`
+    pub fn parse<'a, R: TryFrom<&'a [u8], Error = ParseError>>(&'a self) -> Result<R, ParseError> {
+        R::try_from(&self.data)
+    }
+    /// The `CM_MME_ERROR.IND` the peer sent instead of a reply, if any
+    pub fn error(&self) -> Option<MMEError<'_>> {
+        let msg = UnknownMessage::try_from(&self.data[..]).ok()?;
+        if msg.mmtype() != MMType::CM_MME_ERROR.ind() {
+            return None;
+        }
+        MMEError::try_from(&self.data[..]).ok()
+    }
+}
+
+/// What a reply to an outstanding request must look like
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct RequestKey {
+    peer: EtherAddr,
+    mmv: MMV,
+    mmtype: MMType,
+    sequence: Option<u8>,
+}
+impl RequestKey {
+    fn matches(&self, source: EtherAddr, msg: &UnknownMessage) -> bool {
+        if self.peer.is_unicast() && source != self.peer {
+            return false;
+        }
+        if msg.mmtype() == MMType::CM_MME_ERROR.ind() {
+            return match MMEError::try_from(msg.0) {
+                Ok(error) => error.error_mmv() == self.mmv && error.error_mmtype() == self.mmtype,
+                Err(_) => false,
+            };
+        }
+        msg.mmv() == self.mmv
+            && msg.mmtype() == self.mmtype.cnf()
+            && (self.sequence.is_none() || msg.sequence() == self.sequence)
+    }
+}
+
+struct Pending {
+    id: RequestId,
+    key: RequestKey,
+    sent: Instant,
+    replies: Vec<Reply>,
+}
+impl Pending {
+    /// Unicast requests expect one reply, broadcasts collect replies until taken
+    fn is_complete(&self) -> bool {
+        self.key.peer.is_unicast() && !self.replies.is_empty()
+    }
+}
+
+/// Keeps a table of outstanding requests, and routes each received frame to the request it answers
+///
+/// Replies are matched by peer address, MMV, MMType and (for vendors that have one) the
+/// sequence number, so many requests can be in flight on the same socket at once.
+/// `CM_MME_ERROR.IND` carries no sequence number, so an error is only routed to a request with
+/// one if no other request to that peer could have caused it.
+///
+/// Requests that are never waited on, collected or cancelled are forgotten after the expiry
+/// (see [`Client::set_expiry`]), along with any replies that weren't taken.
+pub struct Client<T: EtherSocket> {
+    socket: T,
+    pending: Vec<Pending>,
+    expiry: Duration,
+    next_id: u64,
+    next_sequence: u8,
+}
+impl<T: EtherSocket> Client<T> {
+    pub fn new(socket: T) -> Client<T> {
+        Client {
+            socket,
+            pending: Vec::new(),
+            expiry: DEFAULT_EXPIRY,
+            next_id: 0,
+            next_sequence: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.socket
+    }
+
+    /// Number of requests still waiting for (or holding unclaimed) replies
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// How long after being sent a request is forgotten
+    pub fn set_expiry(&mut self, expiry: Duration) {
+        self.expiry = expiry;
+    }
+
+    /// Allocate a sequence number for requests that carry one (e.g. Broadcom `seq`)
+    pub fn next_sequence(&mut self) -> u8 {
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        self.next_sequence
+    }
+
+    /// Send `request` without waiting for the reply
+    pub fn send<'a, M: MessageTX<'a>>(
+        &mut self,
+        destination: EtherAddr,
+        request: M,
+    ) -> Result<RequestId, RequestError<T::Error>> {
+        let mut buffer = [0; ETHERNET_MTU];
+        let bytes = request.encode(&mut buffer).map_err(RequestError::Encode)?;
+        self.socket.sendto(destination, bytes)?;
+        let id = RequestId(self.next_id);
+        self.next_id += 1;
+        self.pending.push(Pending {
+            id,
+            key: RequestKey {
+                peer: destination,
+                mmv: M::MMV,
+                mmtype: M::MMTYPE,
+                sequence: request.sequence(),
+            },
+            sent: Instant::now(),
+            replies: Vec::new(),
+        });
+        Ok(id)
+    }
+
+    /// Receive a single frame and route it to the request it answers
+    ///
+    /// Returns `false` if nothing was received within `timeout`.
+    pub fn poll(&mut self, timeout: Option<Duration>) -> Result<bool, T::Error> {
+        let expiry = self.expiry;
+        self.pending
+            .retain(|pending| pending.sent.elapsed() < expiry);
+
+        let mut buffer = [0; ETHERNET_MTU];
+        let (source, data) = match self.socket.recvfrom(&mut buffer, timeout)? {
+            Some(received) => received,
+            None => return Ok(false),
+        };
+        let msg = match UnknownMessage::try_from(data) {
+            Ok(msg) => msg,
+            Err(err) => {
+                warn!("[{source:?}] {err}");
+                return Ok(true);
+            }
+        };
+        let mut matching = self
+            .pending
+            .iter_mut()
+            .filter(|pending| !pending.is_complete() && pending.key.matches(source, &msg));
+        let pending = matching.next();
+        let sequenced = pending.as_ref().is_some_and(|p| p.key.sequence.is_some());
+        if sequenced && msg.mmtype() == MMType::CM_MME_ERROR.ind() && matching.next().is_some() {
+            debug!("[{source:?}] {msg:?} - Ambiguous error");
+            return Ok(true);
+        }
+        match pending {
+            Some(pending) => pending.replies.push(Reply {
+                source,
+                data: data.to_vec(),
+            }),
+            None => debug!("[{source:?}] {msg:?} - No matching request"),
+        }
+        Ok(true)
+    }
+
+    /// Take the reply to a unicast request, if it has arrived
+    ///
+    /// For broadcast requests this takes the first reply, and the request stays outstanding.
+    pub fn take(&mut self, id: RequestId) -> Option<Reply> {
+        let index = self.pending.iter().position(|pending| pending.id == id)?;
+        let pending = &mut self.pending[index];
+        if pending.replies.is_empty() {
+            return None;
+        }
+        let reply = pending.replies.remove(0);
+        if pending.key.peer.is_unicast() {
+            self.pending.remove(index);
+        }
+        Some(reply)
+    }
+
+    /// Wait up to `timeout` for the reply to `id`, routing other replies as they arrive
+    ///
+    /// The request is forgotten if no reply arrives in time.
+    pub fn wait(&mut self, id: RequestId, timeout: Duration) -> Result<Option<Reply>, T::Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(reply) = self.take(id) {
+                return Ok(Some(reply));
+            }
+            let now = Instant::now();
+            if now >= deadline || !self.poll(Some(deadline - now))? {
+                if let Some(reply) = self.take(id) {
+                    return Ok(Some(reply));
+                }
+                self.cancel(id);
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Receive for `timeout`, then return every reply to `id` and forget the request
+    pub fn collect(&mut self, id: RequestId, timeout: Duration) -> Result<Vec<Reply>, T::Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let now = Instant::now();
+            if now >= deadline || !self.poll(Some(deadline - now))? {
+                break;
+            }
+        }
+        Ok(self.cancel(id))
+    }
+
+    /// Forget about a request, returning any replies that hadn't been taken
+    pub fn cancel(&mut self, id: RequestId) -> Vec<Reply> {
+        match self.pending.iter().position(|pending| pending.id == id) {
+            Some(index) => self.pending.remove(index).replies,
+            None => Vec::new(),
+        }
+    }
+
+    /// Send `request` and wait for its reply, routing replies to other requests meanwhile
+    pub fn request<'a, M: MessageTX<'a>>(
+        &mut self,
+        destination: EtherAddr,
+        request: M,
+        timeout: Duration,
+    ) -> Result<Option<Reply>, RequestError<T::Error>> {
+        let id = self.send(destination, request)?;
+        Ok(self.wait(id, timeout)?)
+    }
+}
+impl<T: EtherSocket> core::fmt::Debug for Client<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "Client({:?} pending={})",
+            self.socket,
+            self.pending.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::homeplug::emulator::*;
+    use crate::memory::*;
+
+    const HOST: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x01]);
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn pipelined() {
+        let wire = MemoryWire::new();
+        let devices: Vec<EtherAddr> = (1..=3)
+            .map(|i| EtherAddr([0x00, 0xb0, 0x52, 0, 0, i]))
+            .collect();
+        let _emulators: Vec<EmulatorHandle> = devices
+            .iter()
+            .map(|&addr| {
+                let mut state = DeviceState::qualcomm(addr);
+                state.hfid_user = std::format!("Device {}", addr.0[5]);
+                let emulator = Emulator::new(state);
+                emulator.spawn(&wire.interface("qca", addr)).unwrap()
+            })
+            .collect();
+        let host = wire.interface("host", HOST);
+        let mut client = Client::new(host.open(EtherType::HOMEPLUG_AV).unwrap());
+
+        // Every request is sent before any reply is read
+        let ids: Vec<(EtherAddr, RequestId, RequestId)> = devices
+            .iter()
+            .map(|&addr| {
+                let hfid = client.send(addr, HFIDRequest::GetUsr).unwrap();
+                let caps = client.send(addr, StationCapabilitiesRequest).unwrap();
+                (addr, hfid, caps)
+            })
+            .collect();
+        let discover = client
+            .send(EtherAddr::BROADCAST, DiscoverListRequest)
+            .unwrap();
+        assert_eq!(client.pending(), 7);
+
+        // Collect in the opposite order to sending
+        for &(addr, hfid, caps) in ids.iter().rev() {
+            let reply = client.wait(caps, TIMEOUT).unwrap().unwrap();
+            assert_eq!(reply.source, addr);
+            let caps: StationCapabilities = reply.parse().unwrap();
+            assert_eq!(caps.addr(), addr);
+            let reply = client.wait(hfid, TIMEOUT).unwrap().unwrap();
+            let hfid: HFID = reply.parse().unwrap();
+            assert_eq!(hfid.hfid(), std::format!("Device {}", addr.0[5]));
+        }
+        let replies = client
+            .collect(discover, Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(replies.len(), 3);
+        assert_eq!(client.pending(), 0);
+
+        // Errors are routed to the request that caused them
+        let id = client.send(
+            devices[0],
+            slac::SlacParamRequest {
+                application_type: slac::ApplicationType::PEV_EVSE,
+                security_type: slac::SecurityType::NONE,
+                run_id: [0; 8],
+            },
+        );
+        let reply = client.wait(id.unwrap(), TIMEOUT).unwrap().unwrap();
+        assert_eq!(reply.error().unwrap().error(), ErrorType::NOT_SUPPORTED);
+    }
+
+    /// `CM_MME_ERROR.IND` rejecting `request`
+    fn not_supported(reply: &mut [u8], request: &[u8]) -> usize {
+        let msg = UnknownMessage(request);
+        let mmtype = MMType::CM_MME_ERROR.ind();
+        let header = set_header(reply, MMV::HOMEPLUG_AV_1_1, mmtype, OUI::default());
+        reply[header] = ErrorType::NOT_SUPPORTED.0;
+        reply[header + 1] = msg.mmv().0;
+        reply[header + 2..header + 4].copy_from_slice(&msg.mmtype().to_le_bytes());
+        reply[header + 4..header + 6].copy_from_slice(&[0, 0]);
+        header + 6
+    }
+
+    #[test]
+    fn errors() {
+        let wire = MemoryWire::new();
+        let qca = EtherAddr([0x00, 0xb0, 0x52, 0, 0, 1]);
+        let bcm = EtherAddr([0x00, 0x1f, 0x84, 0, 0, 1]);
+        let host = wire.interface("host", HOST);
+        let mut client = Client::new(host.open(EtherType::HOMEPLUG_AV).unwrap());
+        let mut device = wire
+            .interface("qca", qca)
+            .open(EtherType::HOMEPLUG_AV)
+            .unwrap();
+        let mut b = [0; ETHERNET_MTU];
+
+        // An error only answers the request with the same MMV and MMType
+        let hfid = client.send(qca, HFIDRequest::GetUsr).unwrap();
+        let caps = client.send(qca, StationCapabilitiesRequest).unwrap();
+        let (_, request) = device.recvfrom(&mut b, Some(TIMEOUT)).unwrap().unwrap();
+        let mut error = [0; ETHERNET_MTU];
+        let size = not_supported(&mut error, request);
+        device.sendto(HOST, &error[..size]).unwrap();
+        let reply = client.wait(hfid, TIMEOUT).unwrap().unwrap();
+        assert_eq!(reply.error().unwrap().error_mmtype(), MMType::CM_HFID);
+        assert_eq!(client.take(caps), None);
+        assert_eq!(client.pending(), 1);
+        client.cancel(caps);
+
+        // Errors carry no sequence number, so can't pick between two requests of the same type
+        let mut client = Client::new(host.open(EtherType::MEDIAXTREAM).unwrap());
+        let mut device = wire
+            .interface("bcm", bcm)
+            .open(EtherType::MEDIAXTREAM)
+            .unwrap();
+        let requests: Vec<RequestId> = (0..2)
+            .map(|_| {
+                let seq = client.next_sequence();
+                let property = broadcom::Property::NAME_A0;
+                client
+                    .send(bcm, broadcom::GetPropertyRequest { seq, property })
+                    .unwrap()
+            })
+            .collect();
+        let (_, request) = device.recvfrom(&mut b, Some(TIMEOUT)).unwrap().unwrap();
+        let size = not_supported(&mut error, request);
+        device.sendto(HOST, &error[..size]).unwrap();
+        assert!(client.poll(Some(TIMEOUT)).unwrap());
+        assert_eq!(client.take(requests[0]), None);
+        assert_eq!(client.take(requests[1]), None);
+    }
+
+    #[test]
+    fn expiry() {
+        let wire = MemoryWire::new();
+        let qca = EtherAddr([0x00, 0xb0, 0x52, 0, 0, 1]);
+        let _emulator = Emulator::new(DeviceState::qualcomm(qca))
+            .spawn(&wire.interface("qca", qca))
+            .unwrap();
+        let host = wire.interface("host", HOST);
+        let mut client = Client::new(host.open(EtherType::HOMEPLUG_AV).unwrap());
+        client.set_expiry(Duration::from_millis(200));
+
+        // Broadcasts stay outstanding after their replies arrive, until they expire
+        client
+            .send(EtherAddr::BROADCAST, DiscoverListRequest)
+            .unwrap();
+        assert!(client.poll(Some(TIMEOUT)).unwrap());
+        assert_eq!(client.pending(), 1);
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!client.poll(Some(Duration::from_millis(10))).unwrap());
+        assert_eq!(client.pending(), 0);
+    }
+
+    #[test]
+    fn sequence_numbers() {
+        let wire = MemoryWire::new();
+        let bcm = EtherAddr([0x00, 0x1f, 0x84, 0, 0, 1]);
+        let emulator = Emulator::new(DeviceState::broadcom(bcm));
+        let _emulator = emulator.spawn(&wire.interface("bcm", bcm)).unwrap();
+        let host = wire.interface("host", HOST);
+        let mut client = Client::new(host.open(EtherType::MEDIAXTREAM).unwrap());
+
+        let properties = [broadcom::Property::NAME_A0, broadcom::Property::NAME_B0];
+        let ids: Vec<(broadcom::Property, RequestId)> = properties
+            .iter()
+            .map(|&property| {
+                let seq = client.next_sequence();
+                let request = broadcom::GetPropertyRequest { seq, property };
+                (property, client.send(bcm, request).unwrap())
+            })
+            .collect();
+        for &(property, id) in ids.iter().rev() {
+            let reply = client.wait(id, TIMEOUT).unwrap().unwrap();
+            let reply: broadcom::GetProperty = reply.parse().unwrap();
+            let expected = emulator.state().property(property).unwrap().to_vec();
+            assert_eq!(reply.first().unwrap(), &expected[..]);
+        }
+    }
+}
@@ -1,11 +1,31 @@
 use super::*;
 use crate::*;
+use core::convert::TryInto;
 
 pub struct Station<'a>(&'a [u8]);
 impl Station<'_> {
     pub fn addr(&self) -> EtherAddr {
         EtherAddr::from_slice(&self.0[0..=5])
     }
+    pub fn tei(&self) -> u8 {
+        self.0[6]
+    }
+    pub fn same_network(&self) -> bool {
+        self.0[7] != 0
+    }
+    pub fn snid(&self) -> u8 {
+        self.0[8]
+    }
+    pub fn flags(&self) -> u8 {
+        self.0[9]
+    }
+    /// Received signal level code (0 is unknown, then 5dB steps from >-10dB to <-75dB)
+    pub fn signal_level(&self) -> u8 {
+        self.0[10]
+    }
+    pub fn ble(&self) -> u8 {
+        self.0[11]
+    }
 }
 impl core::fmt::Debug for Station<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -46,6 +66,26 @@ impl core::fmt::Debug for Station<'_> {
 }
 
 pub struct Network<'a>(&'a [u8]);
+impl Network<'_> {
+    pub fn nid(&self) -> [u8; 7] {
+        self.0[0..7].try_into().unwrap()
+    }
+    pub fn snid(&self) -> u8 {
+        self.0[7]
+    }
+    pub fn hybrid(&self) -> u8 {
+        self.0[8]
+    }
+    pub fn slots(&self) -> u8 {
+        self.0[9]
+    }
+    pub fn coordinating(&self) -> u8 {
+        self.0[10]
+    }
+    pub fn offset(&self) -> u16 {
+        u16::from_le_bytes([self.0[11], self.0[12]])
+    }
+}
 impl core::fmt::Debug for Network<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         let data = self.0;
@@ -73,17 +113,19 @@ impl<'a> MessageTX<'a> for DiscoverListRequest {
 }
 
 #[derive(Eq, PartialEq, Hash)]
-pub struct DiscoverList<'a>(pub &'a [u8]);
+pub struct DiscoverList<'a>(pub(crate) &'a [u8]);
 impl DiscoverList<'_> {
+    #[allow(mismatched_lifetime_syntaxes, clippy::implied_bounds_in_impls)]
     pub fn stations(&self) -> impl ExactSizeIterator + Iterator<Item = Station> {
         let data = self.payload();
         let station_count = data[0] as usize;
         data[1..].chunks_exact(12).take(station_count).map(Station)
     }
+    #[allow(mismatched_lifetime_syntaxes, clippy::implied_bounds_in_impls)]
     pub fn networks(&self) -> impl ExactSizeIterator + Iterator<Item = Network> {
         let data = self.payload();
         let station_count = data[0] as usize;
-        let network_offset = 1 + (station_count * 20);
+        let network_offset = 1 + (station_count * 12);
         let (network_count, networks) = if network_offset < data.len() {
             (data[network_offset] as usize, &data[network_offset + 1..])
         } else {
@@ -115,8 +157,86 @@ impl core::fmt::Debug for DiscoverList<'_> {
         Ok(())
     }
 }
-impl<'a> From<&'a [u8]> for DiscoverList<'a> {
-    fn from(data: &'a [u8]) -> Self {
-        Self(data)
+impl<'a> TryFrom<&'a [u8]> for DiscoverList<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 1)?;
+        let network_offset = 1 + payload[0] as usize * 12;
+        expect_payload(payload, network_offset)?;
+        if let Some(&network_count) = payload.get(network_offset) {
+            expect_payload(payload, network_offset + 1 + network_count as usize * 13)?;
+        }
+        Ok(Self(data))
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use owned::*;
+
+#[cfg(feature = "alloc")]
+mod owned {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Decoded copy of a [`Station`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedStation {
+        pub addr: EtherAddr,
+        pub tei: u8,
+        pub same_network: bool,
+        pub snid: u8,
+        pub flags: u8,
+        pub signal_level: u8,
+        pub ble: u8,
+    }
+
+    /// Decoded copy of a [`Network`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedNetwork {
+        pub nid: [u8; 7],
+        pub snid: u8,
+        pub hybrid: u8,
+        pub slots: u8,
+        pub coordinating: u8,
+        pub offset: u16,
+    }
+
+    /// Decoded copy of a [`DiscoverList`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedDiscoverList {
+        pub stations: Vec<OwnedStation>,
+        pub networks: Vec<OwnedNetwork>,
+    }
+
+    impl DiscoverList<'_> {
+        pub fn to_owned(&self) -> OwnedDiscoverList {
+            let stations = self.stations().map(|station| OwnedStation {
+                addr: station.addr(),
+                tei: station.tei(),
+                same_network: station.same_network(),
+                snid: station.snid(),
+                flags: station.flags(),
+                signal_level: station.signal_level(),
+                ble: station.ble(),
+            });
+            let networks = self.networks().map(|network| OwnedNetwork {
+                nid: network.nid(),
+                snid: network.snid(),
+                hybrid: network.hybrid(),
+                slots: network.slots(),
+                coordinating: network.coordinating(),
+                offset: network.offset(),
+            });
+            OwnedDiscoverList {
+                stations: stations.collect(),
+                networks: networks.collect(),
+            }
+        }
     }
 }
@@ -0,0 +1,573 @@
+//! Decode management messages into a tree of named fields, for diagnostics
+//!
+//! Message layouts are described by tables of [`Spec`]s, one table per message type. Every
+//! standard CC_, CP_, PH_, NN_ and CM_ message has a layout, as do the vendor messages this
+//! library supports. Other vendor messages are still named, with their payload shown as raw
+//! data.
+
+extern crate std;
+
+mod standard;
+mod vendor;
+
+use super::*;
+use crate::*;
+use std::string::String;
+use std::vec::Vec;
+
+/// Decoded value of a field
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Value<'a> {
+    /// The field only groups other fields
+    None,
+    Unsigned(u64),
+    /// An enumerated value, and its name if it is a known one
+    Named {
+        value: u64,
+        name: Option<&'static str>,
+    },
+    Address(EtherAddr),
+    Oui(OUI),
+    MMV(MMV),
+    MMType(MMType),
+    Bytes(&'a [u8]),
+    Text(&'a str),
+}
+impl Value<'_> {
+    /// The value as an integer, for unsigned and enumerated fields
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::Unsigned(value) | Value::Named { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+}
+impl core::fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        /// Longer byte strings are abbreviated
+        const MAX_BYTES: usize = 32;
+        match *self {
+            Value::None => Ok(()),
+            Value::Unsigned(value) => write!(f, "{}", value),
+            Value::Named {
+                value,
+                name: Some(name),
+            } => write!(f, "{} (0x{:02x})", name, value),
+            Value::Named { value, name: None } => write!(f, "Unknown (0x{:02x})", value),
+            Value::Address(addr) => write!(f, "{:?}", addr),
+            Value::Oui(oui) => write!(f, "{:?}", oui),
+            Value::MMV(mmv) => write!(f, "{:?}", mmv),
+            Value::MMType(mmtype) => write!(f, "{:?}", mmtype),
+            Value::Bytes(bytes) => {
+                for byte in bytes.iter().take(MAX_BYTES) {
+                    write!(f, "{:02x}", byte)?;
+                }
+                if bytes.len() > MAX_BYTES {
+                    write!(f, "... ({} bytes)", bytes.len())?;
+                }
+                Ok(())
+            }
+            Value::Text(text) => write!(f, "{:?}", text),
+        }
+    }
+}
+
+/// A decoded field, and the fields it contains
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field<'a> {
+    pub name: &'static str,
+    /// Offset from the start of the message
+    pub offset: usize,
+    pub length: usize,
+    pub value: Value<'a>,
+    pub children: Vec<Field<'a>>,
+}
+impl<'a> Field<'a> {
+    /// Find the first field called `name` in this field's subtree (depth first)
+    pub fn find(&self, name: &str) -> Option<&Field<'a>> {
+        find(&self.children, name)
+    }
+
+    fn write(&self, f: &mut core::fmt::Formatter, depth: usize) -> core::fmt::Result {
+        write!(f, "{:indent$}{}", "", self.name, indent = depth * 2)?;
+        if self.value != Value::None {
+            write!(f, ": {}", self.value)?;
+        }
+        writeln!(f)?;
+        for child in &self.children {
+            child.write(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+fn find<'f, 'a>(fields: &'f [Field<'a>], name: &str) -> Option<&'f Field<'a>> {
+    fields.iter().find_map(|field| {
+        Some(field)
+            .filter(|field| field.name == name)
+            .or_else(|| field.find(name))
+    })
+}
+
+/// A whole decoded message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dissection<'a> {
+    /// Message name, e.g. `CM_STA_CAP.CNF`
+    pub name: String,
+    /// The header, followed by the payload fields
+    pub fields: Vec<Field<'a>>,
+    /// Why decoding stopped early, if it did
+    pub error: Option<ParseError>,
+}
+impl<'a> Dissection<'a> {
+    /// Find the first field called `name` (depth first)
+    pub fn find(&self, name: &str) -> Option<&Field<'a>> {
+        find(&self.fields, name)
+    }
+}
+impl core::fmt::Display for Dissection<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        for field in &self.fields {
+            field.write(f, 1)?;
+        }
+        if let Some(error) = self.error {
+            writeln!(f, "  [{}]", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// How a field is encoded (integers are little endian)
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum Kind {
+    U8,
+    U16,
+    U32,
+    U64,
+    /// A byte from a table of `(value, name)`
+    Enum(&'static [(u8, &'static str)]),
+    Address,
+    Oui,
+    MessageVersion,
+    MessageType,
+    /// Network identifier (7 bytes)
+    Nid,
+    Bytes(usize),
+    /// NUL padded text
+    Text(usize),
+    /// Bytes, with the length given by an earlier field
+    Data(&'static str),
+    /// Text, with the length given by an earlier field
+    TextData(&'static str),
+    /// Everything left in the message (omitted if empty)
+    Rest,
+    /// A named group of fields
+    Group(&'static [Spec]),
+    /// Fields shared between layouts, decoded in place
+    Inline(&'static [Spec]),
+    /// Records `(item name, fields)`, with the count given by an earlier field
+    List(&'static str, &'static str, &'static [Spec]),
+    /// Fields that depend on an earlier field's value, with a default for other values
+    Switch(
+        &'static str,
+        &'static [(u64, &'static [Spec])],
+        &'static [Spec],
+    ),
+}
+
+/// A named field of a message layout
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Spec(pub &'static str, pub Kind);
+
+/// Layout of a single message type
+pub(crate) struct Layout {
+    pub mmtype: MMType,
+    /// Zero for standard messages
+    pub oui: OUI,
+    /// Vendor message name, standard messages are named by [`MMType`]
+    pub name: Option<&'static str>,
+    pub fields: &'static [Spec],
+}
+
+/// Fields decoded so far, for looking up counts and lengths
+struct Scope<'s, 'a> {
+    fields: &'s [Field<'a>],
+    parent: Option<&'s Scope<'s, 'a>>,
+}
+impl Scope<'_, '_> {
+    fn lookup(&self, name: &str) -> Option<u64> {
+        match self.fields.iter().rev().find(|field| field.name == name) {
+            Some(field) => field.value.as_u64(),
+            None => self.parent?.lookup(name),
+        }
+    }
+}
+
+struct Dissector<'a> {
+    data: &'a [u8],
+    position: usize,
+    payload: usize,
+    error: Option<ParseError>,
+}
+impl<'a> Dissector<'a> {
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let end = self.position.saturating_add(length);
+        match self.data.get(self.position..end) {
+            Some(bytes) => {
+                self.position += length;
+                Some(bytes)
+            }
+            None => {
+                self.error = Some(ParseError::TruncatedPayload {
+                    expected: end - self.payload,
+                    actual: self.data.len() - self.payload,
+                });
+                None
+            }
+        }
+    }
+
+    fn fields(&mut self, specs: &[Spec], parent: Option<&Scope<'_, 'a>>) -> Vec<Field<'a>> {
+        let mut fields = Vec::new();
+        for &Spec(name, kind) in specs {
+            if self.error.is_some() {
+                break;
+            }
+            let inline = match kind {
+                Kind::Inline(specs) => Some(specs),
+                Kind::Switch(on, cases, default) => {
+                    let value = Scope {
+                        fields: &fields,
+                        parent,
+                    }
+                    .lookup(on);
+                    let specs = cases.iter().find(|(case, _)| Some(*case) == value);
+                    Some(specs.map_or(default, |(_, specs)| *specs))
+                }
+                _ => None,
+            };
+            if let Some(specs) = inline {
+                let scope = Scope {
+                    fields: &fields,
+                    parent,
+                };
+                let mut inlined = self.fields(specs, Some(&scope));
+                fields.append(&mut inlined);
+                continue;
+            }
+            let offset = self.position;
+            let field = {
+                let scope = Scope {
+                    fields: &fields,
+                    parent,
+                };
+                self.field(kind, &scope)
+            };
+            if let Some((value, children)) = field {
+                fields.push(Field {
+                    name,
+                    offset,
+                    length: self.position - offset,
+                    value,
+                    children,
+                });
+            }
+        }
+        fields
+    }
+
+    fn field(&mut self, kind: Kind, scope: &Scope<'_, 'a>) -> Option<(Value<'a>, Vec<Field<'a>>)> {
+        let lookup = |field: &str| scope.lookup(field).unwrap_or(0) as usize;
+        let value = match kind {
+            Kind::U8 => Value::Unsigned(self.take(1)?[0] as u64),
+            Kind::U16 => {
+                let bytes = self.take(2)?;
+                Value::Unsigned(u16::from_le_bytes([bytes[0], bytes[1]]) as u64)
+            }
+            Kind::U32 => {
+                let mut value = [0; 4];
+                value.copy_from_slice(self.take(4)?);
+                Value::Unsigned(u32::from_le_bytes(value) as u64)
+            }
+            Kind::U64 => {
+                let mut value = [0; 8];
+                value.copy_from_slice(self.take(8)?);
+                Value::Unsigned(u64::from_le_bytes(value))
+            }
+            Kind::Enum(names) => {
+                let value = self.take(1)?[0];
+                let name = names.iter().find(|(v, _)| *v == value).map(|(_, n)| *n);
+                Value::Named {
+                    value: value as u64,
+                    name,
+                }
+            }
+            Kind::Address => Value::Address(EtherAddr::from_slice(self.take(6)?)),
+            Kind::Oui => {
+                let bytes = self.take(3)?;
+                Value::Oui(OUI([bytes[0], bytes[1], bytes[2]]))
+            }
+            Kind::MessageVersion => Value::MMV(MMV(self.take(1)?[0])),
+            Kind::MessageType => {
+                let bytes = self.take(2)?;
+                Value::MMType(MMType::from_le_bytes([bytes[0], bytes[1]]))
+            }
+            Kind::Nid => Value::Bytes(self.take(7)?),
+            Kind::Bytes(length) => Value::Bytes(self.take(length)?),
+            Kind::Text(length) => text(self.take(length)?),
+            Kind::Data(length) => Value::Bytes(self.take(lookup(length))?),
+            Kind::TextData(length) => text(self.take(lookup(length))?),
+            Kind::Rest => {
+                let rest = self.take(self.data.len() - self.position)?;
+                if rest.is_empty() {
+                    return None;
+                }
+                Value::Bytes(rest)
+            }
+            Kind::Group(specs) => return Some((Value::None, self.fields(specs, Some(scope)))),
+            Kind::List(count, item, specs) => {
+                let mut items = Vec::new();
+                for _ in 0..lookup(count) {
+                    let offset = self.position;
+                    let children = self.fields(specs, Some(scope));
+                    items.push(Field {
+                        name: item,
+                        offset,
+                        length: self.position - offset,
+                        value: Value::None,
+                        children,
+                    });
+                    if self.error.is_some() {
+                        break;
+                    }
+                }
+                return Some((Value::None, items));
+            }
+            Kind::Inline(_) | Kind::Switch(..) => unreachable!("expanded by `fields`"),
+        };
+        Some((value, Vec::new()))
+    }
+}
+
+/// NUL padded text, or bytes if it isn't valid UTF-8
+fn text(bytes: &[u8]) -> Value<'_> {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    match core::str::from_utf8(&bytes[..end]) {
+        Ok(text) => Value::Text(text),
+        Err(_) => Value::Bytes(bytes),
+    }
+}
+
+fn layouts() -> impl Iterator<Item = &'static Layout> {
+    standard::LAYOUTS.iter().chain(vendor::LAYOUTS.iter())
+}
+
+fn layout(mmtype: MMType, oui: OUI) -> Option<&'static Layout> {
+    layouts().find(|layout| layout.mmtype == mmtype && layout.oui == oui)
+}
+
+/// Name of a message, including vendor specific ones this library knows about
+pub fn message_name(mmtype: MMType, oui: OUI) -> String {
+    let code = mmtype.code();
+    let vendor_name = if !mmtype.is_vendor() {
+        None
+    } else if oui == OUI::QUALCOMM {
+        qualcomm::mmtype_name(mmtype)
+    } else {
+        layouts()
+            .find(|layout| layout.mmtype.base() == mmtype.base() && layout.oui == oui)
+            .and_then(|layout| layout.name)
+    };
+    match vendor_name {
+        Some(name) => std::format!("{}.{:?}", name, code),
+        None => std::format!("{:?}", mmtype),
+    }
+}
+
+/// Decode a management message (starting at the MMV, after the Ethernet header)
+///
+/// Decoding stops at the first field that doesn't fit in the message, leaving the fields
+/// decoded so far and the reason in [`Dissection::error`].
+pub fn dissect(data: &[u8]) -> Dissection<'_> {
+    let msg = match UnknownMessage::try_from(data) {
+        Ok(msg) => msg,
+        Err(error) => {
+            return Dissection {
+                name: String::from("Invalid"),
+                fields: Vec::new(),
+                error: Some(error),
+            }
+        }
+    };
+    let (mmv, mmtype, oui) = (msg.mmv(), msg.mmtype(), msg.oui());
+    let payload = header_size(mmv, mmtype);
+    let mut dissector = Dissector {
+        data,
+        position: 0,
+        payload,
+        error: None,
+    };
+
+    let mut header = std::vec![
+        Spec("MMV", Kind::MessageVersion),
+        Spec("MMType", Kind::MessageType)
+    ];
+    if mmv != MMV::HOMEPLUG_AV_1_0 {
+        header.push(Spec("FMI", Kind::U8));
+        header.push(Spec("FMSN", Kind::U8));
+    }
+    if mmtype.is_vendor() {
+        header.push(Spec("OUI", Kind::Oui));
+    }
+    let header = dissector.fields(&header, None);
+
+    let specs = match layout(mmtype, oui) {
+        Some(layout) => layout.fields,
+        None => &[Spec("Data", Kind::Rest)],
+    };
+    let body = dissector.fields(specs, None);
+    let mut fields = std::vec![Field {
+        name: "Header",
+        offset: 0,
+        length: payload,
+        value: Value::None,
+        children: header,
+    }];
+    if !body.is_empty() || dissector.error.is_some() {
+        fields.push(Field {
+            name: "Payload",
+            offset: payload,
+            length: dissector.position - payload,
+            value: Value::None,
+            children: body,
+        });
+    }
+    Dissection {
+        name: message_name(mmtype, oui),
+        fields,
+        error: dissector.error,
+    }
+}
+
+/// `(value, name)` tables shared by the layouts
+mod names {
+    pub const RESULT: &[(u8, &str)] = &[(0x00, "Success"), (0x01, "Failure")];
+    pub const STATION_ROLE: &[(u8, &str)] = &[(0x00, "STA"), (0x01, "PCo"), (0x02, "CCo")];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_layout() {
+        // Every layout decodes from a large enough message, and copes with an empty payload
+        let mut data = [0u8; 1500];
+        for layout in layouts() {
+            let mmv = if layout.oui == OUI::QUALCOMM {
+                MMV::HOMEPLUG_AV_1_0
+            } else {
+                MMV::HOMEPLUG_AV_1_1
+            };
+            let size = set_header(&mut data, mmv, layout.mmtype, layout.oui);
+            let dissection = dissect(&data);
+            assert_eq!(dissection.error, None, "{}", dissection.name);
+            let dissection = dissect(&data[..size]);
+            assert_eq!(dissection.name, message_name(layout.mmtype, layout.oui));
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn decode() {
+        let data = [
+            0x00, 0x39, 0xa0, 0x00, 0xb0, 0x52, // VS_NW_INFO.CNF
+            0x01, // One network
+            0xb0, 0xf2, 0xe6, 0x95, 0x66, 0x6b, 0x03, 0x05, 0x01, 0x02, // NID, SNID, TEI, Role
+            0x00, 0xb0, 0x52, 0x00, 0x00, 0x01, 0x01, 0x01, // CCo, CCo TEI, Stations
+            0x00, 0xb0, 0x52, 0x00, 0x00, 0x02, 0x02, 0x02, 0x00, 0x00, 0x00, 0x00, 0x99, 85, 91,
+        ];
+        let dissection = dissect(&data);
+        assert_eq!(dissection.name, "VS_NW_INFO.CNF");
+        assert_eq!(dissection.error, None);
+        let role = dissection.find("Role").unwrap();
+        assert_eq!(role.offset, 16);
+        assert_eq!(role.value, Value::Named { value: 2, name: Some("CCo") });
+        let station = dissection.find("Station").unwrap();
+        assert_eq!(station.find("TxRate").unwrap().value, Value::Unsigned(85));
+        assert_eq!(
+            station.find("Address").unwrap().value,
+            Value::Address(EtherAddr([0x00, 0xb0, 0x52, 0x00, 0x00, 0x02]))
+        );
+
+        // Truncated in the middle of the station list
+        let dissection = dissect(&data[..data.len() - 2]);
+        assert_eq!(dissection.find("Station").unwrap().find("TxRate"), None);
+        assert_eq!(
+            dissection.error,
+            Some(ParseError::TruncatedPayload { expected: 33, actual: 32 })
+        );
+
+        // Standard messages are decoded field by field
+        let data = [0x01, 0x00, 0x00, 0x00, 0x00, 0xaa];
+        let dissection = dissect(&data);
+        assert_eq!(dissection.name, "CC_CCO_APPOINT.REQ");
+        assert_eq!(dissection.find("ReqType").unwrap().value.as_u64(), Some(0xaa));
+        let data = [0x01, 0x08, 0x00, 0x00, 0x00, 0x01, 0x07];
+        let dissection = dissect(&data);
+        assert_eq!(dissection.name, "CC_LINK_INFO.REQ");
+        assert_eq!(dissection.find("GLID").unwrap().value, Value::Unsigned(7));
+
+        // Messages without a layout (e.g. unknown vendor messages) are named, with raw data
+        let data = [0x01, 0x00, 0xa0, 0x00, 0x00, 0x00, 0x80, 0xe1, 0xaa];
+        let dissection = dissect(&data);
+        assert_eq!(dissection.name, message_name(MMType(0xa000), OUI([0x00, 0x80, 0xe1])));
+        assert_eq!(dissection.find("Data").unwrap().value, Value::Bytes(&[0xaa]));
+    }
+
+    #[test]
+    fn standard_coverage() {
+        // Every named standard message type has at least one layout
+        for value in (0..0x8000).step_by(4) {
+            let mmtype = MMType(value);
+            if mmtype.is_vendor() || mmtype.name(MMTypeProfile::HomePlugAV).is_none() {
+                continue;
+            }
+            assert!(
+                standard::LAYOUTS
+                    .iter()
+                    .any(|layout| layout.mmtype.base() == mmtype),
+                "{:?}",
+                mmtype
+            );
+        }
+    }
+
+    #[test]
+    fn slac() {
+        let request = slac::SlacParamRequest {
+            application_type: slac::ApplicationType::PEV_EVSE,
+            security_type: slac::SecurityType::NONE,
+            run_id: [1, 2, 3, 4, 5, 6, 7, 8],
+        };
+        let mut buffer = [0; 64];
+        let dissection = dissect(request.encode(&mut buffer).unwrap());
+        assert_eq!(dissection.name, "CM_SLAC_PARM.REQ");
+        assert_eq!(
+            dissection.find("ApplicationType").unwrap().value,
+            Value::Named {
+                value: 0,
+                name: Some("PEV-EVSE matching")
+            }
+        );
+        assert_eq!(
+            dissection.find("RunID").unwrap().value,
+            Value::Bytes(&[1, 2, 3, 4, 5, 6, 7, 8])
+        );
+        assert_eq!(
+            MMType::CM_SLAC_PARM.name(MMTypeProfile::HomePlugAV),
+            Some("CM_TRIGGER_ATTEN_CHAR")
+        );
+    }
+}
@@ -0,0 +1,842 @@
+//! Layouts of the standard HomePlug AV (and Green PHY) management messages
+
+use super::names::*;
+use super::Kind::*;
+use super::*;
+
+const STATION_VERSION: &[(u8, &str)] = &[(0x00, "HPAV1.1"), (0x01, "HPAV2.0")];
+const SIGNAL_LEVEL: &[(u8, &str)] = &[
+    (0x00, "Unknown"),
+    (0x01, ">-10dB"),
+    (0x02, ">-15dB"),
+    (0x03, ">-20dB"),
+    (0x04, ">-25dB"),
+    (0x05, ">-30dB"),
+    (0x06, ">-35dB"),
+    (0x07, ">-40dB"),
+    (0x08, ">-45dB"),
+    (0x09, ">-50dB"),
+    (0x0a, ">-55dB"),
+    (0x0b, ">-60dB"),
+    (0x0c, ">-65dB"),
+    (0x0d, ">-70dB"),
+    (0x0e, ">-75dB"),
+    (0x0f, "<-75dB"),
+];
+const KEY_TYPE: &[(u8, &str)] = &[
+    (0x00, "DAK"),
+    (0x01, "NMK"),
+    (0x02, "NEK"),
+    (0x03, "TEK"),
+    (0x04, "HASH_KEY"),
+    (0x05, "NONCE_ONLY"),
+];
+const PROTOCOL_ID: &[(u8, &str)] = &[
+    (0x00, "AUTHENTICATE"),
+    (0x01, "PROVIDE_NEK"),
+    (0x02, "PROVIDE_NMK_DAK"),
+    (0x03, "PROVIDE_NMK_UKE"),
+    (0x04, "HLE"),
+];
+const GET_KEY_RESULT: &[(u8, &str)] = &[
+    (0x00, "Key granted"),
+    (0x01, "Request refused"),
+    (0x02, "Unsupported method/key type"),
+];
+const HFID_REQ_TYPE: &[(u8, &str)] = &[
+    (0x00, "GET_MFG"),
+    (0x01, "GET_USR"),
+    (0x02, "GET_NET"),
+    (0x03, "SET_USR"),
+    (0x04, "SET_NET"),
+    (0xff, "FAILURE"),
+];
+const ERROR_TYPE: &[(u8, &str)] = &[
+    (0x00, "Not Supported"),
+    (0x01, "Invalid Fields"),
+    (0x02, "Unsupported Feature"),
+];
+const ASSOC_REQ_TYPE: &[(u8, &str)] = &[(0x00, "New"), (0x01, "Renew")];
+const ASSOC_RESULT: &[(u8, &str)] = &[
+    (0x00, "Success"),
+    (0x01, "Temporary resource exhaustion"),
+    (0x02, "Permanent resource exhaustion"),
+    (0x03, "Unknown NID"),
+];
+const BURST: &[(u8, &str)] = &[(0x00, "None"), (0x01, "SACK"), (0x02, "SACK+SOF")];
+const LINK_STATS_REQ_TYPE: &[(u8, &str)] =
+    &[(0x00, "Reset"), (0x01, "Get"), (0x02, "Get and reset")];
+const LINK_STATS_DIRECTION: &[(u8, &str)] = &[(0x00, "Transmit"), (0x01, "Receive")];
+const APPLICATION_TYPE: &[(u8, &str)] = &[(0x00, "PEV-EVSE matching")];
+const SECURITY_TYPE: &[(u8, &str)] = &[(0x00, "No security"), (0x01, "Public key")];
+const RESP_TYPE: &[(u8, &str)] = &[(0x00, "Not transmitted"), (0x01, "Other GP station")];
+const SIGNAL_TYPE: &[(u8, &str)] = &[(0x00, "PEV S2 toggles")];
+const VALIDATE_RESULT: &[(u8, &str)] = &[
+    (0x00, "Not ready"),
+    (0x01, "Ready"),
+    (0x02, "Success"),
+    (0x03, "Failure"),
+    (0x04, "Not required"),
+];
+const LINK_REQ_TYPE: &[(u8, &str)] = &[(0x00, "CID"), (0x01, "GLID")];
+const CONN_INFO_REQ_TYPE: &[(u8, &str)] = &[(0x00, "CSPEC"), (0x01, "Link statistics")];
+const PKCS_STATUS: &[(u8, &str)] = &[
+    (0x00, "Success"),
+    (0x01, "Unsupported cipher suite"),
+    (0x02, "Certificate unavailable"),
+];
+
+const fn standard(mmtype: MMType, fields: &'static [Spec]) -> Layout {
+    Layout {
+        mmtype,
+        oui: OUI([0, 0, 0]),
+        name: None,
+        fields,
+    }
+}
+
+/// Capabilities a station reports in `CM_STA_IDENTIFY`
+const STA_IDENTIFY: &[Spec] = &[
+    Spec("GreenPHYCapability", U8),
+    Spec("PowerSaveCapability", U8),
+    Spec("GreenPHYPreferredAllocation", U8),
+    Spec("RepeatingAndRouting", U8),
+    Spec("HomePlugAVVersion", U8),
+    Spec("EfficientNotching", U8),
+];
+
+/// Green PHY messages (ISO 15118-3) start with these
+const SLAC_HEADER: &[Spec] = &[
+    Spec("ApplicationType", Enum(APPLICATION_TYPE)),
+    Spec("SecurityType", Enum(SECURITY_TYPE)),
+];
+
+const SLAC_MATCH: &[Spec] = &[
+    Spec("", Inline(SLAC_HEADER)),
+    Spec("MVFLength", U16),
+    Spec("PEV_ID", Bytes(17)),
+    Spec("PEV_MAC", Address),
+    Spec("EVSE_ID", Bytes(17)),
+    Spec("EVSE_MAC", Address),
+    Spec("RunID", Bytes(8)),
+    Spec("Reserved", Bytes(8)),
+];
+
+/// A connection or link, identified by its CID or GLID
+#[rustfmt::skip]
+const LINK_ID: &[Spec] = &[
+    Spec("ReqType", Enum(LINK_REQ_TYPE)),
+    Spec("", Switch("ReqType", &[(0x01, &[Spec("GLID", U8)])], &[Spec("CID", U16)])),
+];
+
+/// Connection specification (CSPEC), shown as its length and raw contents
+const CSPEC: &[Spec] = &[Spec("CSPECLength", U16), Spec("CSPEC", Rest)];
+
+/// Neighbour network coordination messages identify the sending network
+const NN_SOURCE: &[Spec] = &[
+    Spec("NID", Nid),
+    Spec("SNID", U8),
+    Spec("TEI", U8),
+    Spec("CCo", Address),
+];
+
+/// Allocations are requested in the beacon's schedule format
+#[rustfmt::skip]
+const NN_SCHEDULE: &[Spec] = &[
+    Spec("NumSessions", U8),
+    Spec("Sessions", List("NumSessions", "Session", &[
+        Spec("StartTime", U16),
+        Spec("EndTime", U16),
+    ])),
+];
+
+#[rustfmt::skip]
+pub(super) static LAYOUTS: &[Layout] = &[
+    standard(MMType::CC_CCO_APPOINT.req(), &[
+        Spec("ReqType", U8),
+        Spec("Address", Address),
+    ]),
+    standard(MMType::CC_CCO_APPOINT.cnf(), &[Spec("Result", Enum(RESULT))]),
+    standard(MMType::CC_BACKUP_APPOINT.req(), &[Spec("Action", U8)]),
+    standard(MMType::CC_BACKUP_APPOINT.cnf(), &[Spec("Result", Enum(RESULT))]),
+    standard(MMType::CC_LINK_INFO.req(), LINK_ID),
+    standard(MMType::CC_LINK_INFO.cnf(), &[
+        Spec("", Inline(LINK_ID)),
+        Spec("Result", Enum(RESULT)),
+        Spec("LinkInfo", Rest),
+    ]),
+    standard(MMType::CC_LINK_INFO.ind(), &[
+        Spec("", Inline(LINK_ID)),
+        Spec("LinkInfo", Rest),
+    ]),
+    standard(MMType::CC_LINK_INFO.rsp(), LINK_ID),
+    standard(MMType::CC_HANDOVER.req(), &[
+        Spec("HandoverType", U8),
+        Spec("Reason", U8),
+    ]),
+    standard(MMType::CC_HANDOVER.cnf(), &[Spec("Result", Enum(RESULT))]),
+    standard(MMType::CC_HANDOVER_INFO.ind(), &[
+        Spec("RSC", U8),
+        Spec("BackupCCo", Address),
+        Spec("NumStations", U8),
+        Spec("Stations", List("NumStations", "Station", &[
+            Spec("Address", Address),
+            Spec("TEI", U8),
+            Spec("Status", U8),
+        ])),
+    ]),
+    standard(MMType::CC_DISCOVER_LIST.req(), &[]),
+    standard(MMType::CC_DISCOVER_LIST.cnf(), &[
+        Spec("NumStations", U8),
+        Spec("Stations", List("NumStations", "Station", &[
+            Spec("Address", Address),
+            Spec("TEI", U8),
+            Spec("SameNetwork", U8),
+            Spec("SNID", U8),
+            Spec("Flags", U8),
+            Spec("SignalLevel", Enum(SIGNAL_LEVEL)),
+            Spec("AverageBLE", U8),
+        ])),
+        Spec("NumNetworks", U8),
+        Spec("Networks", List("NumNetworks", "Network", &[
+            Spec("NID", Nid),
+            Spec("SNID", U8),
+            Spec("Hybrid", U8),
+            Spec("NumSlots", U8),
+            Spec("Coordinating", U8),
+            Spec("Offset", U16),
+        ])),
+    ]),
+    standard(MMType::CC_LINK_NEW.req(), &[
+        Spec("CID", U16),
+        Spec("CLST", U8),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CC_LINK_NEW.cnf(), &[
+        Spec("CID", U16),
+        Spec("GLID", U8),
+        Spec("Result", Enum(RESULT)),
+        Spec("ProposedCSPEC", Rest),
+    ]),
+    standard(MMType::CC_LINK_MOD.req(), &[
+        Spec("CID", U16),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CC_LINK_MOD.cnf(), &[
+        Spec("CID", U16),
+        Spec("Result", Enum(RESULT)),
+        Spec("ProposedCSPEC", Rest),
+    ]),
+    standard(MMType::CC_LINK_SQZ.req(), &[
+        Spec("CID", U16),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CC_LINK_SQZ.cnf(), &[
+        Spec("CID", U16),
+        Spec("Result", Enum(RESULT)),
+    ]),
+    standard(MMType::CC_LINK_REL.req(), &[
+        Spec("CID", U16),
+        Spec("Reason", U8),
+    ]),
+    standard(MMType::CC_LINK_REL.ind(), &[
+        Spec("CID", U16),
+        Spec("Reason", U8),
+        Spec("ViolatedCSPEC", Rest),
+    ]),
+    standard(MMType::CC_DETECT_REPORT.req(), &[]),
+    standard(MMType::CC_DETECT_REPORT.cnf(), &[
+        Spec("HP1.0Detected", U8),
+        Spec("HP1.1Detected", U8),
+    ]),
+    standard(MMType::CC_WHO_RU.req(), &[Spec("NID", Nid)]),
+    standard(MMType::CC_WHO_RU.cnf(), &[
+        Spec("NID", Nid),
+        Spec("CCo", Address),
+    ]),
+    standard(MMType::CC_ASSOC.req(), &[
+        Spec("ReqType", Enum(ASSOC_REQ_TYPE)),
+        Spec("NID", Nid),
+        Spec("CCoCapability", U8),
+        Spec("ProxyNetworkCapability", U8),
+    ]),
+    standard(MMType::CC_ASSOC.cnf(), &[
+        Spec("Result", Enum(ASSOC_RESULT)),
+        Spec("NID", Nid),
+        Spec("SNID", U8),
+        Spec("TEI", U8),
+        Spec("LeaseTime", U16),
+    ]),
+    standard(MMType::CC_LEAVE.req(), &[Spec("Reason", U8)]),
+    standard(MMType::CC_LEAVE.cnf(), &[]),
+    standard(MMType::CC_LEAVE.ind(), &[
+        Spec("Reason", U8),
+        Spec("NID", Nid),
+    ]),
+    standard(MMType::CC_LEAVE.rsp(), &[]),
+    standard(MMType::CC_SET_TEI_MAP.req(), &[]),
+    standard(MMType::CC_SET_TEI_MAP.ind(), &[
+        Spec("Mode", U8),
+        Spec("NumStations", U8),
+        Spec("Stations", List("NumStations", "Station", &[
+            Spec("TEI", U8),
+            Spec("Address", Address),
+            Spec("Status", U8),
+        ])),
+    ]),
+    standard(MMType::CC_RELAY.req(), &[
+        Spec("FinalDestination", Address),
+        Spec("FinalTEI", U8),
+        Spec("Length", U16),
+        Spec("Data", Data("Length")),
+    ]),
+    standard(MMType::CC_RELAY.ind(), &[
+        Spec("OriginalSource", Address),
+        Spec("OriginalTEI", U8),
+        Spec("Length", U16),
+        Spec("Data", Data("Length")),
+    ]),
+    standard(MMType::CC_BEACON_RELIABILITY.req(), &[Spec("NID", Nid)]),
+    standard(MMType::CC_BEACON_RELIABILITY.cnf(), &[
+        Spec("NID", Nid),
+        Spec("BeaconsExpected", U16),
+        Spec("BeaconsReceived", U16),
+    ]),
+    standard(MMType::CC_ALLOC_MOVE.ind(), &[
+        Spec("GLID", U8),
+        Spec("", Inline(NN_SCHEDULE)),
+    ]),
+    standard(MMType::CC_ALLOC_MOVE.rsp(), &[Spec("GLID", U8)]),
+    standard(MMType::CC_ACCESS_NEW.req(), &[
+        Spec("HM", Address),
+        Spec("CID", U16),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CC_ACCESS_NEW.cnf(), &[
+        Spec("CID", U16),
+        Spec("GLID", U8),
+        Spec("Result", Enum(RESULT)),
+        Spec("ProposedCSPEC", Rest),
+    ]),
+    standard(MMType::CC_ACCESS_NEW.ind(), &[
+        Spec("CID", U16),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CC_ACCESS_NEW.rsp(), &[
+        Spec("CID", U16),
+        Spec("Result", Enum(RESULT)),
+    ]),
+    standard(MMType::CC_ACCESS_REL.req(), &[
+        Spec("CID", U16),
+        Spec("Reason", U8),
+    ]),
+    standard(MMType::CC_ACCESS_REL.cnf(), &[
+        Spec("CID", U16),
+        Spec("Result", Enum(RESULT)),
+    ]),
+    standard(MMType::CC_ACCESS_REL.ind(), &[
+        Spec("CID", U16),
+        Spec("Reason", U8),
+    ]),
+    standard(MMType::CC_ACCESS_REL.rsp(), &[Spec("CID", U16)]),
+    standard(MMType::CC_DCPPC.ind(), &[Spec("DCPPC", U8)]),
+    standard(MMType::CC_DCPPC.rsp(), &[]),
+    standard(MMType::CC_HP1_DET.req(), &[]),
+    standard(MMType::CC_HP1_DET.cnf(), &[
+        Spec("HP1.0Detected", U8),
+        Spec("HP1.1Detected", U8),
+    ]),
+    standard(MMType::CC_BLE_UPDATE.ind(), &[
+        Spec("NumLinks", U8),
+        Spec("Links", List("NumLinks", "Link", &[
+            Spec("TEI", U8),
+            Spec("LID", U8),
+            Spec("BLE", U8),
+        ])),
+    ]),
+    standard(MMType::CC_BCAST_REPEAT.ind(), &[Spec("Repeat", U8)]),
+    standard(MMType::CC_BCAST_REPEAT.rsp(), &[]),
+    standard(MMType::CC_MH_LINK_NEW.req(), &[
+        Spec("CID", U16),
+        Spec("CLST", U8),
+        Spec("Source", Address),
+        Spec("FinalDestination", Address),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CC_MH_LINK_NEW.cnf(), &[
+        Spec("CID", U16),
+        Spec("GLID", U8),
+        Spec("Result", Enum(RESULT)),
+        Spec("ProposedCSPEC", Rest),
+    ]),
+    standard(MMType::CC_ISP_DETECTION_REPORT.ind(), &[
+        Spec("Report", U8),
+        Spec("Status", Rest),
+    ]),
+    standard(MMType::CC_ISP_START_RESYNC.ind(), &[
+        Spec("ResyncTime", U32),
+        Spec("Offset", U16),
+    ]),
+    standard(MMType::CC_ISP_FINISH_RESYNC.ind(), &[
+        Spec("ResyncTime", U32),
+        Spec("Offset", U16),
+    ]),
+    standard(MMType::CC_ISP_RESYNC_DETECTED.ind(), &[
+        Spec("System", U8),
+        Spec("Offset", U16),
+    ]),
+    standard(MMType::CC_ISP_RESYNC_TRANSMIT.ind(), &[
+        Spec("System", U8),
+        Spec("Offset", U16),
+    ]),
+    standard(MMType::CC_POWERSAVE.req(), &[Spec("PowerSaveSchedule", U8)]),
+    standard(MMType::CC_POWERSAVE.cnf(), &[
+        Spec("Result", Enum(RESULT)),
+        Spec("PowerSaveSchedule", U8),
+        Spec("StartBeacon", U16),
+    ]),
+    standard(MMType::CC_POWERSAVE_EXIT.req(), &[]),
+    standard(MMType::CC_POWERSAVE_EXIT.cnf(), &[Spec("Result", Enum(RESULT))]),
+    standard(MMType::CC_POWERSAVE_LIST.req(), &[]),
+    standard(MMType::CC_POWERSAVE_LIST.cnf(), &[
+        Spec("NumStations", U8),
+        Spec("Stations", List("NumStations", "Station", &[
+            Spec("TEI", U8),
+            Spec("PowerSaveSchedule", U8),
+        ])),
+    ]),
+    standard(MMType::CC_STOP_POWERSAVE.req(), &[]),
+    standard(MMType::CC_STOP_POWERSAVE.cnf(), &[Spec("Result", Enum(RESULT))]),
+    standard(MMType::CP_PROXY_APPOINT.req(), &[
+        Spec("ReqType", U8),
+        Spec("PCo", Address),
+        Spec("TEI", U8),
+    ]),
+    standard(MMType::CP_PROXY_APPOINT.cnf(), &[Spec("Result", Enum(RESULT))]),
+    standard(MMType::PH_PROXY_APPOINT.ind(), &[
+        Spec("HCo", Address),
+        Spec("NumStations", U8),
+        Spec("Stations", List("NumStations", "Station", &[
+            Spec("Address", Address),
+            Spec("TEI", U8),
+        ])),
+    ]),
+    standard(MMType::PH_PROXY_APPOINT.rsp(), &[Spec("Result", Enum(RESULT))]),
+    standard(MMType::CP_PROXY_WAKE.req(), &[Spec("Address", Address)]),
+    standard(MMType::NN_INL.req(), &[
+        Spec("NumAuthStations", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("NumNetworks", U8),
+        Spec("Networks", List("NumNetworks", "Network", &[
+            Spec("NID", Nid),
+            Spec("SNID", U8),
+        ])),
+        Spec("Schedule", Rest),
+    ]),
+    standard(MMType::NN_INL.cnf(), &[
+        Spec("NumAuthStations", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("NumNetworks", U8),
+        Spec("Networks", List("NumNetworks", "Network", &[
+            Spec("NID", Nid),
+            Spec("SNID", U8),
+        ])),
+        Spec("Schedule", Rest),
+    ]),
+    standard(MMType::NN_NEW_NET.req(), &[
+        Spec("ReqID", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("", Inline(NN_SCHEDULE)),
+    ]),
+    standard(MMType::NN_NEW_NET.cnf(), &[
+        Spec("ReqID", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("Result", Enum(RESULT)),
+    ]),
+    standard(MMType::NN_NEW_NET.ind(), &[
+        Spec("ReqID", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("Result", Enum(RESULT)),
+    ]),
+    standard(MMType::NN_ADD_ALLOC.req(), &[
+        Spec("ReqID", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("", Inline(NN_SCHEDULE)),
+    ]),
+    standard(MMType::NN_ADD_ALLOC.cnf(), &[
+        Spec("ReqID", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("Result", Enum(RESULT)),
+    ]),
+    standard(MMType::NN_ADD_ALLOC.ind(), &[
+        Spec("ReqID", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("Result", Enum(RESULT)),
+    ]),
+    standard(MMType::NN_REL_ALLOC.req(), &[
+        Spec("ReqID", U8),
+        Spec("", Inline(NN_SOURCE)),
+        Spec("", Inline(NN_SCHEDULE)),
+    ]),
+    standard(MMType::NN_REL_ALLOC.cnf(), &[
+        Spec("ReqID", U8),
+        Spec("", Inline(NN_SOURCE)),
+    ]),
+    standard(MMType::NN_REL_NET.ind(), &[Spec("", Inline(NN_SOURCE))]),
+    standard(MMType::CM_UNASSOCIATED_STA.ind(), &[
+        Spec("NID", Nid),
+        Spec("CCoCapability", U8),
+    ]),
+    standard(MMType::CM_ENCRYPTED_PAYLOAD.ind(), &[
+        Spec("PEKS", U8),
+        Spec("AVLNStatus", U8),
+        Spec("PID", Enum(PROTOCOL_ID)),
+        Spec("PRN", U16),
+        Spec("PMN", U8),
+        Spec("IV", Bytes(16)),
+        Spec("Length", U16),
+        Spec("EncryptedPayload", Rest),
+    ]),
+    standard(MMType::CM_ENCRYPTED_PAYLOAD.rsp(), &[
+        Spec("Result", Enum(RESULT)),
+        Spec("PID", Enum(PROTOCOL_ID)),
+        Spec("PRN", U16),
+    ]),
+    standard(MMType::CM_SET_KEY.req(), &[
+        Spec("KeyType", Enum(KEY_TYPE)),
+        Spec("MyNonce", U32),
+        Spec("YourNonce", U32),
+        Spec("PID", Enum(PROTOCOL_ID)),
+        Spec("PRN", U16),
+        Spec("PMN", U8),
+        Spec("CCoCapability", U8),
+        Spec("NID", Nid),
+        Spec("NewEKS", U8),
+        Spec("NewKey", Rest),
+    ]),
+    standard(MMType::CM_SET_KEY.cnf(), &[
+        Spec("Result", Enum(RESULT)),
+        Spec("MyNonce", U32),
+        Spec("YourNonce", U32),
+        Spec("PID", Enum(PROTOCOL_ID)),
+        Spec("PRN", U16),
+        Spec("PMN", U8),
+        Spec("CCoCapability", U8),
+    ]),
+    standard(MMType::CM_GET_KEY.req(), &[
+        Spec("RequestType", U8),
+        Spec("RequestedKeyType", Enum(KEY_TYPE)),
+        Spec("NID", Nid),
+        Spec("MyNonce", U32),
+        Spec("PID", Enum(PROTOCOL_ID)),
+        Spec("PRN", U16),
+        Spec("PMN", U8),
+        Spec("HashKey", Rest),
+    ]),
+    standard(MMType::CM_GET_KEY.cnf(), &[
+        Spec("Result", Enum(GET_KEY_RESULT)),
+        Spec("RequestedKeyType", Enum(KEY_TYPE)),
+        Spec("MyNonce", U32),
+        Spec("YourNonce", U32),
+        Spec("NID", Nid),
+        Spec("EKS", U8),
+        Spec("PID", Enum(PROTOCOL_ID)),
+        Spec("PRN", U16),
+        Spec("PMN", U8),
+        Spec("Key", Rest),
+    ]),
+    standard(MMType::CM_SC_JOIN.req(), &[Spec("CCoCapability", U8)]),
+    standard(MMType::CM_SC_JOIN.cnf(), &[
+        Spec("NID", Nid),
+        Spec("AVLNStatus", U8),
+        Spec("CCoCapability", U8),
+        Spec("Status", Rest),
+    ]),
+    standard(MMType::CM_CHAN_EST.ind(), &[
+        Spec("MaxFL", U8),
+        Spec("RIFS", U8),
+        Spec("NumToneMaps", U8),
+        Spec("ToneMapIndex", U8),
+        Spec("NumIntervals", U8),
+        Spec("Intervals", List("NumIntervals", "Interval", &[
+            Spec("End", U16),
+            Spec("ToneMapIndex", U8),
+        ])),
+        Spec("ToneMap", Rest),
+    ]),
+    standard(MMType::CM_TM_UPDATE.ind(), &[
+        Spec("NumToneMaps", U8),
+        Spec("ToneMapIndex", U8),
+        Spec("ToneMap", Rest),
+    ]),
+    standard(MMType::CM_AMP_MAP.req(), &[
+        Spec("AMLength", U16),
+        Spec("AMData", Rest),
+    ]),
+    standard(MMType::CM_AMP_MAP.cnf(), &[Spec("Result", Enum(RESULT))]),
+    standard(MMType::CM_BRG_INFO.req(), &[]),
+    standard(MMType::CM_BRG_INFO.cnf(), &[
+        Spec("Bridging", U8),
+        Spec("TEI", U8),
+        Spec("", Switch("Bridging", &[(0, &[])], &[
+            Spec("NumDestinations", U8),
+            Spec("Destinations", List("NumDestinations", "Destination", &[
+                Spec("Address", Address),
+            ])),
+        ])),
+    ]),
+    standard(MMType::CM_CONN_NEW.req(), &[
+        Spec("CID", U16),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CM_CONN_NEW.cnf(), &[
+        Spec("CID", U16),
+        Spec("LLID", U8),
+        Spec("Result", Enum(RESULT)),
+        Spec("ProposedCSPEC", Rest),
+    ]),
+    standard(MMType::CM_CONN_REL.ind(), &[
+        Spec("CID", U16),
+        Spec("Reason", U8),
+        Spec("ViolatedCSPEC", Rest),
+    ]),
+    standard(MMType::CM_CONN_REL.rsp(), &[Spec("CID", U16)]),
+    standard(MMType::CM_CONN_MOD.req(), &[
+        Spec("CID", U16),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CM_CONN_MOD.cnf(), &[
+        Spec("CID", U16),
+        Spec("Result", Enum(RESULT)),
+        Spec("ProposedCSPEC", Rest),
+    ]),
+    standard(MMType::CM_CONN_INFO.req(), &[
+        Spec("ReqType", Enum(CONN_INFO_REQ_TYPE)),
+        Spec("CID", U16),
+    ]),
+    standard(MMType::CM_CONN_INFO.cnf(), &[
+        Spec("ReqType", Enum(CONN_INFO_REQ_TYPE)),
+        Spec("CID", U16),
+        Spec("Result", Enum(RESULT)),
+        Spec("Info", Rest),
+    ]),
+    standard(MMType::CM_STA_CAP.req(), &[]),
+    standard(MMType::CM_STA_CAP.cnf(), &[
+        Spec("Version", Enum(STATION_VERSION)),
+        Spec("Address", Address),
+        Spec("OUI", Oui),
+        Spec("AutoConnect", U8),
+        Spec("Smoothing", U8),
+        Spec("CCoCapability", U8),
+        Spec("ProxyCapable", U8),
+        Spec("Reserved", U8),
+        Spec("BackupCCo", U8),
+        Spec("SoftHandover", U8),
+        Spec("TwoSymbolFC", U8),
+        Spec("MaxFLAV", U16),
+        Spec("HomePlug1.1", U8),
+        Spec("HomePlug1.0.1", U8),
+        Spec("Regulatory", U8),
+        Spec("Burst", Enum(BURST)),
+        Spec("ImplementationVersion", U16),
+    ]),
+    standard(MMType::CM_NW_INFO.req(), &[]),
+    standard(MMType::CM_NW_INFO.cnf(), &[
+        Spec("NumNetworks", U8),
+        Spec("Networks", List("NumNetworks", "Network", &[
+            Spec("NID", Nid),
+            Spec("SNID", U8),
+            Spec("TEI", U8),
+            Spec("Role", Enum(STATION_ROLE)),
+            Spec("CCo", Address),
+            Spec("Access", U8),
+            Spec("NumCoordinatingNetworks", U8),
+        ])),
+    ]),
+    standard(MMType::CM_GET_BEACON.req(), &[Spec("NID", Nid)]),
+    standard(MMType::CM_GET_BEACON.cnf(), &[Spec("Beacon", Rest)]),
+    standard(MMType::CM_HFID.req(), &[
+        Spec("ReqType", Enum(HFID_REQ_TYPE)),
+        Spec("", Switch("ReqType", &[
+            (0x02, &[Spec("NID", Nid)]),
+            (0x03, &[Spec("HFID", Text(64))]),
+            (0x04, &[Spec("NID", Nid), Spec("HFID", Text(64))]),
+        ], &[])),
+    ]),
+    standard(MMType::CM_HFID.cnf(), &[
+        Spec("ReqType", Enum(HFID_REQ_TYPE)),
+        Spec("HFID", Text(64)),
+    ]),
+    standard(MMType::CM_MME_ERROR.ind(), &[
+        Spec("Reason", Enum(ERROR_TYPE)),
+        Spec("RxMMV", MessageVersion),
+        Spec("RxMMType", MessageType),
+        Spec("Offset", U16),
+    ]),
+    standard(MMType::CM_NW_STATS.req(), &[]),
+    standard(MMType::CM_NW_STATS.cnf(), &[
+        Spec("NumStations", U8),
+        Spec("Stations", List("NumStations", "Station", &[
+            Spec("Address", Address),
+            Spec("AvgPhyRateTx", U16),
+            Spec("AvgPhyRateRx", U16),
+        ])),
+    ]),
+    standard(MMType::CM_LINK_STATS.req(), &[
+        Spec("ReqType", Enum(LINK_STATS_REQ_TYPE)),
+        Spec("ReqID", U8),
+        Spec("NID", Nid),
+        Spec("LID", U8),
+        Spec("TLFlag", Enum(LINK_STATS_DIRECTION)),
+        Spec("MgmtFlag", U8),
+        Spec("Address", Address),
+    ]),
+    standard(MMType::CM_LINK_STATS.cnf(), &[
+        Spec("ReqID", U8),
+        Spec("Result", Enum(RESULT)),
+        Spec("Statistics", Rest),
+    ]),
+    standard(MMType::CM_ROUTE_INFO.req(), &[]),
+    standard(MMType::CM_ROUTE_INFO.cnf(), &[
+        Spec("NumRoutes", U8),
+        Spec("Routes", List("NumRoutes", "Route", &[
+            Spec("DestinationTEI", U8),
+            Spec("NextTEI", U8),
+            Spec("RouteDataRate", U16),
+            Spec("RouteNumHops", U8),
+        ])),
+    ]),
+    standard(MMType::CM_ROUTE_INFO.ind(), &[
+        Spec("NumRoutes", U8),
+        Spec("Routes", List("NumRoutes", "Route", &[
+            Spec("DestinationTEI", U8),
+            Spec("NextTEI", U8),
+            Spec("RouteDataRate", U16),
+            Spec("RouteNumHops", U8),
+        ])),
+    ]),
+    standard(MMType::CM_UNREACHABLE.ind(), &[
+        Spec("NetworkTime", U32),
+        Spec("NumStations", U8),
+        Spec("Stations", List("NumStations", "Station", &[Spec("TEI", U8)])),
+    ]),
+    standard(MMType::CM_MH_CONN_NEW.req(), &[
+        Spec("CID", U16),
+        Spec("FinalDestination", Address),
+        Spec("", Inline(CSPEC)),
+    ]),
+    standard(MMType::CM_MH_CONN_NEW.cnf(), &[
+        Spec("CID", U16),
+        Spec("Result", Enum(RESULT)),
+        Spec("ProposedCSPEC", Rest),
+    ]),
+    standard(MMType::CM_EXTENDEDTONEMASK.req(), &[]),
+    standard(MMType::CM_EXTENDEDTONEMASK.cnf(), &[
+        Spec("NumCarriers", U16),
+        Spec("ToneMask", Rest),
+    ]),
+    standard(MMType::CM_STA_IDENTIFY.req(), &[]),
+    standard(MMType::CM_STA_IDENTIFY.cnf(), STA_IDENTIFY),
+    standard(MMType::CM_STA_IDENTIFY.ind(), STA_IDENTIFY),
+    standard(MMType::CM_STA_IDENTIFY.rsp(), &[]),
+    standard(MMType::CM_SLAC_PARM.req(), &[
+        Spec("", Inline(SLAC_HEADER)),
+        Spec("RunID", Bytes(8)),
+    ]),
+    standard(MMType::CM_SLAC_PARM.cnf(), &[
+        Spec("MSoundTarget", Address),
+        Spec("NumSounds", U8),
+        Spec("TimeOut", U8),
+        Spec("RespType", Enum(RESP_TYPE)),
+        Spec("ForwardingSTA", Address),
+        Spec("", Inline(SLAC_HEADER)),
+        Spec("RunID", Bytes(8)),
+    ]),
+    standard(MMType::CM_START_ATTEN_CHAR.ind(), &[
+        Spec("", Inline(SLAC_HEADER)),
+        Spec("NumSounds", U8),
+        Spec("TimeOut", U8),
+        Spec("RespType", Enum(RESP_TYPE)),
+        Spec("ForwardingSTA", Address),
+        Spec("RunID", Bytes(8)),
+    ]),
+    standard(MMType::CM_ATTEN_CHAR.ind(), &[
+        Spec("", Inline(SLAC_HEADER)),
+        Spec("SourceAddress", Address),
+        Spec("RunID", Bytes(8)),
+        Spec("SourceID", Bytes(17)),
+        Spec("ResponseID", Bytes(17)),
+        Spec("NumSounds", U8),
+        Spec("NumGroups", U8),
+        Spec("Attenuation", List("NumGroups", "Group", &[Spec("dB", U8)])),
+    ]),
+    standard(MMType::CM_ATTEN_CHAR.rsp(), &[
+        Spec("", Inline(SLAC_HEADER)),
+        Spec("SourceAddress", Address),
+        Spec("RunID", Bytes(8)),
+        Spec("SourceID", Bytes(17)),
+        Spec("ResponseID", Bytes(17)),
+        Spec("Result", Enum(RESULT)),
+    ]),
+    standard(MMType::CM_PKCS_CERT.req(), &[
+        Spec("Target", Address),
+        Spec("NumCipherSuites", U8),
+        Spec("CipherSuites", List("NumCipherSuites", "CipherSuite", &[Spec("ID", U16)])),
+    ]),
+    standard(MMType::CM_PKCS_CERT.cnf(), &[
+        Spec("Target", Address),
+        Spec("Status", Enum(PKCS_STATUS)),
+        Spec("CipherSuite", U16),
+        Spec("CertificateLength", U16),
+        Spec("Certificate", Data("CertificateLength")),
+    ]),
+    standard(MMType::CM_PKCS_CERT.ind(), &[
+        Spec("Target", Address),
+        Spec("CipherSuite", U16),
+        Spec("CertificateLength", U16),
+        Spec("Certificate", Data("CertificateLength")),
+    ]),
+    standard(MMType::CM_PKCS_CERT.rsp(), &[
+        Spec("Target", Address),
+        Spec("Status", Enum(PKCS_STATUS)),
+    ]),
+    standard(MMType::CM_MNBC_SOUND.ind(), &[
+        Spec("", Inline(SLAC_HEADER)),
+        Spec("SenderID", Bytes(17)),
+        Spec("Count", U8),
+        Spec("RunID", Bytes(8)),
+        Spec("Reserved", Bytes(8)),
+        Spec("Random", Bytes(16)),
+    ]),
+    standard(MMType::CM_VALIDATE.req(), &[
+        Spec("SignalType", Enum(SIGNAL_TYPE)),
+        Spec("Timer", U8),
+        Spec("Result", Enum(VALIDATE_RESULT)),
+    ]),
+    standard(MMType::CM_VALIDATE.cnf(), &[
+        Spec("SignalType", Enum(SIGNAL_TYPE)),
+        Spec("ToggleNum", U8),
+        Spec("Result", Enum(VALIDATE_RESULT)),
+    ]),
+    standard(MMType::CM_SLAC_MATCH.req(), SLAC_MATCH),
+    standard(MMType::CM_SLAC_MATCH.cnf(), &[
+        Spec("", Inline(SLAC_MATCH)),
+        Spec("NID", Nid),
+        Spec("Reserved", U8),
+        Spec("NMK", Bytes(16)),
+    ]),
+    standard(MMType::CM_SLAC_USER_DATA.req(), &[
+        Spec("", Inline(SLAC_HEADER)),
+        Spec("UserData", Rest),
+    ]),
+    standard(MMType::CM_SLAC_USER_DATA.cnf(), &[
+        Spec("", Inline(SLAC_HEADER)),
+        Spec("UserData", Rest),
+    ]),
+    standard(MMType::CM_ATTEN_PROFILE.ind(), &[
+        Spec("PEV_MAC", Address),
+        Spec("NumGroups", U8),
+        Spec("Reserved", U8),
+        Spec("Attenuation", List("NumGroups", "Group", &[Spec("dB", U8)])),
+    ]),
+];
@@ -0,0 +1,198 @@
+//! Layouts of the vendor specific messages this library supports
+
+use super::names::*;
+use super::Kind::*;
+use super::*;
+
+const STATUS: &[(u8, &str)] = &[(0x00, "Success")];
+const DIRECTION: &[(u8, &str)] = &[(0x00, "TX"), (0x01, "RX"), (0x02, "TX+RX")];
+const MODULE_ID: &[(u8, &str)] = &[(0x01, "Firmware"), (0x02, "PIB"), (0x03, "Firmware+PIB")];
+const PUSH_BUTTON_ACTION: &[(u8, &str)] = &[(0x01, "Join"), (0x02, "Leave"), (0x03, "Status")];
+const PROPERTY: &[(u8, &str)] = &[
+    (0x1b, "NAME_A0"),
+    (0x1c, "NAME_B0"),
+    (0x25, "HFID_USER"),
+    (0x26, "NAME_B1"),
+];
+
+const fn qualcomm(mmtype: MMType, fields: &'static [Spec]) -> Layout {
+    Layout {
+        mmtype,
+        oui: OUI::QUALCOMM,
+        name: None,
+        fields,
+    }
+}
+
+const fn broadcom(mmtype: MMType, name: &'static str, fields: &'static [Spec]) -> Layout {
+    Layout {
+        mmtype,
+        oui: OUI::BROADCOM,
+        name: Some(name),
+        fields,
+    }
+}
+
+const TX_COUNTERS: Spec = Spec(
+    "TX",
+    Group(&[
+        Spec("MPDUAcked", U64),
+        Spec("MPDUCollisions", U64),
+        Spec("MPDUFailed", U64),
+        Spec("PBPassed", U64),
+        Spec("PBFailed", U64),
+    ]),
+);
+#[rustfmt::skip]
+const RX_COUNTERS: Spec = Spec(
+    "RX",
+    Group(&[
+        Spec("MPDUAcked", U64),
+        Spec("MPDUFailed", U64),
+        Spec("PBPassed", U64),
+        Spec("PBFailed", U64),
+        Spec("TurboBERPassed", U64),
+        Spec("TurboBERFailed", U64),
+        Spec("NumIntervals", U8),
+        Spec("Intervals", List("NumIntervals", "Interval", &[
+            Spec("PhyRate", U8),
+            Spec("PBPassed", U64),
+            Spec("PBFailed", U64),
+            Spec("TurboBERPassed", U64),
+            Spec("TurboBERFailed", U64),
+        ])),
+    ]),
+);
+
+const GET_PROPERTY: MMType = broadcom::GetPropertyRequest::MMTYPE;
+const SET_PROPERTY: MMType = broadcom::SetPropertyRequest::MMTYPE;
+
+#[rustfmt::skip]
+pub(super) static LAYOUTS: &[Layout] = &[
+    qualcomm(qualcomm::VS_SW_VER.req(), &[]),
+    qualcomm(qualcomm::VS_SW_VER.cnf(), &[
+        Spec("Status", Enum(STATUS)),
+        Spec("Device", Enum(qualcomm::DEVICE_NAMES)),
+        Spec("VersionLength", U8),
+        Spec("Version", TextData("VersionLength")),
+    ]),
+    qualcomm(qualcomm::VS_RS_DEV.req(), &[]),
+    qualcomm(qualcomm::VS_RS_DEV.cnf(), &[Spec("Status", Enum(STATUS))]),
+    qualcomm(qualcomm::VS_NW_INFO.req(), &[]),
+    qualcomm(qualcomm::VS_NW_INFO.cnf(), &[
+        Spec("NumNetworks", U8),
+        Spec("Networks", List("NumNetworks", "Network", &[
+            Spec("NID", Nid),
+            Spec("SNID", U8),
+            Spec("TEI", U8),
+            Spec("Role", Enum(STATION_ROLE)),
+            Spec("CCo", Address),
+            Spec("CCoTEI", U8),
+            Spec("NumStations", U8),
+            Spec("Stations", List("NumStations", "Station", &[
+                Spec("Address", Address),
+                Spec("TEI", U8),
+                Spec("BridgedAddress", Address),
+                Spec("TxRate", U8),
+                Spec("RxRate", U8),
+            ])),
+        ])),
+    ]),
+    qualcomm(qualcomm::VS_LNK_STATS.req(), &[
+        Spec("Clear", U8),
+        Spec("Direction", Enum(DIRECTION)),
+        Spec("LID", U8),
+        Spec("Peer", Address),
+    ]),
+    qualcomm(qualcomm::VS_LNK_STATS.cnf(), &[
+        Spec("Status", Enum(STATUS)),
+        Spec("", Switch("Status", &[(0x00, &[
+            Spec("Direction", Enum(DIRECTION)),
+            Spec("LID", U8),
+            Spec("TEI", U8),
+            Spec("", Switch("Direction", &[
+                (0x00, &[TX_COUNTERS]),
+                (0x01, &[RX_COUNTERS]),
+            ], &[TX_COUNTERS, RX_COUNTERS])),
+        ])], &[])),
+    ]),
+    qualcomm(qualcomm::VS_RD_MOD.req(), &[
+        Spec("Module", Enum(MODULE_ID)),
+        Spec("Reserved", U8),
+        Spec("Length", U16),
+        Spec("Offset", U32),
+    ]),
+    qualcomm(qualcomm::VS_RD_MOD.cnf(), &[
+        Spec("Status", Enum(STATUS)),
+        Spec("", Switch("Status", &[(0x00, &[
+            Spec("Reserved", Bytes(3)),
+            Spec("Module", Enum(MODULE_ID)),
+            Spec("Reserved", U8),
+            Spec("Length", U16),
+            Spec("Offset", U32),
+            Spec("Checksum", U32),
+            Spec("Data", Data("Length")),
+        ])], &[])),
+    ]),
+    qualcomm(qualcomm::VS_WR_MOD.req(), &[
+        Spec("Module", Enum(MODULE_ID)),
+        Spec("Reserved", U8),
+        Spec("Length", U16),
+        Spec("Offset", U32),
+        Spec("Checksum", U32),
+        Spec("Data", Data("Length")),
+    ]),
+    qualcomm(qualcomm::VS_WR_MOD.cnf(), &[
+        Spec("Status", Enum(STATUS)),
+        Spec("", Switch("Status", &[(0x00, &[
+            Spec("Module", Enum(MODULE_ID)),
+            Spec("Reserved", U8),
+            Spec("Length", U16),
+            Spec("Offset", U32),
+        ])], &[])),
+    ]),
+    qualcomm(qualcomm::VS_MOD_NVM.req(), &[
+        Spec("Module", Enum(MODULE_ID)),
+        Spec("Reserved", U8),
+    ]),
+    qualcomm(qualcomm::VS_MOD_NVM.cnf(), &[
+        Spec("Status", Enum(STATUS)),
+        Spec("Module", Enum(MODULE_ID)),
+    ]),
+    qualcomm(qualcomm::VS_PB_ENC.req(), &[Spec("Action", Enum(PUSH_BUTTON_ACTION))]),
+    qualcomm(qualcomm::VS_PB_ENC.cnf(), &[
+        Spec("Status", Enum(STATUS)),
+        Spec("AVLNStatus", U8),
+    ]),
+    qualcomm(qualcomm::VS_SET_KEY.req(), &[
+        Spec("EKS", U8),
+        Spec("NMK", Bytes(16)),
+        Spec("PEKS", U8),
+        Spec("RDA", Address),
+        Spec("DAK", Bytes(16)),
+    ]),
+    qualcomm(qualcomm::VS_SET_KEY.cnf(), &[Spec("Status", Enum(STATUS))]),
+    broadcom(GET_PROPERTY.req(), "GET_PROPERTY", &[
+        Spec("Seq", U8),
+        Spec("Property", Enum(PROPERTY)),
+    ]),
+    broadcom(GET_PROPERTY.cnf(), "GET_PROPERTY", &[
+        Spec("Seq", U8),
+        Spec("Count", U8),
+        Spec("RecordSize", U16),
+        Spec("Records", List("Count", "Record", &[Spec("Data", Data("RecordSize"))])),
+    ]),
+    broadcom(SET_PROPERTY.req(), "SET_PROPERTY", &[
+        Spec("Seq", U8),
+        Spec("Property", Enum(PROPERTY)),
+        Spec("Reserved", U8),
+        Spec("Count", U8),
+        Spec("RecordSize", U16),
+        Spec("Records", List("Count", "Record", &[Spec("Data", Data("RecordSize"))])),
+    ]),
+    broadcom(SET_PROPERTY.cnf(), "SET_PROPERTY", &[
+        Spec("Seq", U8),
+        Spec("Result", Enum(RESULT)),
+        Spec("Data", Rest),
+    ]),
+];
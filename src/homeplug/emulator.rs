@@ -0,0 +1,1108 @@
+//! Emulated HomePlug AV adapter for testing tools without real hardware
+
+extern crate std;
+
+use super::*;
+use crate::*;
+use core::convert::TryInto;
+use std::string::String;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::vec::Vec;
+
+/// Minimum size of an MME once the ethernet header is removed
+const MIN_FRAME_SIZE: usize = 46;
+
+/// Status returned when a vendor request can't be carried out
+const VENDOR_FAILURE: qualcomm::Status = qualcomm::Status(0x14);
+
+/// NID of the default "HomePlugAV" network key with simple security
+const DEFAULT_NID: [u8; 7] = [0xb0, 0xf2, 0xe6, 0x95, 0x66, 0x6b, 0x03];
+
+/// Configurable state of an emulated adapter
+#[derive(Debug, Clone)]
+pub struct DeviceState {
+    pub address: EtherAddr,
+    pub oui: OUI,
+    pub version: StationVersion,
+    pub tei: u8,
+    pub role: StationRole,
+    /// Network membership key (`CM_SET_KEY`/`VS_SET_KEY`)
+    pub nmk: [u8; 16],
+    /// Device access key, from the password on the adapter's label
+    pub dak: [u8; 16],
+    pub nid: [u8; 7],
+    pub snid: u8,
+    pub cco: EtherAddr,
+    pub hfid_manufacturer: String,
+    pub hfid_user: String,
+    pub hfid_network: String,
+    /// Firmware version string reported by Qualcomm `VS_SW_VER`
+    pub firmware: String,
+    /// Ethernet devices bridged by this adapter (`CM_BRG_INFO`)
+    pub bridged: Vec<EtherAddr>,
+    /// Other power-line stations this adapter can hear (`CC_DISCOVER_LIST`)
+    pub stations: Vec<EtherAddr>,
+    /// Average TX and RX PHY rates in Mbps to other stations (`CM_NW_STATS`)
+    pub phy_rates: Vec<(EtherAddr, u16, u16)>,
+    /// Broadcom property records (`GetPropertyRequest`/`SetPropertyRequest`)
+    pub properties: Vec<(broadcom::Property, Vec<u8>)>,
+    /// Qualcomm modules stored in flash (`VS_RD_MOD`)
+    pub modules: Vec<(qualcomm::ModuleId, Vec<u8>)>,
+    /// Qualcomm modules written to memory but not yet committed (`VS_WR_MOD`/`VS_MOD_NVM`)
+    pub staged: Vec<(qualcomm::ModuleId, Vec<u8>)>,
+    /// Network key and NID adopted when Simple Connect starts (`CM_SC_JOIN`/`VS_PB_ENC`), as
+    /// if another adapter's button had been pressed too
+    pub pairing: Option<([u8; 16], [u8; 7])>,
+    /// Transmit and receive counters of the links to other stations (`VS_LNK_STATS`)
+    pub links: Vec<(
+        EtherAddr,
+        qualcomm::TxLinkCounters,
+        qualcomm::RxLinkCounters,
+    )>,
+    /// Statistics of the links to other stations in the network `nid` (`CM_LINK_STATS`)
+    pub link_statistics: Vec<(EtherAddr, TxLinkStatistics, RxLinkStatistics)>,
+    /// Refuse every request that would change the device's settings
+    pub locked: bool,
+}
+impl DeviceState {
+    /// A HomePlug AV 1.1 Qualcomm/Atheros adapter (e.g. QCA7420)
+    pub fn qualcomm(address: EtherAddr) -> DeviceState {
+        DeviceState {
+            address,
+            oui: OUI::QUALCOMM,
+            version: StationVersion::HOMEPLUG_AV_1_1,
+            tei: 1,
+            role: StationRole::CCO,
+            nmk: NMK_HomePlugAV,
+            dak: DAK_HomePlugAV,
+            nid: DEFAULT_NID,
+            snid: 0,
+            cco: address,
+            hfid_manufacturer: String::from("Qualcomm Atheros QCA7420"),
+            hfid_user: String::new(),
+            hfid_network: String::new(),
+            firmware: String::from("MAC-QCA7420-1.1.0.727-02-20130125-CS"),
+            bridged: Vec::new(),
+            stations: Vec::new(),
+            phy_rates: Vec::new(),
+            properties: Vec::new(),
+            modules: Vec::new(),
+            staged: Vec::new(),
+            pairing: None,
+            links: Vec::new(),
+            link_statistics: Vec::new(),
+            locked: false,
+        }
+    }
+    /// A HomePlug AV 2.0 Broadcom adapter (e.g. BCM60355)
+    pub fn broadcom(address: EtherAddr) -> DeviceState {
+        let name = |s: &str| {
+            let mut record = [0u8; 64];
+            record.iter_mut().zip(s.bytes()).for_each(|(d, s)| *d = s);
+            record.to_vec()
+        };
+        DeviceState {
+            address,
+            oui: OUI::BROADCOM,
+            version: StationVersion::HOMEPLUG_AV_2_0,
+            tei: 2,
+            role: StationRole::STA,
+            nmk: NMK_HomePlugAV,
+            dak: DAK_HomePlugAV,
+            nid: DEFAULT_NID,
+            snid: 0,
+            cco: EtherAddr::NULL,
+            hfid_manufacturer: String::new(),
+            hfid_user: String::new(),
+            hfid_network: String::new(),
+            firmware: String::new(),
+            bridged: Vec::new(),
+            stations: Vec::new(),
+            phy_rates: Vec::new(),
+            properties: std::vec![
+                (broadcom::Property::NAME_A0, name("BCM60355 firmware")),
+                (broadcom::Property::NAME_B0, name("BCM60355")),
+                (broadcom::Property::HFID_USER, name("")),
+            ],
+            modules: Vec::new(),
+            staged: Vec::new(),
+            pairing: None,
+            links: Vec::new(),
+            link_statistics: Vec::new(),
+            locked: false,
+        }
+    }
+    /// A HomePlug GreenPHY ST/IoTecha modem, as used in EV chargers
+    pub fn st(address: EtherAddr) -> DeviceState {
+        DeviceState {
+            address,
+            oui: OUI::ST,
+            version: StationVersion::HOMEPLUG_AV_1_1,
+            tei: 3,
+            role: StationRole::STA,
+            nmk: NMK_HomePlugAV,
+            dak: DAK_HomePlugAV,
+            nid: DEFAULT_NID,
+            snid: 0,
+            cco: EtherAddr::NULL,
+            hfid_manufacturer: String::from("ST GreenPHY"),
+            hfid_user: String::new(),
+            hfid_network: String::new(),
+            firmware: String::new(),
+            bridged: Vec::new(),
+            stations: Vec::new(),
+            phy_rates: Vec::new(),
+            properties: Vec::new(),
+            modules: Vec::new(),
+            staged: Vec::new(),
+            pairing: None,
+            links: Vec::new(),
+            link_statistics: Vec::new(),
+            locked: false,
+        }
+    }
+    pub fn property(&self, property: broadcom::Property) -> Option<&[u8]> {
+        self.properties
+            .iter()
+            .find(|(p, _)| *p == property)
+            .map(|(_, data)| &data[..])
+    }
+    pub fn module(&self, module: qualcomm::ModuleId) -> Option<&[u8]> {
+        self.modules
+            .iter()
+            .find(|(m, _)| *m == module)
+            .map(|(_, data)| &data[..])
+    }
+}
+
+/// Answers management messages on behalf of a [`DeviceState`]
+#[derive(Debug, Clone)]
+pub struct Emulator {
+    state: Arc<Mutex<DeviceState>>,
+}
+impl Emulator {
+    pub fn new(state: DeviceState) -> Emulator {
+        Emulator {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Shared access to the emulated device state
+    pub fn state(&self) -> std::sync::MutexGuard<'_, DeviceState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Build the reply to `request` in `reply`, if this device would reply at all
+    ///
+    /// `reply` must hold at least [`ETHERNET_MTU`] bytes, there's no reply otherwise.
+    pub fn respond<'b>(
+        &self,
+        ethertype: EtherType,
+        request: &[u8],
+        reply: &'b mut [u8],
+    ) -> Option<&'b [u8]> {
+        if reply.len() < ETHERNET_MTU {
+            return None;
+        }
+        #[cfg(feature = "crypto")]
+        {
+            let encrypted = MMType::CM_ENCRYPTED_PAYLOAD.ind();
+            let msg = UnknownMessage::try_from(request);
+            if ethertype == EtherType::HOMEPLUG_AV && msg.is_ok_and(|m| m.mmtype() == encrypted) {
+                return self.respond_encrypted(request, reply);
+            }
+        }
+        self.respond_with_key(ethertype, request, reply, None)
+    }
+
+    /// Reply to a request that was encrypted with `key` (if any)
+    fn respond_with_key<'b>(
+        &self,
+        ethertype: EtherType,
+        request: &[u8],
+        reply: &'b mut [u8],
+        key: Option<PayloadKeySelect>,
+    ) -> Option<&'b [u8]> {
+        if request.len() < 3 {
+            return None;
+        }
+        let msg = UnknownMessage(request);
+        let mmv = msg.mmv();
+        let mmtype = msg.mmtype();
+        if header_size(mmv, mmtype) > request.len() || mmtype.code() != MMTypeCode::REQ {
+            return None;
+        }
+        let payload = msg.payload();
+        // Offset of the payload, which handler errors are relative to
+        let fields = request.len() - payload.len();
+        let mut state = self.state.lock().unwrap();
+        if ethertype == EtherType::MEDIAXTREAM && state.oui != OUI::BROADCOM {
+            // Only Broadcom/Gigle devices speak the Mediaxtream protocol
+            return None;
+        }
+        let cnf = mmtype.cnf();
+
+        let size = match (ethertype, mmtype, msg.oui()) {
+            (EtherType::HOMEPLUG_AV, MMType::CC_DISCOVER_LIST, _) => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                header + discover_list(&state, &mut reply[header..])
+            }
+            (EtherType::HOMEPLUG_AV, MMType::CM_STA_CAP, _) => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                header + station_capabilities(&state, &mut reply[header..])
+            }
+            (EtherType::HOMEPLUG_AV, MMType::CM_BRG_INFO, _) => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                header + bridge_info(&state, &mut reply[header..])
+            }
+            (EtherType::HOMEPLUG_AV, MMType::CM_NW_INFO, _) => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                header + network_info(&state, &mut reply[header..])
+            }
+            (EtherType::HOMEPLUG_AV, MMType::CM_NW_STATS, _) => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                header + network_stats(&state, &mut reply[header..])
+            }
+            (EtherType::HOMEPLUG_AV, MMType::CM_SET_KEY, _) => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                match set_key(&mut state, payload, &mut reply[header..], key) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::HOMEPLUG_AV, MMType::CM_LINK_STATS, _) => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                match link_stats(&state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::HOMEPLUG_AV, MMType::CM_SC_JOIN, _) if state.locked => mme_error(
+                reply,
+                request,
+                ErrorType::UNSUPPORTED_FEATURE,
+                offset::MMTYPE_L,
+            ),
+            (EtherType::HOMEPLUG_AV, MMType::CM_SC_JOIN, _) => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                header + simple_connect(&mut state, &mut reply[header..])
+            }
+            // Broadcom devices only expose their names through vendor properties
+            (EtherType::HOMEPLUG_AV, MMType::CM_HFID, _) if state.oui != OUI::BROADCOM => {
+                let header = set_header(reply, mmv, cnf, OUI::default());
+                match hfid(&mut state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::MEDIAXTREAM, MMType(0xa05c), OUI::BROADCOM)
+                if state.oui == OUI::BROADCOM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::BROADCOM);
+                match get_property(&state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::MEDIAXTREAM, MMType(0xa058), OUI::BROADCOM)
+                if state.oui == OUI::BROADCOM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::BROADCOM);
+                match set_property(&mut state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::HOMEPLUG_AV, qualcomm::VS_SW_VER, OUI::QUALCOMM)
+                if state.oui == OUI::QUALCOMM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::QUALCOMM);
+                header + software_version(&state, &mut reply[header..])
+            }
+            (EtherType::HOMEPLUG_AV, qualcomm::VS_RS_DEV, OUI::QUALCOMM)
+                if state.oui == OUI::QUALCOMM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::QUALCOMM);
+                reply[header] = qualcomm::Status::SUCCESS.0;
+                header + 1
+            }
+            (EtherType::HOMEPLUG_AV, qualcomm::VS_PB_ENC, OUI::QUALCOMM)
+                if state.oui == OUI::QUALCOMM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::QUALCOMM);
+                match push_button(&mut state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::HOMEPLUG_AV, qualcomm::VS_SET_KEY, OUI::QUALCOMM)
+                if state.oui == OUI::QUALCOMM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::QUALCOMM);
+                match set_nmk(&mut state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::HOMEPLUG_AV, qualcomm::VS_LNK_STATS, OUI::QUALCOMM)
+                if state.oui == OUI::QUALCOMM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::QUALCOMM);
+                match link_counters(&state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::HOMEPLUG_AV, qualcomm::VS_RD_MOD, OUI::QUALCOMM)
+                if state.oui == OUI::QUALCOMM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::QUALCOMM);
+                match read_module(&state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::HOMEPLUG_AV, qualcomm::VS_WR_MOD, OUI::QUALCOMM)
+                if state.oui == OUI::QUALCOMM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::QUALCOMM);
+                match write_module(&mut state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            (EtherType::HOMEPLUG_AV, qualcomm::VS_MOD_NVM, OUI::QUALCOMM)
+                if state.oui == OUI::QUALCOMM =>
+            {
+                let header = set_header(reply, mmv, cnf, OUI::QUALCOMM);
+                match commit_module(&mut state, payload, &mut reply[header..]) {
+                    Ok(size) => header + size,
+                    Err(field) => {
+                        mme_error(reply, request, ErrorType::INVALID_FIELDS, fields + field)
+                    }
+                }
+            }
+            _ => mme_error(reply, request, ErrorType::NOT_SUPPORTED, offset::MMTYPE_L),
+        };
+
+        Some(padded(reply, size))
+    }
+
+    /// Decrypt a `CM_ENCRYPTED_PAYLOAD.IND`, and encrypt the reply with the same key
+    #[cfg(feature = "crypto")]
+    fn respond_encrypted<'b>(&self, request: &[u8], reply: &'b mut [u8]) -> Option<&'b [u8]> {
+        let ind = EncryptedPayloadInd::try_from(request).ok()?;
+        let key = match ind.peks() {
+            PayloadKeySelect::DAK => Some(self.state().dak),
+            PayloadKeySelect::NMK => Some(self.state().nmk),
+            _ => None,
+        };
+        let mut decrypted = [0; 1500];
+        let inner = key.and_then(|key| ind.decrypt(key, &mut decrypted).ok());
+        let (key, inner) = match (key, inner) {
+            (Some(key), Some(inner)) => (key, inner),
+            _ => {
+                let rsp = EncryptedPayloadResponse {
+                    result: 1,
+                    pid: ind.pid(),
+                    prn: ind.prn(),
+                };
+                let size = rsp.encode(reply).ok()?.len();
+                return Some(padded(reply, size));
+            }
+        };
+        let mut inner_reply = [0; 1500];
+        let peks = Some(ind.peks());
+        let inner_reply =
+            self.respond_with_key(EtherType::HOMEPLUG_AV, inner, &mut inner_reply, peks)?;
+        let indication = EncryptedPayloadIndication {
+            peks: ind.peks(),
+            avln_status: 0,
+            pid: ind.pid(),
+            prn: ind.prn(),
+            pmn: ind.pmn().wrapping_add(1),
+            iv: ind.iv(),
+            key,
+            random: [0; 16],
+            message: inner_reply,
+        };
+        let size = indication.encode(reply).ok()?.len();
+        Some(padded(reply, size))
+    }
+
+    /// Wait for a single request on `socket` and answer it
+    ///
+    /// Returns `false` if nothing was received within `timeout`.
+    pub fn serve<T: EtherSocket>(
+        &self,
+        socket: &mut T,
+        ethertype: EtherType,
+        timeout: Option<Duration>,
+    ) -> Result<bool, T::Error> {
+        let mut buffer = [0; 1500];
+        let mut reply = [0; 1500];
+        let (addr, data) = match socket.recvfrom(&mut buffer, timeout)? {
+            Some(received) => received,
+            None => return Ok(false),
+        };
+        if let Some(reply) = self.respond(ethertype, data, &mut reply) {
+            socket.sendto(addr, reply)?;
+        }
+        Ok(true)
+    }
+
+    /// Answer requests on `interface` from background threads until the handle is dropped
+    pub fn spawn<I: EtherInterface>(&self, interface: &I) -> Result<EmulatorHandle, I::Error> {
+        let running = Arc::new(AtomicBool::new(true));
+        let mut threads = Vec::new();
+        for ethertype in [EtherType::HOMEPLUG_AV, EtherType::MEDIAXTREAM] {
+            let mut socket = interface.open(ethertype)?;
+            let emulator = self.clone();
+            let running = running.clone();
+            threads.push(std::thread::spawn(move || {
+                let timeout = Some(Duration::from_millis(10));
+                while running.load(Ordering::Relaxed) {
+                    if let Err(err) = emulator.serve(&mut socket, ethertype, timeout) {
+                        log::warn!("[{:?}] Emulator stopped: {}", emulator.state().address, err);
+                        break;
+                    }
+                }
+            }));
+        }
+        Ok(EmulatorHandle { running, threads })
+    }
+}
+
+/// Background emulator threads, stopped when dropped
+pub struct EmulatorHandle {
+    running: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+impl Drop for EmulatorHandle {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The `size` byte reply, padded to the minimum frame size
+fn padded(reply: &mut [u8], size: usize) -> &[u8] {
+    let padded = size.max(MIN_FRAME_SIZE);
+    reply[size..padded].iter_mut().for_each(|b| *b = 0);
+    &reply[..padded]
+}
+
+/// `CM_MME_ERROR.IND` about the field at `offset` in `request`
+fn mme_error(reply: &mut [u8], request: &[u8], error: ErrorType, offset: usize) -> usize {
+    let msg = UnknownMessage(request);
+    let mmtype = MMType::CM_MME_ERROR.ind();
+    let header = set_header(reply, MMV::HOMEPLUG_AV_1_1, mmtype, OUI::default());
+    let payload = &mut reply[header..];
+    payload[0] = error.0;
+    payload[1] = msg.mmv().0;
+    payload[2..4].copy_from_slice(&msg.mmtype().to_le_bytes());
+    payload[4..6].copy_from_slice(&(offset as u16).to_le_bytes());
+    header + 6
+}
+
+fn discover_list(state: &DeviceState, payload: &mut [u8]) -> usize {
+    // Room for the station entries and the trailing network count
+    let count = state.stations.len().min((payload.len() - 2) / 12).min(255);
+    let stations = &state.stations[..count];
+    payload[0] = stations.len() as u8;
+    let mut size = 1;
+    for (i, addr) in stations.iter().enumerate() {
+        let entry = &mut payload[size..size + 12];
+        entry[0..6].copy_from_slice(&addr.0);
+        entry[6] = state.tei.wrapping_add(i as u8 + 1); // TEI
+        entry[7] = 1; // Same network
+        entry[8] = state.snid;
+        entry[9] = 0; // Flags
+        entry[10] = 0x03; // Signal level >-20dB
+        entry[11] = 0x80; // Average BLE
+        size += 12;
+    }
+    payload[size] = 0; // No other networks
+    size + 1
+}
+
+fn station_capabilities(state: &DeviceState, payload: &mut [u8]) -> usize {
+    let data = &mut payload[..26];
+    data.iter_mut().for_each(|b| *b = 0);
+    data[0] = state.version.0;
+    data[1..7].copy_from_slice(&state.address.0);
+    data[7..10].copy_from_slice(&state.oui.0);
+    data[10] = 1; // AutoConnect
+    data[12] = 1; // CCo level
+    data[18..20].copy_from_slice(&2644u16.to_le_bytes()); // Max frame length
+    data[20] = 1; // HomePlug 1.1 interoperability
+    data[23] = 1; // Burst=SACK
+    26
+}
+
+fn bridge_info(state: &DeviceState, payload: &mut [u8]) -> usize {
+    let count = state.bridged.len().min((payload.len() - 3) / 6).min(255);
+    let bridged = &state.bridged[..count];
+    payload[0] = !bridged.is_empty() as u8;
+    payload[1] = state.tei;
+    payload[2] = bridged.len() as u8;
+    for (dest, addr) in payload[3..].chunks_exact_mut(6).zip(bridged) {
+        dest.copy_from_slice(&addr.0);
+    }
+    3 + bridged.len() * 6
+}
+
+fn network_stats(state: &DeviceState, payload: &mut [u8]) -> usize {
+    let count = state.phy_rates.len().min((payload.len() - 1) / 10).min(255);
+    let rates = &state.phy_rates[..count];
+    payload[0] = rates.len() as u8;
+    for (entry, (addr, tx, rx)) in payload[1..].chunks_exact_mut(10).zip(rates) {
+        entry[0..6].copy_from_slice(&addr.0);
+        entry[6..8].copy_from_slice(&tx.to_le_bytes());
+        entry[8..10].copy_from_slice(&rx.to_le_bytes());
+    }
+    1 + rates.len() * 10
+}
+
+fn network_info(state: &DeviceState, payload: &mut [u8]) -> usize {
+    payload[0] = 1;
+    let entry = &mut payload[1..19];
+    entry[0..7].copy_from_slice(&state.nid);
+    entry[7] = state.snid;
+    entry[8] = state.tei;
+    entry[9] = state.role.0;
+    entry[10..16].copy_from_slice(&state.cco.0);
+    entry[16] = 0; // Access
+    entry[17] = 0; // Coordinating networks
+    19
+}
+
+fn software_version(state: &DeviceState, payload: &mut [u8]) -> usize {
+    let version = &state.firmware.as_bytes()[..state.firmware.len().min(253)];
+    payload[0] = qualcomm::Status::SUCCESS.0;
+    payload[1] = qualcomm::DeviceId::QCA7420.0;
+    payload[2] = version.len() as u8 + 1;
+    payload[3..3 + version.len()].copy_from_slice(version);
+    payload[3 + version.len()] = 0;
+    4 + version.len()
+}
+
+/// Byte at `offset` in a request payload, or the offset as the error if the payload is too short
+///
+/// Request handlers return the size of their reply, or the offset of the offending field.
+fn byte(request: &[u8], offset: usize) -> Result<u8, usize> {
+    request.get(offset).copied().ok_or(offset)
+}
+
+/// `length` bytes at `offset` in a request payload, or the offset as the error
+fn bytes(request: &[u8], offset: usize, length: usize) -> Result<&[u8], usize> {
+    request.get(offset..offset + length).ok_or(offset)
+}
+
+/// `N` byte field at `offset` in a request payload, or the offset as the error
+fn field<const N: usize>(request: &[u8], offset: usize) -> Result<[u8; N], usize> {
+    request
+        .get(offset..offset + N)
+        .and_then(|field| field.try_into().ok())
+        .ok_or(offset)
+}
+
+fn hfid(state: &mut DeviceState, request: &[u8], payload: &mut [u8]) -> Result<usize, usize> {
+    fn copy_hfid(dest: &mut [u8], s: &str) {
+        dest.iter_mut().for_each(|b| *b = 0);
+        dest.iter_mut().zip(s.bytes()).for_each(|(d, s)| *d = s);
+    }
+    fn read_hfid(bytes: &[u8]) -> String {
+        String::from_utf8_lossy(bytes).trim_end_matches('\0').into()
+    }
+    let req_type = HFIDReqType(byte(request, 0)?);
+    let (result, hfid) = payload.split_at_mut(1);
+    let hfid = &mut hfid[..64];
+    result[0] = req_type.0;
+    match req_type {
+        HFIDReqType::GET_MFG => copy_hfid(hfid, &state.hfid_manufacturer),
+        HFIDReqType::GET_USR => copy_hfid(hfid, &state.hfid_user),
+        HFIDReqType::GET_NET => copy_hfid(hfid, &state.hfid_network),
+        HFIDReqType::SET_USR | HFIDReqType::SET_NET if state.locked => {
+            result[0] = HFIDReqType::FAILURE.0;
+            hfid.iter_mut().for_each(|b| *b = 0);
+        }
+        HFIDReqType::SET_USR => {
+            state.hfid_user = read_hfid(&field::<64>(request, 1)?);
+            copy_hfid(hfid, &state.hfid_user);
+        }
+        HFIDReqType::SET_NET => {
+            state.hfid_network = read_hfid(&field::<64>(request, 7)?);
+            copy_hfid(hfid, &state.hfid_network);
+        }
+        _ => return Err(0),
+    }
+    Ok(65)
+}
+
+/// Keys are accepted from the host, or from anyone who knows the DAK
+fn set_key(
+    state: &mut DeviceState,
+    request: &[u8],
+    payload: &mut [u8],
+    key: Option<PayloadKeySelect>,
+) -> Result<usize, usize> {
+    let key_type = KeyType(byte(request, 0)?);
+    let pid = ProtocolId(byte(request, 9)?);
+    let nid = field(request, 14)?;
+    let success = key_type == KeyType::NMK
+        && !state.locked
+        && match pid {
+            ProtocolId::HLE => true,
+            ProtocolId::PROVIDE_NMK_DAK => key == Some(PayloadKeySelect::DAK),
+            _ => false,
+        };
+    if success {
+        state.nmk = field(request, 22)?;
+        state.nid = nid;
+    }
+    payload[0] = !success as u8;
+    payload[1..14].copy_from_slice(&field::<13>(request, 1)?);
+    Ok(14)
+}
+
+fn set_nmk(state: &mut DeviceState, request: &[u8], payload: &mut [u8]) -> Result<usize, usize> {
+    let nmk: [u8; 16] = field(request, 1)?;
+    let peks = byte(request, 17)?;
+    // Only keys for the local device are supported
+    if peks != EKS_NONE || state.locked {
+        payload[0] = VENDOR_FAILURE.0;
+        return Ok(1);
+    }
+    state.nmk = nmk;
+    #[cfg(feature = "sha2")]
+    {
+        state.nid = generate_nid(nmk, SecurityLevel::SIMPLE);
+    }
+    payload[0] = qualcomm::Status::SUCCESS.0;
+    Ok(1)
+}
+
+fn simple_connect(state: &mut DeviceState, payload: &mut [u8]) -> usize {
+    if let Some((nmk, nid)) = state.pairing.take() {
+        state.nmk = nmk;
+        state.nid = nid;
+    }
+    payload[..7].copy_from_slice(&state.nid);
+    payload[7..14].iter_mut().for_each(|b| *b = 0);
+    14
+}
+
+fn push_button(
+    state: &mut DeviceState,
+    request: &[u8],
+    payload: &mut [u8],
+) -> Result<usize, usize> {
+    let action = qualcomm::PushButtonAction(byte(request, 0)?);
+    if state.locked {
+        payload[0] = VENDOR_FAILURE.0;
+        return Ok(1);
+    }
+    if action == qualcomm::PushButtonAction::JOIN {
+        simple_connect(state, payload);
+    }
+    payload[0] = qualcomm::Status::SUCCESS.0;
+    payload[1] = 0;
+    Ok(2)
+}
+
+fn link_counters(state: &DeviceState, request: &[u8], payload: &mut [u8]) -> Result<usize, usize> {
+    use qualcomm::Direction;
+    let direction = Direction(byte(request, 1)?);
+    let lid = byte(request, 2)?;
+    let peer = EtherAddr(field(request, 3)?);
+    let link = state.links.iter().position(|(addr, _, _)| *addr == peer);
+    let (index, tx, rx) = match link {
+        Some(index) if direction.0 <= Direction::BOTH.0 => {
+            let (_, tx, rx) = state.links[index];
+            (index, tx, rx)
+        }
+        _ => {
+            payload[0] = VENDOR_FAILURE.0;
+            return Ok(1);
+        }
+    };
+    payload[0] = qualcomm::Status::SUCCESS.0;
+    payload[1] = direction.0;
+    payload[2] = lid;
+    payload[3] = state.tei.wrapping_add(index as u8 + 1); // Peer TEI
+    let mut counters = Vec::new();
+    if direction != Direction::RX {
+        counters.extend_from_slice(&[tx.mpdu_acked, tx.mpdu_collisions, tx.mpdu_failed]);
+        counters.extend_from_slice(&[tx.pb_passed, tx.pb_failed]);
+    }
+    if direction != Direction::TX {
+        counters.extend_from_slice(&[rx.mpdu_acked, rx.mpdu_failed, rx.pb_passed, rx.pb_failed]);
+        counters.extend_from_slice(&[rx.turbo_ber_passed, rx.turbo_ber_failed]);
+    }
+    for (i, counter) in counters.iter().enumerate() {
+        payload[4 + i * 8..12 + i * 8].copy_from_slice(&counter.to_le_bytes());
+    }
+    let mut size = 4 + counters.len() * 8;
+    if direction != Direction::TX {
+        payload[size] = 0; // No tone map intervals
+        size += 1;
+    }
+    Ok(size)
+}
+
+fn link_stats(state: &DeviceState, request: &[u8], payload: &mut [u8]) -> Result<usize, usize> {
+    let req_id = byte(request, 1)?;
+    let nid: [u8; 7] = field(request, 2)?;
+    let receive = byte(request, 10)? != 0;
+    let peer = EtherAddr(field(request, 12)?);
+    payload[0] = req_id;
+    let link = state
+        .link_statistics
+        .iter()
+        .find(|(addr, _, _)| *addr == peer);
+    let (tx, rx) = match link {
+        Some((_, tx, rx)) if nid == state.nid => (tx, rx),
+        _ => {
+            payload[1] = 1; // Failure
+            return Ok(2);
+        }
+    };
+    payload[1] = 0; // Success
+    let stats = &mut payload[2..];
+    let size = if receive {
+        stats[0..4].copy_from_slice(&rx.msdus.to_le_bytes());
+        stats[4..12].copy_from_slice(&rx.octets.to_le_bytes());
+        let counters = [
+            rx.segments_received,
+            rx.segments_missed,
+            rx.pbs,
+            rx.mpdus,
+            rx.fec_errors,
+        ];
+        for (dest, counter) in stats[12..32].chunks_exact_mut(4).zip(counters) {
+            dest.copy_from_slice(&counter.to_le_bytes());
+        }
+        32
+    } else {
+        stats[0..4].copy_from_slice(&tx.msdus.to_le_bytes());
+        stats[4..12].copy_from_slice(&tx.octets.to_le_bytes());
+        let counters = [
+            tx.segments_generated,
+            tx.segments_delivered,
+            tx.segments_dropped,
+            tx.pbs,
+            tx.mpdus,
+            tx.mpdus_acked,
+            tx.collisions,
+            tx.pb_errors,
+        ];
+        for (dest, counter) in stats[12..44].chunks_exact_mut(4).zip(counters) {
+            dest.copy_from_slice(&counter.to_le_bytes());
+        }
+        44
+    };
+    stats[size..size + 4].iter_mut().for_each(|b| *b = 0); // No latency bins
+    Ok(2 + size + 4)
+}
+
+fn get_property(state: &DeviceState, request: &[u8], payload: &mut [u8]) -> Result<usize, usize> {
+    let seq = byte(request, 0)?;
+    let property = broadcom::Property(byte(request, 1)?);
+    let record = state.property(property).unwrap_or(&[]);
+    let record = &record[..record.len().min(payload.len() - 4)];
+    payload[0] = seq;
+    payload[1] = !record.is_empty() as u8;
+    payload[2..4].copy_from_slice(&(record.len() as u16).to_le_bytes());
+    payload[4..4 + record.len()].copy_from_slice(record);
+    Ok(4 + record.len())
+}
+
+fn set_property(
+    state: &mut DeviceState,
+    request: &[u8],
+    payload: &mut [u8],
+) -> Result<usize, usize> {
+    let seq = byte(request, 0)?;
+    let property = broadcom::Property(byte(request, 1)?);
+    let size = u16::from_le_bytes([byte(request, 4)?, byte(request, 5)?]) as usize;
+    let data = bytes(request, 6, size)?.to_vec();
+    payload[0] = seq;
+    if state.locked {
+        payload[1] = 1; // Failure
+        return Ok(2);
+    }
+    match state.properties.iter_mut().find(|(p, _)| *p == property) {
+        Some((_, record)) => *record = data,
+        None => state.properties.push((property, data)),
+    }
+    payload[1] = 0; // Success
+    Ok(2)
+}
+
+fn read_module(state: &DeviceState, request: &[u8], payload: &mut [u8]) -> Result<usize, usize> {
+    let module = qualcomm::ModuleId(byte(request, 0)?);
+    let length = u16::from_le_bytes([byte(request, 2)?, byte(request, 3)?]) as usize;
+    let offset = u32::from_le_bytes(field(request, 4)?) as usize;
+    payload[..16].iter_mut().for_each(|b| *b = 0);
+    let image = state.module(module).unwrap_or(&[]);
+    if length == 0 || length > qualcomm::MODULE_CHUNK_SIZE || offset >= image.len() {
+        payload[0] = VENDOR_FAILURE.0;
+        return Ok(1);
+    }
+    let data = &image[offset..image.len().min(offset + length)];
+    payload[4] = module.0;
+    payload[6..8].copy_from_slice(&(data.len() as u16).to_le_bytes());
+    payload[8..12].copy_from_slice(&(offset as u32).to_le_bytes());
+    payload[12..16].copy_from_slice(&qualcomm::checksum32(data).to_le_bytes());
+    payload[16..16 + data.len()].copy_from_slice(data);
+    Ok(16 + data.len())
+}
+
+fn write_module(
+    state: &mut DeviceState,
+    request: &[u8],
+    payload: &mut [u8],
+) -> Result<usize, usize> {
+    let module = qualcomm::ModuleId(byte(request, 0)?);
+    let length = u16::from_le_bytes([byte(request, 2)?, byte(request, 3)?]) as usize;
+    let offset = u32::from_le_bytes(field(request, 4)?) as usize;
+    let checksum = u32::from_le_bytes(field(request, 8)?);
+    let data = bytes(request, 12, length)?;
+    payload[..9].iter_mut().for_each(|b| *b = 0);
+    if length > qualcomm::MODULE_CHUNK_SIZE || qualcomm::checksum32(data) != checksum {
+        payload[0] = VENDOR_FAILURE.0;
+        return Ok(1);
+    }
+    let staged = match state.staged.iter_mut().position(|(m, _)| *m == module) {
+        Some(index) => &mut state.staged[index].1,
+        None => {
+            state.staged.push((module, Vec::new()));
+            &mut state.staged.last_mut().unwrap().1
+        }
+    };
+    if staged.len() < offset + length {
+        staged.resize(offset + length, 0);
+    }
+    staged[offset..offset + length].copy_from_slice(data);
+    payload[1] = module.0;
+    payload[3..5].copy_from_slice(&(length as u16).to_le_bytes());
+    payload[5..9].copy_from_slice(&(offset as u32).to_le_bytes());
+    Ok(9)
+}
+
+fn commit_module(
+    state: &mut DeviceState,
+    request: &[u8],
+    payload: &mut [u8],
+) -> Result<usize, usize> {
+    let module = qualcomm::ModuleId(byte(request, 0)?);
+    let mut status = VENDOR_FAILURE;
+    for part in [qualcomm::ModuleId::FIRMWARE, qualcomm::ModuleId::PIB] {
+        if module.0 & part.0 == 0 {
+            continue;
+        }
+        if let Some(index) = state.staged.iter().position(|(m, _)| *m == part) {
+            let (_, image) = state.staged.remove(index);
+            state.modules.retain(|(m, _)| *m != part);
+            state.modules.push((part, image));
+            status = qualcomm::Status::SUCCESS;
+        }
+    }
+    payload[0] = status.0;
+    payload[1] = module.0;
+    Ok(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::*;
+    use std::collections::HashSet;
+
+    const HOST: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x01]);
+    const QCA: EtherAddr = EtherAddr([0x00, 0xb0, 0x52, 0x11, 0x22, 0x33]);
+    const BCM: EtherAddr = EtherAddr([0x00, 0x1f, 0x84, 0x44, 0x55, 0x66]);
+
+    #[test]
+    fn network() {
+        let wire = MemoryWire::new();
+        let mut qca = DeviceState::qualcomm(QCA);
+        qca.hfid_user = "Upstairs".into();
+        qca.bridged = std::vec![EtherAddr([0x02, 0, 0, 0, 0, 0x99])];
+        qca.stations = std::vec![BCM];
+        let qca = Emulator::new(qca);
+        let mut bcm = DeviceState::broadcom(BCM);
+        bcm.stations = std::vec![QCA];
+        let bcm = Emulator::new(bcm);
+        let _qca = qca.spawn(&wire.interface("qca", QCA)).unwrap();
+        let _bcm = bcm.spawn(&wire.interface("bcm", BCM)).unwrap();
+
+        let host = wire.interface("host", HOST);
+        let mut s = host.open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut found = HashSet::new();
+        discover_devices(&mut s, |addr, msg| {
+            found.insert(addr);
+            found.extend(msg.stations().map(|station| station.addr()));
+        })
+        .unwrap();
+        assert_eq!(found, [QCA, BCM].iter().copied().collect());
+
+        let mut b = [0; 1500];
+        let m = send_request(&mut s, &mut b, QCA, StationCapabilitiesRequest).unwrap();
+        assert_eq!(m.unwrap().oui(), OUI::QUALCOMM);
+        let m = send_request(&mut s, &mut b, QCA, BridgeInfoRequest).unwrap();
+        let bridge = m.unwrap().to_owned();
+        let m = send_request(&mut s, &mut b, QCA, HFIDRequest::GetUsr).unwrap();
+        let hfid = m.unwrap().to_owned();
+        // Owned copies outlive the receive buffer
+        assert_eq!(bridge.destinations, [EtherAddr([0x02, 0, 0, 0, 0, 0x99])]);
+        assert_eq!(hfid.hfid, "Upstairs");
+
+        let m = send_request(&mut s, &mut b, QCA, qualcomm::SoftwareVersionRequest).unwrap();
+        let m = m.unwrap();
+        assert_eq!(m.device(), qualcomm::DeviceId::QCA7420);
+        assert_eq!(m.version(), "MAC-QCA7420-1.1.0.727-02-20130125-CS");
+
+        // Broadcom adapters reject the standard HFID request
+        let m = send_request(&mut s, &mut b, BCM, HFIDRequest::GetUsr).unwrap();
+        assert!(m.is_none());
+
+        let mut xs = host.open(EtherType::MEDIAXTREAM).unwrap();
+        let mut data = [0; 64];
+        data[..6].copy_from_slice(b"Lounge");
+        let request = broadcom::SetPropertyRequest {
+            seq: 1,
+            property: broadcom::Property::HFID_USER,
+            data,
+        };
+        assert!(send_request(&mut xs, &mut b, BCM, request)
+            .unwrap()
+            .is_some());
+        let request = broadcom::GetPropertyRequest {
+            seq: 2,
+            property: broadcom::Property::HFID_USER,
+        };
+        let m = send_request(&mut xs, &mut b, BCM, request)
+            .unwrap()
+            .unwrap();
+        assert_eq!(m.first().unwrap(), &data[..]);
+    }
+
+    #[test]
+    fn errors() {
+        let emulator = Emulator::new(DeviceState::qualcomm(QCA));
+        let mut buffer = [0; 128];
+        let request = HFIDRequest::SetUsr { hfid: [b'x'; 64] };
+        let request = request.encode(&mut buffer).unwrap();
+        let header = request.len() - UnknownMessage(request).payload().len();
+        let mut reply = [0; 1500];
+
+        // Replies need room for a whole frame
+        let small = &mut reply[..100];
+        assert!(emulator
+            .respond(EtherType::HOMEPLUG_AV, request, small)
+            .is_none());
+
+        // Errors point at the missing or unsupported field of the request
+        let truncated = &request[..header + 10];
+        let m = emulator.respond(EtherType::HOMEPLUG_AV, truncated, &mut reply);
+        let error = MMEError::try_from(m.unwrap()).unwrap();
+        assert_eq!(error.error(), ErrorType::INVALID_FIELDS);
+        assert_eq!(error.error_offset(), header + 1);
+        let unknown = [0x01, 0xfc, 0xa0, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let m = emulator.respond(EtherType::HOMEPLUG_AV, &unknown, &mut reply);
+        let error = MMEError::try_from(m.unwrap()).unwrap();
+        assert_eq!(error.error(), ErrorType::NOT_SUPPORTED);
+        assert_eq!(error.error_offset(), 1);
+    }
+
+    #[test]
+    fn oversized() {
+        let wire = MemoryWire::new();
+        let mut qca = DeviceState::qualcomm(QCA);
+        let addrs = (0..300u16).map(|i| EtherAddr([0x02, 0, 0, 0, (i >> 8) as u8, i as u8]));
+        qca.stations = addrs.clone().collect();
+        qca.bridged = addrs.collect();
+        let mut bcm = DeviceState::broadcom(BCM);
+        let record = std::vec![0x55; 2000];
+        bcm.properties = std::vec![(broadcom::Property::HFID_USER, record)];
+        let _qca = Emulator::new(qca)
+            .spawn(&wire.interface("qca", QCA))
+            .unwrap();
+        let _bcm = Emulator::new(bcm)
+            .spawn(&wire.interface("bcm", BCM))
+            .unwrap();
+        let host = wire.interface("host", HOST);
+        let mut s = host.open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut b = [0; 1500];
+
+        // Replies are cut short to fit in a frame, rather than overrunning it
+        let m = send_request(&mut s, &mut b, QCA, DiscoverListRequest).unwrap();
+        assert_eq!(m.unwrap().stations().len(), 124);
+        let m = send_request(&mut s, &mut b, QCA, BridgeInfoRequest).unwrap();
+        assert_eq!(m.unwrap().destinations().len(), 248);
+
+        let mut xs = host.open(EtherType::MEDIAXTREAM).unwrap();
+        let request = broadcom::GetPropertyRequest {
+            seq: 1,
+            property: broadcom::Property::HFID_USER,
+        };
+        let m = send_request(&mut xs, &mut b, BCM, request).unwrap();
+        assert!(m.unwrap().first().unwrap().len() < 1500);
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn network_password() {
+        let wire = MemoryWire::new();
+        let qca = Emulator::new(DeviceState::qualcomm(QCA));
+        let bcm = Emulator::new(DeviceState::broadcom(BCM));
+        let _qca = qca.spawn(&wire.interface("qca", QCA)).unwrap();
+        let _bcm = bcm.spawn(&wire.interface("bcm", BCM)).unwrap();
+        let host = wire.interface("host", HOST);
+        let mut s = host.open(EtherType::HOMEPLUG_AV).unwrap();
+
+        let nmk = generate_nmk("HomePlugAV0123");
+        let nid = generate_nid(nmk, SecurityLevel::SIMPLE);
+        for (addr, device) in [(QCA, &qca), (BCM, &bcm)] {
+            let set = set_network_password(&mut s, addr, "HomePlugAV0123", SecurityLevel::SIMPLE);
+            assert_eq!(set.unwrap(), Some(nid));
+            assert_eq!(device.state().nmk, NMK_HomePlugAV0123);
+            assert_eq!(device.state().nid, nid);
+        }
+
+        // Qualcomm devices pick the NID themselves, so another security level can't be honoured
+        let secure = generate_nid(generate_nmk("Secret"), SecurityLevel::SECURE);
+        let set = set_network_password(&mut s, QCA, "Secret", SecurityLevel::SECURE);
+        assert_eq!(set.unwrap(), None);
+        assert_eq!(qca.state().nmk, NMK_HomePlugAV0123);
+        let set = set_network_password(&mut s, BCM, "Secret", SecurityLevel::SECURE);
+        assert_eq!(set.unwrap(), Some(secure));
+        assert_eq!(bcm.state().nid, secure);
+    }
+}
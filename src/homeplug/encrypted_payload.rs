@@ -0,0 +1,424 @@
+use super::*;
+use core::convert::TryInto;
+
+/// Key a `CM_ENCRYPTED_PAYLOAD` is encrypted with (PEKS)
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct PayloadKeySelect(pub u8);
+impl PayloadKeySelect {
+    /// Destination station's Device Access Key
+    pub const DAK: Self = Self(0x00);
+    /// Network Membership Key of the AVLN
+    pub const NMK: Self = Self(0x01);
+    /// Values from here to `0x0e` select a Temporary Encryption Key
+    pub const TEK: Self = Self(0x02);
+    /// Payload isn't encrypted
+    pub const NONE: Self = Self(0x0f);
+}
+impl core::fmt::Debug for PayloadKeySelect {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::DAK => write!(f, "DAK"),
+            Self::NMK => write!(f, "NMK"),
+            Self::NONE => write!(f, "NONE"),
+            Self(tek @ 0x02..=0x0e) => write!(f, "TEK{}", tek - 0x02),
+            _ => write!(f, "PayloadKeySelect{:02x}", self.0),
+        }
+    }
+}
+
+/// Why a `CM_ENCRYPTED_PAYLOAD.IND` couldn't be decrypted
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecryptError {
+    /// Encrypted data is shorter than one block
+    InvalidLength(usize),
+    /// Buffer can't hold the decrypted data
+    BufferTooSmall { required: usize, capacity: usize },
+    /// Decrypted fields are inconsistent or the CRC is wrong (usually the wrong key)
+    Corrupt,
+    /// PID, PRN or PMN inside the encryption differ from the ones outside it
+    Mismatch,
+}
+impl core::fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            DecryptError::InvalidLength(length) => {
+                write!(f, "Invalid encrypted length ({} bytes)", length)
+            }
+            DecryptError::BufferTooSmall { required, capacity } => write!(
+                f,
+                "Buffer too small ({} bytes required, {} available)",
+                required, capacity
+            ),
+            DecryptError::Corrupt => write!(f, "Decrypted payload is corrupt (wrong key?)"),
+            DecryptError::Mismatch => write!(f, "Decrypted protocol fields don't match"),
+        }
+    }
+}
+
+/// Size of an AES block, the encrypted data is a multiple of this
+const BLOCK_SIZE: usize = 16;
+/// Longest random filler before the message
+#[cfg(feature = "crypto")]
+const MAX_FILLER: usize = 15;
+/// CRC, PID, PRN and PMN after the message
+#[cfg(feature = "crypto")]
+const TRAILER_SIZE: usize = 8;
+
+/// CM_ENCRYPTED_PAYLOAD.IND - Another management message, encrypted with AES-128-CBC
+///
+/// The encrypted data is the random filler, `message`, its CRC-32, a copy of the
+/// PID/PRN/PMN, padding, and the length of the filler.
+#[cfg(feature = "crypto")]
+pub struct EncryptedPayloadIndication<'p> {
+    pub peks: PayloadKeySelect,
+    /// Association and CCo capability of the sender
+    pub avln_status: u8,
+    pub pid: ProtocolId,
+    pub prn: u16,
+    pub pmn: u8,
+    pub iv: [u8; 16],
+    /// DAK, NMK or TEK selected by `peks`
+    pub key: [u8; 16],
+    /// Random bytes for the filler and padding (the first picks the filler's length)
+    pub random: [u8; 16],
+    /// The whole management message to protect, starting at its MMV
+    pub message: &'p [u8],
+}
+#[cfg(feature = "crypto")]
+impl<'a> MessageTX<'a> for EncryptedPayloadIndication<'_> {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_ENCRYPTED_PAYLOAD.ind();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        use aes::cipher::{block_padding::NoPadding, BlockEncryptMut, KeyIvInit};
+
+        frame.push_u8(self.peks.0)?;
+        frame.push_u8(self.avln_status)?;
+        frame.push_u8(self.pid.0)?;
+        frame.push_u16_le(self.prn)?;
+        frame.push_u8(self.pmn)?;
+        frame.push(&self.iv)?;
+        frame.push_u16_le(length_u16(self.message)?)?;
+
+        let filler = self.random[0] as usize % (MAX_FILLER + 1);
+        let unpadded = filler + self.message.len() + TRAILER_SIZE + 1;
+        let padding = (BLOCK_SIZE - unpadded % BLOCK_SIZE) % BLOCK_SIZE;
+        let size = unpadded + padding;
+        let data = frame.reserve(size)?;
+
+        let (random, rest) = data.split_at_mut(filler);
+        random.copy_from_slice(&self.random[..filler]);
+        let (message, rest) = rest.split_at_mut(self.message.len());
+        message.copy_from_slice(self.message);
+        rest[..4].copy_from_slice(&crc32(self.message).to_le_bytes());
+        rest[4] = self.pid.0;
+        rest[5..7].copy_from_slice(&self.prn.to_le_bytes());
+        rest[7] = self.pmn;
+        rest[8..8 + padding].copy_from_slice(&self.random[..padding]);
+        rest[8 + padding] = filler as u8;
+
+        cbc::Encryptor::<aes::Aes128>::new(&self.key.into(), &self.iv.into())
+            .encrypt_padded_mut::<NoPadding>(data, size)
+            .expect("whole blocks");
+        Ok(())
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct EncryptedPayloadInd<'a>(pub(crate) &'a [u8]);
+impl EncryptedPayloadInd<'_> {
+    pub fn peks(&self) -> PayloadKeySelect {
+        PayloadKeySelect(self.payload()[0])
+    }
+    pub fn avln_status(&self) -> u8 {
+        self.payload()[1]
+    }
+    pub fn pid(&self) -> ProtocolId {
+        ProtocolId(self.payload()[2])
+    }
+    pub fn prn(&self) -> u16 {
+        u16::from_le_bytes([self.payload()[3], self.payload()[4]])
+    }
+    pub fn pmn(&self) -> u8 {
+        self.payload()[5]
+    }
+    pub fn iv(&self) -> [u8; 16] {
+        self.payload()[6..22].try_into().unwrap()
+    }
+    /// Length of the encrypted management message
+    pub fn length(&self) -> usize {
+        u16::from_le_bytes([self.payload()[22], self.payload()[23]]) as usize
+    }
+    /// Encrypted data, without any Ethernet padding
+    pub fn encrypted(&self) -> &[u8] {
+        let data = &self.payload()[24..];
+        &data[..data.len() - data.len() % BLOCK_SIZE]
+    }
+
+    /// Decrypt with `key` into `buffer`, returning the management message
+    #[cfg(feature = "crypto")]
+    pub fn decrypt<'b>(
+        &self,
+        key: [u8; 16],
+        buffer: &'b mut [u8],
+    ) -> Result<&'b [u8], DecryptError> {
+        use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, KeyIvInit};
+
+        let encrypted = self.encrypted();
+        if encrypted.is_empty() {
+            return Err(DecryptError::InvalidLength(self.payload().len() - 24));
+        }
+        let size = encrypted.len();
+        if buffer.len() < size {
+            return Err(DecryptError::BufferTooSmall {
+                required: size,
+                capacity: buffer.len(),
+            });
+        }
+        let data = &mut buffer[..size];
+        data.copy_from_slice(encrypted);
+        cbc::Decryptor::<aes::Aes128>::new(&key.into(), &self.iv().into())
+            .decrypt_padded_mut::<NoPadding>(data)
+            .map_err(|_| DecryptError::InvalidLength(size))?;
+
+        let filler = data[size - 1] as usize;
+        let end = filler + self.length();
+        if filler > MAX_FILLER || end + TRAILER_SIZE + 1 > size {
+            return Err(DecryptError::Corrupt);
+        }
+        let trailer = &data[end..end + TRAILER_SIZE];
+        if trailer[..4] != crc32(&data[filler..end]).to_le_bytes() {
+            return Err(DecryptError::Corrupt);
+        }
+        let prn = u16::from_le_bytes([trailer[5], trailer[6]]);
+        if trailer[4] != self.pid().0 || prn != self.prn() || trailer[7] != self.pmn() {
+            return Err(DecryptError::Mismatch);
+        }
+        let buffer: &'b [u8] = buffer;
+        Ok(&buffer[filler..end])
+    }
+}
+impl MessageReader for EncryptedPayloadInd<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for EncryptedPayloadInd<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "EncryptedPayloadInd({:?} {:?} PRN:{:04x} PMN:{} {} bytes)",
+            self.peks(),
+            self.pid(),
+            self.prn(),
+            self.pmn(),
+            self.length()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for EncryptedPayloadInd<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 24)?;
+        Ok(Self(data))
+    }
+}
+
+/// CM_ENCRYPTED_PAYLOAD.RSP - Sent when an encrypted payload couldn't be handled
+pub struct EncryptedPayloadResponse {
+    pub result: u8,
+    pub pid: ProtocolId,
+    pub prn: u16,
+}
+impl<'a> MessageTX<'a> for EncryptedPayloadResponse {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_ENCRYPTED_PAYLOAD.rsp();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.result)?;
+        frame.push_u8(self.pid.0)?;
+        frame.push_u16_le(self.prn)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct EncryptedPayloadRsp<'a>(pub(crate) &'a [u8]);
+impl EncryptedPayloadRsp<'_> {
+    pub fn is_success(&self) -> bool {
+        self.payload()[0] == 0
+    }
+    pub fn pid(&self) -> ProtocolId {
+        ProtocolId(self.payload()[1])
+    }
+    pub fn prn(&self) -> u16 {
+        u16::from_le_bytes([self.payload()[2], self.payload()[3]])
+    }
+}
+impl MessageReader for EncryptedPayloadRsp<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for EncryptedPayloadRsp<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let result = if self.is_success() {
+            "Success"
+        } else {
+            "Failure"
+        };
+        write!(
+            f,
+            "EncryptedPayloadRsp({} {:?} PRN:{:04x})",
+            result,
+            self.pid(),
+            self.prn()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for EncryptedPayloadRsp<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 4)?;
+        Ok(Self(data))
+    }
+}
+
+/// CRC-32 (as used by Ethernet) of the encrypted message
+#[cfg(feature = "crypto")]
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(all(test, feature = "crypto"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn aes_cbc() {
+        use aes::cipher::{block_padding::NoPadding, BlockEncryptMut, KeyIvInit};
+
+        // NIST SP 800-38A, F.2.1 CBC-AES128.Encrypt
+        let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let iv = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+        let mut data = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+            0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        ];
+        cbc::Encryptor::<aes::Aes128>::new(&key.into(), &iv.into())
+            .encrypt_padded_mut::<NoPadding>(&mut data, 32)
+            .unwrap();
+        assert_eq!(data, [
+            0x76, 0x49, 0xab, 0xac, 0x81, 0x19, 0xb2, 0x46, 0xce, 0xe9, 0x8e, 0x9b, 0x12, 0xe9, 0x19, 0x7d,
+            0x50, 0x86, 0xcb, 0x9b, 0x50, 0x72, 0x19, 0xee, 0x95, 0xdb, 0x11, 0x3a, 0x91, 0x76, 0x78, 0xb2,
+        ]);
+
+        // Ethernet's CRC-32 check value
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn regression_vector() {
+        // Neither the HomePlug AV specification nor any capture available to us has a vector for
+        // CM_ENCRYPTED_PAYLOAD, so this isn't a known answer. The ciphertext was computed with a
+        // separate AES-CBC implementation (Python `cryptography`) from the plaintext layout
+        // checked below, which catches layout regressions but not a misreading of the spec.
+        //
+        // CM_STA_CAP.REQ sent with the default NMK, a 3 byte filler and 15 bytes of padding
+        let inner = [0x01, 0x34, 0x60, 0x00, 0x00];
+        let iv = [0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf];
+        let random = [0x13, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xcb, 0xcc, 0xcd, 0xce, 0xcf];
+        let indication = EncryptedPayloadIndication {
+            peks: PayloadKeySelect::NMK,
+            avln_status: 0,
+            pid: ProtocolId::PROVIDE_NMK_DAK,
+            prn: 0x1234,
+            pmn: 1,
+            iv,
+            key: NMK_HomePlugAV,
+            random,
+            message: &inner,
+        };
+        let mut buffer = [0; 128];
+        let data = indication.encode(&mut buffer).unwrap();
+        assert_eq!(data[..29], [
+            0x01, 0x06, 0x60, 0x00, 0x00, // MMV, CM_ENCRYPTED_PAYLOAD.IND, FMI
+            0x01, 0x00, 0x02, 0x34, 0x12, 0x01, // PEKS, AVLN status, PID, PRN, PMN
+            0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf,
+            0x05, 0x00, // Length
+        ]);
+        assert_eq!(data[29..], [
+            0x3d, 0xea, 0x08, 0xdf, 0x7a, 0xfb, 0x15, 0x78, 0x2a, 0x3f, 0xa3, 0x46, 0x8e, 0xa9, 0x34, 0x08,
+            0x68, 0x07, 0xd2, 0x58, 0xd7, 0x4e, 0xaf, 0x72, 0x5f, 0xd3, 0x67, 0x5c, 0xb5, 0xb4, 0x79, 0x50,
+        ]);
+
+        let ind = EncryptedPayloadInd::try_from(data).unwrap();
+        let mut decrypted = [0; 32];
+        assert_eq!(ind.decrypt(NMK_HomePlugAV, &mut decrypted), Ok(&inner[..]));
+        assert_eq!(decrypted, [
+            0x13, 0xc1, 0xc2, // Filler
+            0x01, 0x34, 0x60, 0x00, 0x00, // Message
+            0x7b, 0x7a, 0xdc, 0xcc, // CRC-32
+            0x02, 0x34, 0x12, 0x01, // PID, PRN, PMN
+            0x13, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xcb, 0xcc, 0xcd, 0xce, // Padding
+            0x03, // Filler length
+        ]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut inner = [0; 64];
+        let inner = StationCapabilitiesRequest.encode(&mut inner).unwrap();
+        let key = NMK_HomePlugAV;
+        for filler in 0..=16 {
+            let indication = EncryptedPayloadIndication {
+                peks: PayloadKeySelect::NMK,
+                avln_status: 0,
+                pid: ProtocolId::PROVIDE_NMK_DAK,
+                prn: 0x1234,
+                pmn: 1,
+                iv: [filler; 16],
+                key,
+                random: [filler; 16],
+                message: inner,
+            };
+            let mut buffer = [0; 128];
+            let data = indication.encode(&mut buffer).unwrap();
+            let ind = EncryptedPayloadInd::try_from(data).unwrap();
+            assert_eq!(ind.encrypted().len() % 16, 0);
+            assert_eq!(ind.pid(), ProtocolId::PROVIDE_NMK_DAK);
+            let mut decrypted = [0; 128];
+            assert_eq!(ind.decrypt(key, &mut decrypted), Ok(inner));
+            assert!(ind.decrypt(NMK_HomePlugAV0123, &mut decrypted).is_err());
+
+            // Ethernet padding is ignored, tampering isn't
+            let mut padded = [0; 128];
+            padded[..data.len()].copy_from_slice(data);
+            let ind = EncryptedPayloadInd(&padded[..data.len() + 3]);
+            assert_eq!(ind.decrypt(key, &mut decrypted), Ok(inner));
+            // A flipped IV bit garbles the first block once decrypted
+            let iv_byte = 5 + 6 + filler as usize % 16;
+            padded[iv_byte] ^= 1;
+            let ind = EncryptedPayloadInd(&padded[..data.len()]);
+            assert_eq!(ind.decrypt(key, &mut decrypted), Err(DecryptError::Corrupt));
+            padded[iv_byte] ^= 1;
+            padded[5 + 3] ^= 1;
+            let ind = EncryptedPayloadInd(&padded[..data.len()]);
+            assert_eq!(
+                ind.decrypt(key, &mut decrypted),
+                Err(DecryptError::Mismatch)
+            );
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Fragmentation of large management messages (HomePlug AV 1.1 and later)
+//!
+//! Fragmented messages repeat the 5 byte MMV/MMTYPE/FMI/FMSN header in every
+//! fragment, and split the remainder of the message (including the OUI of vendor
+//! messages) across up to 16 fragments sharing the same FMSN.
+
+use super::*;
+use crate::*;
+
+/// Size of the header repeated in each fragment
+pub const FRAGMENT_HEADER_SIZE: usize = 5;
+/// Fragment numbers are 4 bits wide
+pub const MAX_FRAGMENTS: usize = 16;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FragmentError {
+    /// Only HomePlug AV 1.1 and 2.0 messages can be fragmented
+    UnsupportedVersion(MMV),
+    /// Message needs more than [`MAX_FRAGMENTS`] fragments
+    TooLarge { size: usize, max: usize },
+    /// Fragment size leaves no room for any message data
+    InvalidFragmentSize(usize),
+}
+impl core::fmt::Display for FragmentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            FragmentError::UnsupportedVersion(mmv) => {
+                write!(f, "Can't fragment {:?} messages", mmv)
+            }
+            FragmentError::TooLarge { size, max } => {
+                write!(f, "Message too large to fragment ({}>{})", size, max)
+            }
+            FragmentError::InvalidFragmentSize(size) => {
+                write!(f, "Invalid fragment size ({})", size)
+            }
+        }
+    }
+}
+
+/// Split an encoded message into correctly numbered fragments
+pub struct Fragments<'a> {
+    header: [u8; FRAGMENT_HEADER_SIZE],
+    entry: &'a [u8],
+    chunk: usize,
+    count: usize,
+    index: usize,
+}
+impl<'a> Fragments<'a> {
+    /// Split `message` into fragments no larger than `max_size` bytes, all using sequence `fmsn`
+    pub fn new(message: &'a [u8], max_size: usize, fmsn: u8) -> Result<Self, FragmentError> {
+        let msg = UnknownMessage(message);
+        if message.len() < FRAGMENT_HEADER_SIZE
+            || (msg.mmv() != MMV::HOMEPLUG_AV_1_1 && msg.mmv() != MMV::HOMEPLUG_AV_2_0)
+        {
+            let mmv = message.first().map_or(MMV(0xff), |&mmv| MMV(mmv));
+            return Err(FragmentError::UnsupportedVersion(mmv));
+        }
+        if max_size <= FRAGMENT_HEADER_SIZE {
+            return Err(FragmentError::InvalidFragmentSize(max_size));
+        }
+        let chunk = max_size - FRAGMENT_HEADER_SIZE;
+        let entry = &message[FRAGMENT_HEADER_SIZE..];
+        let count = entry.len().div_ceil(chunk).max(1);
+        if count > MAX_FRAGMENTS {
+            return Err(FragmentError::TooLarge {
+                size: message.len(),
+                max: FRAGMENT_HEADER_SIZE + chunk * MAX_FRAGMENTS,
+            });
+        }
+        let mut header = [0; FRAGMENT_HEADER_SIZE];
+        header.copy_from_slice(&message[..FRAGMENT_HEADER_SIZE]);
+        header[offset::FMSN] = fmsn;
+        Ok(Fragments {
+            header,
+            entry,
+            chunk,
+            count,
+            index: 0,
+        })
+    }
+}
+impl<'a> Iterator for Fragments<'a> {
+    type Item = Fragment<'a>;
+    fn next(&mut self) -> Option<Fragment<'a>> {
+        if self.index >= self.count {
+            return None;
+        }
+        let start = (self.index * self.chunk).min(self.entry.len());
+        let end = (start + self.chunk).min(self.entry.len());
+        let mut header = self.header;
+        header[offset::FMI] = ((self.index as u8) << 4) | (self.count as u8 - 1);
+        self.index += 1;
+        Some(Fragment {
+            header,
+            entry: &self.entry[start..end],
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+impl ExactSizeIterator for Fragments<'_> {}
+
+/// A single fragment of a larger message
+pub struct Fragment<'a> {
+    header: [u8; FRAGMENT_HEADER_SIZE],
+    entry: &'a [u8],
+}
+impl Fragment<'_> {
+    pub fn len(&self) -> usize {
+        FRAGMENT_HEADER_SIZE + self.entry.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+    /// Convert this fragment to bytes, failing if `bytes` is too small
+    pub fn encode<'b>(&self, bytes: &'b mut [u8]) -> Result<&'b [u8], EncodeError> {
+        let mut frame = FrameWriter::new(bytes);
+        frame.push(&self.header)?;
+        frame.push(self.entry)?;
+        Ok(frame.into_bytes())
+    }
+}
+
+/// Send every fragment of a message to `destination`
+pub fn send_fragments<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    fragments: Fragments,
+) -> Result<(), RequestError<T::Error>> {
+    let mut buffer = [0; ETHERNET_MTU];
+    for fragment in fragments {
+        let bytes = fragment.encode(&mut buffer).map_err(RequestError::Encode)?;
+        socket.sendto(destination, bytes)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+pub use reassembly::*;
+
+#[cfg(feature = "std")]
+mod reassembly {
+    extern crate std;
+
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Instant;
+    use std::vec::Vec;
+
+    struct Pending {
+        started: Instant,
+        mmtype: MMType,
+        fragments: Vec<Option<Vec<u8>>>,
+    }
+
+    /// Collects fragments keyed by source address and FMSN into whole messages
+    pub struct Reassembler {
+        timeout: Duration,
+        pending: HashMap<(EtherAddr, u8), Pending>,
+    }
+    impl Reassembler {
+        /// Create a reassembler that discards incomplete messages after `timeout`
+        pub fn new(timeout: Duration) -> Reassembler {
+            Reassembler {
+                timeout,
+                pending: HashMap::new(),
+            }
+        }
+
+        /// Number of partially received messages
+        pub fn pending(&self) -> usize {
+            self.pending.len()
+        }
+
+        /// Add a received message, returning the whole message once all fragments have arrived
+        ///
+        /// Unfragmented messages are returned immediately.
+        pub fn push(
+            &mut self,
+            source: EtherAddr,
+            data: &[u8],
+        ) -> Result<Option<Vec<u8>>, ParseError> {
+            self.push_at(source, data, Instant::now())
+        }
+
+        /// Same as [`Reassembler::push`], with an explicit arrival time
+        pub fn push_at(
+            &mut self,
+            source: EtherAddr,
+            data: &[u8],
+            now: Instant,
+        ) -> Result<Option<Vec<u8>>, ParseError> {
+            self.expire(now);
+
+            let msg = UnknownMessage::try_from(data)?;
+            let count = msg.fragment_count();
+            if count == 1 {
+                return Ok(Some(data.to_vec()));
+            }
+            let number = msg.fragment_number();
+            if number >= count {
+                return Err(ParseError::InvalidFragment { number, count });
+            }
+
+            let key = (source, msg.fmsn());
+            let mmtype = msg.mmtype();
+            let pending = self.pending.entry(key).or_insert_with(|| Pending {
+                started: now,
+                mmtype,
+                fragments: Vec::new(),
+            });
+            if pending.mmtype != mmtype || pending.fragments.len() != count {
+                // Sequence number reused for a different message, start over
+                pending.started = now;
+                pending.mmtype = mmtype;
+                pending.fragments.clear();
+                pending.fragments.resize(count, None);
+            }
+            pending.fragments[number] = Some(data[FRAGMENT_HEADER_SIZE..].to_vec());
+            if pending.fragments.iter().any(Option::is_none) {
+                return Ok(None);
+            }
+
+            let pending = self.pending.remove(&key).unwrap();
+            let mut message = data[..FRAGMENT_HEADER_SIZE].to_vec();
+            message[offset::FMI] = 0;
+            for fragment in pending.fragments.into_iter().flatten() {
+                message.extend_from_slice(&fragment);
+            }
+            Ok(Some(message))
+        }
+
+        /// Discard messages that haven't completed within the timeout
+        ///
+        /// Returns the number of messages discarded.
+        pub fn expire(&mut self, now: Instant) -> usize {
+            let timeout = self.timeout;
+            let before = self.pending.len();
+            self.pending
+                .retain(|_, pending| now.saturating_duration_since(pending.started) < timeout);
+            before - self.pending.len()
+        }
+    }
+    impl Default for Reassembler {
+        /// One second is more than enough for every fragment to cross the local network
+        fn default() -> Self {
+            Self::new(Duration::from_secs(1))
+        }
+    }
+    impl core::fmt::Debug for Reassembler {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(f, "Reassembler(pending={})", self.pending.len())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::time::Instant;
+    use std::vec::Vec;
+
+    const SOURCE: EtherAddr = EtherAddr([0x00, 0xb0, 0x52, 0x00, 0x00, 0x01]);
+
+    fn message(size: usize) -> Vec<u8> {
+        // Vendor specific VS_RD_MOD.CNF sized message
+        let mut message = std::vec![0x01, 0x25, 0xa0, 0x00, 0x00, 0x00, 0xb0, 0x52];
+        message.extend((0..size).map(|i| i as u8));
+        message
+    }
+
+    #[test]
+    fn roundtrip() {
+        let message = message(3000);
+        let fragments = Fragments::new(&message, 1000, 0x42).unwrap();
+        assert_eq!(fragments.len(), 4);
+
+        let mut encoded: Vec<Vec<u8>> = fragments
+            .map(|fragment| fragment.encode(&mut [0; 1500]).unwrap().to_vec())
+            .collect();
+        assert!(encoded.iter().all(|f| f.len() <= 1000 && f[4] == 0x42));
+        assert_eq!(encoded[2][3], 0x23);
+
+        // Fragments may arrive in any order
+        encoded.swap(0, 3);
+        encoded.swap(1, 2);
+        let mut reassembler = Reassembler::default();
+        let (last, rest) = encoded.split_last().unwrap();
+        for fragment in rest {
+            assert_eq!(reassembler.push(SOURCE, fragment), Ok(None));
+        }
+        assert_eq!(reassembler.pending(), 1);
+        let whole = reassembler.push(SOURCE, last).unwrap().unwrap();
+        assert_eq!(whole[..offset::FMSN], message[..offset::FMSN]);
+        assert_eq!(whole[offset::FMSN], 0x42);
+        assert_eq!(
+            whole[FRAGMENT_HEADER_SIZE..],
+            message[FRAGMENT_HEADER_SIZE..]
+        );
+        assert_eq!(reassembler.pending(), 0);
+
+        // Small messages pass straight through
+        let small = self::message(10);
+        let mut fragments = Fragments::new(&small, 1000, 0).unwrap();
+        let fragment = fragments
+            .next()
+            .unwrap()
+            .encode(&mut [0; 1500])
+            .unwrap()
+            .to_vec();
+        assert!(fragments.next().is_none());
+        assert_eq!(reassembler.push(SOURCE, &fragment), Ok(Some(small)));
+    }
+
+    #[test]
+    fn limits() {
+        let message = message(20000);
+        assert!(matches!(
+            Fragments::new(&message, 1000, 0),
+            Err(FragmentError::TooLarge { .. })
+        ));
+        let v10 = [0x00, 0x00, 0xa0, 0x00, 0xb0, 0x52];
+        assert_eq!(
+            Fragments::new(&v10, 1000, 0).err(),
+            Some(FragmentError::UnsupportedVersion(MMV::HOMEPLUG_AV_1_0))
+        );
+    }
+
+    #[test]
+    fn timeout() {
+        let message = message(2000);
+        let fragments: Vec<Vec<u8>> = Fragments::new(&message, 1500, 1)
+            .unwrap()
+            .map(|fragment| fragment.encode(&mut [0; 1500]).unwrap().to_vec())
+            .collect();
+        let mut reassembler = Reassembler::new(Duration::from_millis(100));
+        let start = Instant::now();
+        assert_eq!(reassembler.push_at(SOURCE, &fragments[0], start), Ok(None));
+        let later = start + Duration::from_millis(200);
+        assert_eq!(reassembler.expire(later), 1);
+        assert_eq!(reassembler.push_at(SOURCE, &fragments[1], later), Ok(None));
+        assert_eq!(reassembler.pending(), 1);
+    }
+}
@@ -0,0 +1,142 @@
+//! Bounded writing of outgoing messages into caller supplied buffers
+//!
+//! Buffers can be any size, so jumbo frames and VLAN tagged frames are no different to
+//! standard 1500 byte frames. Running out of space is reported as an [`EncodeError`].
+
+/// Largest payload of a standard (non-jumbo) ethernet frame
+pub const ETHERNET_MTU: usize = 1500;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// Buffer can't hold the whole message
+    BufferTooSmall { required: usize, capacity: usize },
+    /// Variable length field is longer than its length field can describe
+    FieldTooLarge { length: usize, max: usize },
+}
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            EncodeError::BufferTooSmall { required, capacity } => write!(
+                f,
+                "Buffer too small ({} bytes required, {} available)",
+                required, capacity
+            ),
+            EncodeError::FieldTooLarge { length, max } => {
+                write!(f, "Field too large ({}>{})", length, max)
+            }
+        }
+    }
+}
+
+/// Length of `data` for a 16-bit length field
+pub(crate) fn length_u16(data: &[u8]) -> Result<u16, EncodeError> {
+    let max = u16::MAX as usize;
+    if data.len() > max {
+        return Err(EncodeError::FieldTooLarge {
+            length: data.len(),
+            max,
+        });
+    }
+    Ok(data.len() as u16)
+}
+
+/// Appends message fields to a buffer, failing instead of writing past its end
+pub struct FrameWriter<'a> {
+    bytes: &'a mut [u8],
+    len: usize,
+}
+impl<'a> FrameWriter<'a> {
+    pub fn new(bytes: &'a mut [u8]) -> Self {
+        FrameWriter { bytes, len: 0 }
+    }
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Size of the underlying buffer
+    pub fn capacity(&self) -> usize {
+        self.bytes.len()
+    }
+    /// Number of bytes that can still be written
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.len
+    }
+    /// Reserve `size` bytes, returning them for the caller to fill in
+    pub fn reserve(&mut self, size: usize) -> Result<&mut [u8], EncodeError> {
+        if size > self.remaining() {
+            return Err(EncodeError::BufferTooSmall {
+                required: self.len + size,
+                capacity: self.bytes.len(),
+            });
+        }
+        let start = self.len;
+        self.len += size;
+        Ok(&mut self.bytes[start..self.len])
+    }
+    pub fn push(&mut self, data: &[u8]) -> Result<(), EncodeError> {
+        self.reserve(data.len())?.copy_from_slice(data);
+        Ok(())
+    }
+    pub fn push_u8(&mut self, value: u8) -> Result<(), EncodeError> {
+        self.push(&[value])
+    }
+    pub fn push_u16_le(&mut self, value: u16) -> Result<(), EncodeError> {
+        self.push(&value.to_le_bytes())
+    }
+    pub fn push_u32_le(&mut self, value: u32) -> Result<(), EncodeError> {
+        self.push(&value.to_le_bytes())
+    }
+    /// Append `size` zero bytes (e.g. reserved fields)
+    pub fn zeros(&mut self, size: usize) -> Result<(), EncodeError> {
+        self.reserve(size)?.iter_mut().for_each(|b| *b = 0);
+        Ok(())
+    }
+    /// Get the bytes written so far
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::homeplug::*;
+
+    #[test]
+    fn overflow() {
+        let mut buffer = [0xff; 4];
+        let mut frame = FrameWriter::new(&mut buffer);
+        frame.push_u16_le(0x0201).unwrap();
+        assert_eq!(
+            frame.push_u32_le(0),
+            Err(EncodeError::BufferTooSmall {
+                required: 6,
+                capacity: 4
+            })
+        );
+        frame.zeros(1).unwrap();
+        assert_eq!(frame.remaining(), 1);
+        assert_eq!(frame.into_bytes(), [0x01, 0x02, 0x00]);
+
+        // Oversized requests are rejected rather than panicking
+        let data = [0; ETHERNET_MTU];
+        let request = qualcomm::WriteModuleRequest {
+            module: qualcomm::ModuleId::PIB,
+            offset: 0,
+            data: &data,
+        };
+        assert_eq!(
+            request.encode(&mut [0; ETHERNET_MTU]),
+            Err(EncodeError::BufferTooSmall {
+                required: 6 + 12 + ETHERNET_MTU,
+                capacity: ETHERNET_MTU
+            })
+        );
+        // ...but fit in jumbo frames
+        let mut buffer = [0; 9000];
+        assert_eq!(request.encode(&mut buffer).unwrap().len(), 1518);
+    }
+}
@@ -2,7 +2,8 @@ use super::*;
 use core::str;
 
 #[repr(transparent)]
-#[derive(Default, PartialEq, Eq, Copy, Clone)]
+#[derive(Default, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HFIDReqType(pub u8);
 impl HFIDReqType {
     pub const GET_MFG: Self = Self(0x00);
@@ -38,44 +39,35 @@ impl<'a> MessageTX<'a> for HFIDRequest {
     const MMTYPE: MMType = MMType::CM_HFID;
     type Response = HFID<'a>;
 
-    fn set_payload(&self, bytes: &mut [u8]) -> usize {
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
         match self {
-            HFIDRequest::GetMfg => {
-                bytes[0] = HFIDReqType::GET_MFG.0;
-                1
-            }
-            HFIDRequest::GetUsr => {
-                bytes[0] = HFIDReqType::GET_USR.0;
-                1
-            }
+            HFIDRequest::GetMfg => frame.push_u8(HFIDReqType::GET_MFG.0),
+            HFIDRequest::GetUsr => frame.push_u8(HFIDReqType::GET_USR.0),
             HFIDRequest::GetNet { nid } => {
-                bytes[0] = HFIDReqType::GET_NET.0;
-                bytes[1..7].copy_from_slice(nid);
-                7
+                frame.push_u8(HFIDReqType::GET_NET.0)?;
+                frame.push(nid)
             }
             HFIDRequest::SetUsr { hfid } => {
-                bytes[0] = HFIDReqType::SET_USR.0;
-                bytes[1..65].copy_from_slice(hfid);
-                65
+                frame.push_u8(HFIDReqType::SET_USR.0)?;
+                frame.push(hfid)
             }
             HFIDRequest::SetNet { nid, hfid } => {
-                bytes[0] = HFIDReqType::SET_NET.0;
-                bytes[1..7].copy_from_slice(nid);
-                bytes[7..71].copy_from_slice(hfid);
-                71
+                frame.push_u8(HFIDReqType::SET_NET.0)?;
+                frame.push(nid)?;
+                frame.push(hfid)
             }
         }
     }
 }
 
 #[derive(Eq, PartialEq, Hash)]
-pub struct HFID<'a>(pub &'a [u8]);
+pub struct HFID<'a>(pub(crate) &'a [u8]);
 impl HFID<'_> {
     pub fn req_type(&self) -> HFIDReqType {
         HFIDReqType(self.payload()[0])
     }
     pub fn hfid_bytes(&self) -> &[u8] {
-        &self.payload()[1..=65]
+        &self.payload()[1..65]
     }
     pub fn hfid(&self) -> &str {
         str::from_utf8(self.hfid_bytes())
@@ -93,8 +85,38 @@ impl core::fmt::Debug for HFID<'_> {
         write!(f, "{:?}: {}", self.req_type(), self.hfid())
     }
 }
-impl<'a> From<&'a [u8]> for HFID<'a> {
-    fn from(data: &'a [u8]) -> Self {
-        Self(data)
+impl<'a> TryFrom<&'a [u8]> for HFID<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 65)?;
+        Ok(Self(data))
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use owned::*;
+
+#[cfg(feature = "alloc")]
+mod owned {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::string::{String, ToString};
+
+    /// Decoded copy of an [`HFID`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedHFID {
+        pub req_type: HFIDReqType,
+        pub hfid: String,
+    }
+
+    impl HFID<'_> {
+        pub fn to_owned(&self) -> OwnedHFID {
+            OwnedHFID {
+                req_type: self.req_type(),
+                hfid: self.hfid().to_string(),
+            }
+        }
     }
 }
@@ -0,0 +1,233 @@
+use super::*;
+use crate::*;
+use core::convert::TryInto;
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct LinkStatsReqType(pub u8);
+impl LinkStatsReqType {
+    pub const RESET: Self = Self(0x00);
+    pub const GET: Self = Self(0x01);
+    pub const GET_AND_RESET: Self = Self(0x02);
+}
+impl core::fmt::Debug for LinkStatsReqType {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::RESET => write!(f, "RESET"),
+            Self::GET => write!(f, "GET"),
+            Self::GET_AND_RESET => write!(f, "GET_AND_RESET"),
+            _ => write!(f, "LinkStatsReqType{:02x}", self.0),
+        }
+    }
+}
+
+/// CM_LINK_STATS - Request statistics of one link between the device and a peer station
+pub struct LinkStatsRequest {
+    pub req_type: LinkStatsReqType,
+    /// Echoed in the confirmation
+    pub req_id: u8,
+    pub nid: [u8; 7],
+    /// Link identifier (priority 0-3, or a connection LID)
+    pub lid: u8,
+    /// Statistics of the receive link from `peer`, rather than the transmit link to it (TLFlag)
+    pub receive: bool,
+    /// Management link rather than data link (Mgmt_Flag)
+    pub management: bool,
+    /// Destination of a transmit link, or source of a receive link
+    pub peer: EtherAddr,
+}
+impl<'a> MessageTX<'a> for LinkStatsRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_LINK_STATS;
+    type Response = LinkStats<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.req_type.0)?;
+        frame.push_u8(self.req_id)?;
+        frame.push(&self.nid)?;
+        frame.push_u8(self.lid)?;
+        frame.push_u8(self.receive as u8)?;
+        frame.push_u8(self.management as u8)?;
+        frame.push(&self.peer.0)
+    }
+}
+
+const HEADER_SIZE: usize = 2;
+const TX_SIZE: usize = 44;
+const RX_SIZE: usize = 32;
+
+/// Statistics of a transmit link
+///
+/// The latency histogram that follows these counters isn't decoded.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TxLinkStatistics {
+    /// MSDUs received from the host
+    pub msdus: u32,
+    /// MSDU payload octets received from the host
+    pub octets: u64,
+    pub segments_generated: u32,
+    pub segments_delivered: u32,
+    pub segments_dropped: u32,
+    /// PBs handed to the PHY for transmission
+    pub pbs: u32,
+    pub mpdus: u32,
+    pub mpdus_acked: u32,
+    pub collisions: u32,
+    /// PBs that weren't acknowledged and had to be retransmitted
+    pub pb_errors: u32,
+}
+impl TxLinkStatistics {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..TX_SIZE)?;
+        Some(Self {
+            msdus: u32_at(data, 0),
+            octets: u64::from_le_bytes(data[4..12].try_into().unwrap()),
+            segments_generated: u32_at(data, 12),
+            segments_delivered: u32_at(data, 16),
+            segments_dropped: u32_at(data, 20),
+            pbs: u32_at(data, 24),
+            mpdus: u32_at(data, 28),
+            mpdus_acked: u32_at(data, 32),
+            collisions: u32_at(data, 36),
+            pb_errors: u32_at(data, 40),
+        })
+    }
+}
+
+/// Statistics of a receive link
+///
+/// The latency histogram that follows these counters isn't decoded.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RxLinkStatistics {
+    /// MSDUs delivered to the host
+    pub msdus: u32,
+    /// MSDU payload octets delivered to the host
+    pub octets: u64,
+    pub segments_received: u32,
+    pub segments_missed: u32,
+    /// PBs received from the PHY
+    pub pbs: u32,
+    pub mpdus: u32,
+    /// PBs that failed FEC decoding
+    pub fec_errors: u32,
+}
+impl RxLinkStatistics {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..RX_SIZE)?;
+        Some(Self {
+            msdus: u32_at(data, 0),
+            octets: u64::from_le_bytes(data[4..12].try_into().unwrap()),
+            segments_received: u32_at(data, 12),
+            segments_missed: u32_at(data, 16),
+            pbs: u32_at(data, 20),
+            mpdus: u32_at(data, 24),
+            fec_errors: u32_at(data, 28),
+        })
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// Reply to [`LinkStatsRequest`]
+///
+/// Whether the statistics are of a transmit or receive link depends on the request's TLFlag,
+/// so the caller picks [`LinkStats::tx`] or [`LinkStats::rx`].
+#[derive(Eq, PartialEq, Hash)]
+pub struct LinkStats<'a>(pub(crate) &'a [u8]);
+impl LinkStats<'_> {
+    pub fn req_id(&self) -> u8 {
+        self.payload()[0]
+    }
+    pub fn is_success(&self) -> bool {
+        self.payload()[1] == 0
+    }
+    /// Undecoded statistics block
+    pub fn statistics(&self) -> &[u8] {
+        &self.payload()[HEADER_SIZE..]
+    }
+    pub fn tx(&self) -> Option<TxLinkStatistics> {
+        self.is_success()
+            .then(|| TxLinkStatistics::parse(self.statistics()))
+            .flatten()
+    }
+    pub fn rx(&self) -> Option<RxLinkStatistics> {
+        self.is_success()
+            .then(|| RxLinkStatistics::parse(self.statistics()))
+            .flatten()
+    }
+}
+impl MessageReader for LinkStats<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for LinkStats<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let result = if self.is_success() {
+            "Success"
+        } else {
+            "Failure"
+        };
+        write!(
+            f,
+            "LinkStats({} id={} {} bytes)",
+            result,
+            self.req_id(),
+            self.statistics().len()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for LinkStats<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, HEADER_SIZE)?;
+        Ok(Self(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn statistics() {
+        let request = LinkStatsRequest {
+            req_type: LinkStatsReqType::GET,
+            req_id: 7,
+            nid: [1, 2, 3, 4, 5, 6, 7],
+            lid: 1,
+            receive: true,
+            management: false,
+            peer: EtherAddr([0x00, 0xb0, 0x52, 0, 0, 0x02]),
+        };
+        let mut buffer = [0; 64];
+        let frame = request.encode(&mut buffer).unwrap();
+        assert_eq!(&frame[5..23], [
+            0x01, 0x07, 1, 2, 3, 4, 5, 6, 7, 0x01, 0x01, 0x00, // ReqType to Mgmt_Flag
+            0x00, 0xb0, 0x52, 0x00, 0x00, 0x02, // Source
+        ]);
+
+        let data = [
+            0x01, 0x4e, 0x60, 0x00, 0x00, // CM_LINK_STATS.CNF
+            0x07, 0x00, // ReqID, Result
+            0x10, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // MSDUs, Octets
+            0x20, 0x00, 0x00, 0x00, 0x1e, 0x00, 0x00, 0x00, // Segments received, missed
+            0x30, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, // PBs, MPDUs, FEC
+            0x00, 0x00, 0x00, 0x00, // Latency bins
+        ];
+        let stats = LinkStats::try_from(&data[..]).unwrap();
+        assert_eq!(stats.req_id(), 7);
+        assert_eq!(stats.tx(), None);
+        let rx = stats.rx().unwrap();
+        assert_eq!((rx.msdus, rx.octets, rx.segments_missed), (16, 0x4000, 30));
+        assert_eq!((rx.pbs, rx.mpdus, rx.fec_errors), (48, 8, 5));
+
+        let mut failed = data;
+        failed[6] = 1;
+        assert_eq!(LinkStats::try_from(&failed[..]).unwrap().rx(), None);
+        assert!(LinkStats::try_from(&data[..6]).is_err());
+    }
+}
@@ -1,15 +1,17 @@
 use super::*;
 use crate::OUI;
+use core::convert::TryFrom;
 
-mod offset {
+pub(crate) mod offset {
     pub const MMV: usize = 0;
     pub const MMTYPE_L: usize = 1;
     pub const MMTYPE_H: usize = 2;
     pub const FMI: usize = 3;
     pub const FMSN: usize = 4;
-    pub const OUI0: usize = 5;
-    pub const OUI1: usize = 6;
-    pub const OUI2: usize = 7;
+    // Relative to the end of the fixed header, see `oui_offset`
+    pub const OUI0: usize = 0;
+    pub const OUI1: usize = 1;
+    pub const OUI2: usize = 2;
 }
 
 pub trait MessageTX<'a> {
@@ -18,17 +20,25 @@ pub trait MessageTX<'a> {
     const OUI: OUI = OUI([0x00, 0x00, 0x00]);
     type Response: MessageReader;
 
-    /// Set the payload bytes for this message transmission
-    fn set_payload(&self, bytes: &mut [u8]) -> usize {
-        let _ = bytes;
-        0
+    /// Write the payload bytes for this message transmission
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        let _ = frame;
+        Ok(())
+    }
+
+    /// Vendor sequence number the reply will echo back (e.g. Broadcom `seq`)
+    fn sequence(&self) -> Option<u8> {
+        None
     }
 
-    /// Convert this transmissiable message to bytes
-    fn encode<'b>(&self, bytes: &'b mut [u8]) -> &'b [u8] {
-        let header_size = set_header(bytes, Self::MMV, Self::MMTYPE, Self::OUI);
-        let payload_size = self.set_payload(&mut bytes[header_size..]);
-        &bytes[..header_size + payload_size]
+    /// Convert this transmissiable message to bytes, failing if `bytes` is too small
+    fn encode<'b>(&self, bytes: &'b mut [u8]) -> Result<&'b [u8], EncodeError> {
+        let mut header = [0; 8];
+        let header_size = set_header(&mut header, Self::MMV, Self::MMTYPE, Self::OUI);
+        let mut frame = FrameWriter::new(bytes);
+        frame.push(&header[..header_size])?;
+        self.set_payload(&mut frame)?;
+        Ok(frame.into_bytes())
     }
 }
 
@@ -63,13 +73,22 @@ pub trait MessageReader: core::fmt::Debug {
             0
         }
     }
+    /// Number of fragments the whole message was split into (NF_MI + 1)
+    fn fragment_count(&self) -> usize {
+        (self.fmi() & 0x0f) as usize + 1
+    }
+    /// Index of this fragment within the whole message (FN_MI)
+    fn fragment_number(&self) -> usize {
+        (self.fmi() >> 4) as usize
+    }
     /// Organizationally Unique Identifier (for vendor specific messages)
     fn oui(&self) -> OUI {
         if self.mmtype().is_vendor() {
+            let oui = oui_offset(self.mmv());
             OUI([
-                self.bytes()[offset::OUI0],
-                self.bytes()[offset::OUI1],
-                self.bytes()[offset::OUI2],
+                self.bytes()[oui + offset::OUI0],
+                self.bytes()[oui + offset::OUI1],
+                self.bytes()[oui + offset::OUI2],
             ])
         } else {
             Default::default()
@@ -80,14 +99,29 @@ pub trait MessageReader: core::fmt::Debug {
         let offset = header_size(self.mmv(), self.mmtype());
         &self.bytes()[offset..]
     }
+    /// Vendor sequence number echoed from the request (Broadcom payloads start with `seq`)
+    fn sequence(&self) -> Option<u8> {
+        if self.oui() == OUI::BROADCOM {
+            self.payload().first().copied()
+        } else {
+            None
+        }
+    }
 }
 
-pub struct UnknownMessage<'a>(pub &'a [u8]);
+pub struct UnknownMessage<'a>(pub(crate) &'a [u8]);
 impl MessageReader for UnknownMessage<'_> {
     fn bytes(&self) -> &[u8] {
         self.0
     }
 }
+impl<'a> TryFrom<&'a [u8]> for UnknownMessage<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        parse_payload(data)?;
+        Ok(Self(data))
+    }
+}
 impl core::fmt::Debug for UnknownMessage<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(f, "MMV:{:?} MMType:{:?}", self.mmv(), self.mmtype())?;
@@ -95,25 +129,82 @@ impl core::fmt::Debug for UnknownMessage<'_> {
     }
 }
 
-fn header_size(mmv: MMV, mmtype: MMType) -> usize {
+#[cfg(feature = "alloc")]
+pub use owned::*;
+
+#[cfg(feature = "alloc")]
+mod owned {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// A whole message copied out of the receive buffer, so it can be stored or sent elsewhere
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    pub struct OwnedMessage(pub Vec<u8>);
+    impl OwnedMessage {
+        /// Copy the bytes of any message
+        pub fn new(message: &impl MessageReader) -> Self {
+            OwnedMessage(message.bytes().to_vec())
+        }
+        /// Decode the message as message type `R`
+        pub fn parse<'a, R: TryFrom<&'a [u8], Error = ParseError>>(
+            &'a self,
+        ) -> Result<R, ParseError> {
+            R::try_from(&self.0)
+        }
+    }
+    impl MessageReader for OwnedMessage {
+        fn bytes(&self) -> &[u8] {
+            &self.0
+        }
+    }
+    impl TryFrom<&[u8]> for OwnedMessage {
+        type Error = ParseError;
+        fn try_from(data: &[u8]) -> Result<Self, ParseError> {
+            parse_payload(data)?;
+            Ok(Self(data.to_vec()))
+        }
+    }
+    impl core::fmt::Debug for OwnedMessage {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            UnknownMessage(&self.0).fmt(f)
+        }
+    }
+}
+
+pub(crate) fn header_size(mmv: MMV, mmtype: MMType) -> usize {
     match mmv {
         MMV::HOMEPLUG_AV_1_1 | MMV::HOMEPLUG_AV_2_0 if mmtype.is_vendor() => 8,
         MMV::HOMEPLUG_AV_1_1 | MMV::HOMEPLUG_AV_2_0 => 5,
+        MMV::HOMEPLUG_AV_1_0 if mmtype.is_vendor() => 6,
         MMV::HOMEPLUG_AV_1_0 => 3,
         _ => usize::MAX,
     }
 }
 
-fn set_header(bytes: &mut [u8], mmv: MMV, mmtype: MMType, oui: OUI) -> usize {
+/// Vendor OUI follows the fragmentation fields, which HomePlug AV 1.0 doesn't have
+fn oui_offset(mmv: MMV) -> usize {
+    if mmv == MMV::HOMEPLUG_AV_1_0 {
+        offset::FMI
+    } else {
+        offset::FMSN + 1
+    }
+}
+
+pub(crate) fn set_header(bytes: &mut [u8], mmv: MMV, mmtype: MMType, oui: OUI) -> usize {
     let mut header = [0; 8];
     header[offset::MMV] = mmv.0;
     header[offset::MMTYPE_L] = mmtype.to_le_bytes()[0];
     header[offset::MMTYPE_H] = mmtype.to_le_bytes()[1];
-    header[offset::FMI] = 0;
-    header[offset::FMSN] = 0;
-    header[offset::OUI0] = oui[0];
-    header[offset::OUI1] = oui[1];
-    header[offset::OUI2] = oui[2];
+    if mmv != MMV::HOMEPLUG_AV_1_0 {
+        header[offset::FMI] = 0;
+        header[offset::FMSN] = 0;
+    }
+    let oui_offset = oui_offset(mmv);
+    header[oui_offset + offset::OUI0] = oui[0];
+    header[oui_offset + offset::OUI1] = oui[1];
+    header[oui_offset + offset::OUI2] = oui[2];
 
     let size = header_size(mmv, mmtype);
     bytes[..size].copy_from_slice(&header[..size]);
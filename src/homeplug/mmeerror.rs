@@ -19,7 +19,7 @@ impl core::fmt::Debug for ErrorType {
     }
 }
 
-pub struct MMEError<'a>(pub &'a [u8]);
+pub struct MMEError<'a>(pub(crate) &'a [u8]);
 impl MMEError<'_> {
     pub fn error(&self) -> ErrorType {
         ErrorType(self.payload()[0])
@@ -39,6 +39,13 @@ impl MessageReader for MMEError<'_> {
         self.0
     }
 }
+impl<'a> TryFrom<&'a [u8]> for MMEError<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 6)?;
+        Ok(Self(data))
+    }
+}
 impl core::fmt::Debug for MMEError<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(
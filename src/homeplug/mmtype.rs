@@ -6,6 +6,17 @@ pub enum MMTypeCode {
     RSP = 0b11,
 }
 
+/// Which specification to name message types by, where they disagree
+///
+/// HomePlug Green PHY (and ISO 15118-3 SLAC, built on it) reuses some of the `CM_` codes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum MMTypeProfile {
+    HomePlugAV,
+    /// Green PHY names, used by [`MMType`]'s `Debug`
+    #[default]
+    GreenPhy,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct MMType(pub u16);
 impl MMType {
@@ -76,7 +87,10 @@ impl MMType {
     pub const CM_MH_CONN_NEW: MMType = MMType(0x6058);
     pub const CM_EXTENDEDTONEMASK: MMType = MMType(0x605C);
     pub const CM_STA_IDENTIFY: MMType = MMType(0x6060);
+    /// HomePlug AV name for `0x6064`, see [`MMType::CM_SLAC_PARM`]
     pub const CM_TRIGGER_ATTEN_CHAR: MMType = MMType(0x6064);
+    /// Green PHY name for `0x6064`, which starts SLAC
+    pub const CM_SLAC_PARM: MMType = MMType(0x6064);
     pub const CM_START_ATTEN_CHAR: MMType = MMType(0x6068);
     pub const CM_ATTEN_CHAR: MMType = MMType(0x606C);
     pub const CM_PKCS_CERT: MMType = MMType(0x6070);
@@ -122,87 +136,98 @@ impl MMType {
     pub const fn is_vendor(&self) -> bool {
         (self.0 >> 13) & 0b111 == 0b101
     }
+    /// Name of the message type (without the `.REQ` etc.), if it's a known standard one
+    pub fn name(&self, profile: MMTypeProfile) -> Option<&'static str> {
+        let name = match self.base() {
+            Self::CC_CCO_APPOINT => "CC_CCO_APPOINT",
+            Self::CC_BACKUP_APPOINT => "CC_BACKUP_APPOINT",
+            Self::CC_LINK_INFO => "CC_LINK_INFO",
+            Self::CC_HANDOVER => "CC_HANDOVER",
+            Self::CC_HANDOVER_INFO => "CC_HANDOVER_INFO",
+            Self::CC_DISCOVER_LIST => "CC_DISCOVER_LIST",
+            Self::CC_LINK_NEW => "CC_LINK_NEW",
+            Self::CC_LINK_MOD => "CC_LINK_MOD",
+            Self::CC_LINK_SQZ => "CC_LINK_SQZ",
+            Self::CC_LINK_REL => "CC_LINK_REL",
+            Self::CC_DETECT_REPORT => "CC_DETECT_REPORT",
+            Self::CC_WHO_RU => "CC_WHO_RU",
+            Self::CC_ASSOC => "CC_ASSOC",
+            Self::CC_LEAVE => "CC_LEAVE",
+            Self::CC_SET_TEI_MAP => "CC_SET_TEI_MAP",
+            Self::CC_RELAY => "CC_RELAY",
+            Self::CC_BEACON_RELIABILITY => "CC_BEACON_RELIABILITY",
+            Self::CC_ALLOC_MOVE => "CC_ALLOC_MOVE",
+            Self::CC_ACCESS_NEW => "CC_ACCESS_NEW",
+            Self::CC_ACCESS_REL => "CC_ACCESS_REL",
+            Self::CC_DCPPC => "CC_DCPPC",
+            Self::CC_HP1_DET => "CC_HP1_DET",
+            Self::CC_BLE_UPDATE => "CC_BLE_UPDATE",
+            Self::CC_BCAST_REPEAT => "CC_BCAST_REPEAT",
+            Self::CC_MH_LINK_NEW => "CC_MH_LINK_NEW",
+            Self::CC_ISP_DETECTION_REPORT => "CC_ISP_DETECTION_REPORT",
+            Self::CC_ISP_START_RESYNC => "CC_ISP_START_RESYNC",
+            Self::CC_ISP_FINISH_RESYNC => "CC_ISP_FINISH_RESYNC",
+            Self::CC_ISP_RESYNC_DETECTED => "CC_ISP_RESYNC_DETECTED",
+            Self::CC_ISP_RESYNC_TRANSMIT => "CC_ISP_RESYNC_TRANSMIT",
+            Self::CC_POWERSAVE => "CC_POWERSAVE",
+            Self::CC_POWERSAVE_EXIT => "CC_POWERSAVE_EXIT",
+            Self::CC_POWERSAVE_LIST => "CC_POWERSAVE_LIST",
+            Self::CC_STOP_POWERSAVE => "CC_STOP_POWERSAVE",
+            Self::CP_PROXY_APPOINT => "CP_PROXY_APPOINT",
+            Self::PH_PROXY_APPOINT => "PH_PROXY_APPOINT",
+            Self::CP_PROXY_WAKE => "CP_PROXY_WAKE",
+            Self::NN_INL => "NN_INL",
+            Self::NN_NEW_NET => "NN_NEW_NET",
+            Self::NN_ADD_ALLOC => "NN_ADD_ALLOC",
+            Self::NN_REL_ALLOC => "NN_REL_ALLOC",
+            Self::NN_REL_NET => "NN_REL_NET",
+            Self::CM_UNASSOCIATED_STA => "CM_UNASSOCIATED_STA",
+            Self::CM_ENCRYPTED_PAYLOAD => "CM_ENCRYPTED_PAYLOAD",
+            Self::CM_SET_KEY => "CM_SET_KEY",
+            Self::CM_GET_KEY => "CM_GET_KEY",
+            Self::CM_SC_JOIN => "CM_SC_JOIN",
+            Self::CM_CHAN_EST => "CM_CHAN_EST",
+            Self::CM_TM_UPDATE => "CM_TM_UPDATE",
+            Self::CM_AMP_MAP => "CM_AMP_MAP",
+            Self::CM_BRG_INFO => "CM_BRG_INFO",
+            Self::CM_CONN_NEW => "CM_CONN_NEW",
+            Self::CM_CONN_REL => "CM_CONN_REL",
+            Self::CM_CONN_MOD => "CM_CONN_MOD",
+            Self::CM_CONN_INFO => "CM_CONN_INFO",
+            Self::CM_STA_CAP => "CM_STA_CAP",
+            Self::CM_NW_INFO => "CM_NW_INFO",
+            Self::CM_GET_BEACON => "CM_GET_BEACON",
+            Self::CM_HFID => "CM_HFID",
+            Self::CM_MME_ERROR => "CM_MME_ERROR",
+            Self::CM_NW_STATS => "CM_NW_STATS",
+            Self::CM_LINK_STATS => "CM_LINK_STATS",
+            Self::CM_ROUTE_INFO => "CM_ROUTE_INFO",
+            Self::CM_UNREACHABLE => "CM_UNREACHABLE",
+            Self::CM_MH_CONN_NEW => "CM_MH_CONN_NEW",
+            Self::CM_EXTENDEDTONEMASK => "CM_EXTENDEDTONEMASK",
+            Self::CM_STA_IDENTIFY => "CM_STA_IDENTIFY",
+            Self::CM_SLAC_PARM => match profile {
+                MMTypeProfile::HomePlugAV => "CM_TRIGGER_ATTEN_CHAR",
+                MMTypeProfile::GreenPhy => "CM_SLAC_PARM",
+            },
+            Self::CM_START_ATTEN_CHAR => "CM_START_ATTEN_CHAR",
+            Self::CM_ATTEN_CHAR => "CM_ATTEN_CHAR",
+            Self::CM_PKCS_CERT => "CM_PKCS_CERT",
+            Self::CM_MNBC_SOUND => "CM_MNBC_SOUND",
+            Self::CM_VALIDATE => "CM_VALIDATE",
+            Self::CM_SLAC_MATCH => "CM_SLAC_MATCH",
+            Self::CM_SLAC_USER_DATA => "CM_SLAC_USER_DATA",
+            Self::CM_ATTEN_PROFILE => "CM_ATTEN_PROFILE",
+            _ => return None,
+        };
+        Some(name)
+    }
 }
 impl core::fmt::Debug for MMType {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        match self.base() {
-            Self::CC_CCO_APPOINT => write!(f, "CC_CCO_APPOINT"),
-            Self::CC_BACKUP_APPOINT => write!(f, "CC_BACKUP_APPOINT"),
-            Self::CC_LINK_INFO => write!(f, "CC_LINK_INFO"),
-            Self::CC_HANDOVER => write!(f, "CC_HANDOVER"),
-            Self::CC_HANDOVER_INFO => write!(f, "CC_HANDOVER_INFO"),
-            Self::CC_DISCOVER_LIST => write!(f, "CC_DISCOVER_LIST"),
-            Self::CC_LINK_NEW => write!(f, "CC_LINK_NEW"),
-            Self::CC_LINK_MOD => write!(f, "CC_LINK_MOD"),
-            Self::CC_LINK_SQZ => write!(f, "CC_LINK_SQZ"),
-            Self::CC_LINK_REL => write!(f, "CC_LINK_REL"),
-            Self::CC_DETECT_REPORT => write!(f, "CC_DETECT_REPORT"),
-            Self::CC_WHO_RU => write!(f, "CC_WHO_RU"),
-            Self::CC_ASSOC => write!(f, "CC_ASSOC"),
-            Self::CC_LEAVE => write!(f, "CC_LEAVE"),
-            Self::CC_SET_TEI_MAP => write!(f, "CC_SET_TEI_MAP"),
-            Self::CC_RELAY => write!(f, "CC_RELAY"),
-            Self::CC_BEACON_RELIABILITY => write!(f, "CC_BEACON_RELIABILITY"),
-            Self::CC_ALLOC_MOVE => write!(f, "CC_ALLOC_MOVE"),
-            Self::CC_ACCESS_NEW => write!(f, "CC_ACCESS_NEW"),
-            Self::CC_ACCESS_REL => write!(f, "CC_ACCESS_REL"),
-            Self::CC_DCPPC => write!(f, "CC_DCPPC"),
-            Self::CC_HP1_DET => write!(f, "CC_HP1_DET"),
-            Self::CC_BLE_UPDATE => write!(f, "CC_BLE_UPDATE"),
-            Self::CC_BCAST_REPEAT => write!(f, "CC_BCAST_REPEAT"),
-            Self::CC_MH_LINK_NEW => write!(f, "CC_MH_LINK_NEW"),
-            Self::CC_ISP_DETECTION_REPORT => write!(f, "CC_ISP_DETECTION_REPORT"),
-            Self::CC_ISP_START_RESYNC => write!(f, "CC_ISP_START_RESYNC"),
-            Self::CC_ISP_FINISH_RESYNC => write!(f, "CC_ISP_FINISH_RESYNC"),
-            Self::CC_ISP_RESYNC_DETECTED => write!(f, "CC_ISP_RESYNC_DETECTED"),
-            Self::CC_ISP_RESYNC_TRANSMIT => write!(f, "CC_ISP_RESYNC_TRANSMIT"),
-            Self::CC_POWERSAVE => write!(f, "CC_POWERSAVE"),
-            Self::CC_POWERSAVE_EXIT => write!(f, "CC_POWERSAVE_EXIT"),
-            Self::CC_POWERSAVE_LIST => write!(f, "CC_POWERSAVE_LIST"),
-            Self::CC_STOP_POWERSAVE => write!(f, "CC_STOP_POWERSAVE"),
-            Self::CP_PROXY_APPOINT => write!(f, "CP_PROXY_APPOINT"),
-            Self::PH_PROXY_APPOINT => write!(f, "PH_PROXY_APPOINT"),
-            Self::CP_PROXY_WAKE => write!(f, "CP_PROXY_WAKE"),
-            Self::NN_INL => write!(f, "NN_INL"),
-            Self::NN_NEW_NET => write!(f, "NN_NEW_NET"),
-            Self::NN_ADD_ALLOC => write!(f, "NN_ADD_ALLOC"),
-            Self::NN_REL_ALLOC => write!(f, "NN_REL_ALLOC"),
-            Self::NN_REL_NET => write!(f, "NN_REL_NET"),
-            Self::CM_UNASSOCIATED_STA => write!(f, "CM_UNASSOCIATED_STA"),
-            Self::CM_ENCRYPTED_PAYLOAD => write!(f, "CM_ENCRYPTED_PAYLOAD"),
-            Self::CM_SET_KEY => write!(f, "CM_SET_KEY"),
-            Self::CM_GET_KEY => write!(f, "CM_GET_KEY"),
-            Self::CM_SC_JOIN => write!(f, "CM_SC_JOIN"),
-            Self::CM_CHAN_EST => write!(f, "CM_CHAN_EST"),
-            Self::CM_TM_UPDATE => write!(f, "CM_TM_UPDATE"),
-            Self::CM_AMP_MAP => write!(f, "CM_AMP_MAP"),
-            Self::CM_BRG_INFO => write!(f, "CM_BRG_INFO"),
-            Self::CM_CONN_NEW => write!(f, "CM_CONN_NEW"),
-            Self::CM_CONN_REL => write!(f, "CM_CONN_REL"),
-            Self::CM_CONN_MOD => write!(f, "CM_CONN_MOD"),
-            Self::CM_CONN_INFO => write!(f, "CM_CONN_INFO"),
-            Self::CM_STA_CAP => write!(f, "CM_STA_CAP"),
-            Self::CM_NW_INFO => write!(f, "CM_NW_INFO"),
-            Self::CM_GET_BEACON => write!(f, "CM_GET_BEACON"),
-            Self::CM_HFID => write!(f, "CM_HFID"),
-            Self::CM_MME_ERROR => write!(f, "CM_MME_ERROR"),
-            Self::CM_NW_STATS => write!(f, "CM_NW_STATS"),
-            Self::CM_LINK_STATS => write!(f, "CM_LINK_STATS"),
-            Self::CM_ROUTE_INFO => write!(f, "CM_ROUTE_INFO"),
-            Self::CM_UNREACHABLE => write!(f, "CM_UNREACHABLE"),
-            Self::CM_MH_CONN_NEW => write!(f, "CM_MH_CONN_NEW"),
-            Self::CM_EXTENDEDTONEMASK => write!(f, "CM_EXTENDEDTONEMASK"),
-            Self::CM_STA_IDENTIFY => write!(f, "CM_STA_IDENTIFY"),
-            Self::CM_TRIGGER_ATTEN_CHAR => write!(f, "CM_TRIGGER_ATTEN_CHAR"),
-            Self::CM_START_ATTEN_CHAR => write!(f, "CM_START_ATTEN_CHAR"),
-            Self::CM_ATTEN_CHAR => write!(f, "CM_ATTEN_CHAR"),
-            Self::CM_PKCS_CERT => write!(f, "CM_PKCS_CERT"),
-            Self::CM_MNBC_SOUND => write!(f, "CM_MNBC_SOUND"),
-            Self::CM_VALIDATE => write!(f, "CM_VALIDATE"),
-            Self::CM_SLAC_MATCH => write!(f, "CM_SLAC_MATCH"),
-            Self::CM_SLAC_USER_DATA => write!(f, "CM_SLAC_USER_DATA"),
-            Self::CM_ATTEN_PROFILE => write!(f, "CM_ATTEN_PROFILE"),
-            _ => match (self.0 >> 13) & 0b111 {
+        match self.name(MMTypeProfile::GreenPhy) {
+            Some(name) => write!(f, "{}", name),
+            None => match (self.0 >> 13) & 0b111 {
                 0b000 => write!(f, "STA<>CCo{:04x}", self.0),
                 0b001 => write!(f, "Proxy{:04x}", self.0),
                 0b010 => write!(f, "CCo<>CCo{:04x}", self.0),
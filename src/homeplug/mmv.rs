@@ -1,4 +1,5 @@
 #[derive(Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MMV(pub u8);
 impl MMV {
     pub const HOMEPLUG_AV_1_0: MMV = MMV(0x00);
@@ -1,31 +1,64 @@
 pub mod broadcom;
+pub mod qualcomm;
+pub mod slac;
+pub mod st;
 
+#[cfg(feature = "std")]
+pub mod dissect;
+#[cfg(feature = "std")]
+pub mod emulator;
+
+#[cfg(feature = "tokio")]
+mod async_request;
 mod bridge_info;
+#[cfg(feature = "std")]
+mod client;
 mod discover_list;
+mod encrypted_payload;
+mod fragment;
+mod frame_writer;
 mod hfid;
+mod link_stats;
 mod message;
 mod mmeerror;
 mod mmtype;
 mod mmv;
 mod network_info;
+mod network_stats;
 mod nmk;
+mod parse_error;
+mod sc_join;
+mod set_key;
 mod station_capabilities;
 
 use crate::{EtherAddr, EtherSocket};
+use core::convert::TryFrom;
 use core::time::Duration;
 use log::warn;
 
+#[cfg(feature = "tokio")]
+pub use async_request::*;
 pub use bridge_info::BridgeInfo;
 pub use bridge_info::*;
+#[cfg(feature = "std")]
+pub use client::*;
 pub use discover_list::DiscoverList;
 pub use discover_list::*;
+pub use encrypted_payload::*;
+pub use fragment::*;
+pub use frame_writer::*;
 pub use hfid::*;
+pub use link_stats::*;
 pub use message::*;
 pub use mmeerror::*;
 pub use mmtype::*;
 pub use mmv::*;
 pub use network_info::*;
+pub use network_stats::*;
 pub use nmk::*;
+pub use parse_error::*;
+pub use sc_join::*;
+pub use set_key::*;
 pub use station_capabilities::StationCapabilities;
 pub use station_capabilities::*;
 
@@ -34,54 +67,139 @@ pub fn discover_devices<T: EtherSocket>(
     socket: &mut T,
     mut callback: impl FnMut(EtherAddr, DiscoverList),
 ) -> Result<(), T::Error> {
-    type M = DiscoverListRequest;
-    let mut buffer = [0; 1500];
+    let mut buffer = [0; ETHERNET_MTU];
     let request = DiscoverListRequest;
-    let bytes = request.encode(&mut buffer);
+    let bytes = request.encode(&mut buffer).unwrap();
     socket.sendto(EtherAddr::BROADCAST, bytes)?;
 
     while let Some((addr, data)) = socket.recvfrom(&mut buffer, Some(Duration::from_millis(100)))? {
-        let msg = UnknownMessage(data);
-        if msg.mmv() == M::MMV && msg.mmtype() == M::MMTYPE.cnf() {
-            callback(addr, DiscoverList::from(data));
-        } else if msg.mmv() == MMV::HOMEPLUG_AV_1_1 && msg.mmtype() == MMType::CM_MME_ERROR.ind() {
-            let error = MMEError(data);
-            warn!("[{addr:?}] {error:?}");
-        } else {
-            warn!("[{addr:?}] {msg:?} - Unexpected message");
+        if let Some(list) = discovery_reply(addr, data) {
+            callback(addr, list);
         }
     }
     Ok(())
 }
 
+/// Decode a reply to `CC_DISCOVER_LIST`, logging anything else
+fn discovery_reply(addr: EtherAddr, data: &[u8]) -> Option<DiscoverList<'_>> {
+    type M = DiscoverListRequest;
+    let msg = match UnknownMessage::try_from(data) {
+        Ok(msg) => msg,
+        Err(err) => {
+            warn!("[{addr:?}] {err}");
+            return None;
+        }
+    };
+    if msg.mmv() == M::MMV && msg.mmtype() == M::MMTYPE.cnf() {
+        match DiscoverList::try_from(data) {
+            Ok(list) => return Some(list),
+            Err(err) => warn!("[{addr:?}] {msg:?} - {err}"),
+        }
+    } else if msg.mmv() == MMV::HOMEPLUG_AV_1_1 && msg.mmtype() == MMType::CM_MME_ERROR.ind() {
+        match MMEError::try_from(data) {
+            Ok(error) => warn!("[{addr:?}] {error:?}"),
+            Err(err) => warn!("[{addr:?}] {msg:?} - {err}"),
+        }
+    } else {
+        warn!("[{addr:?}] {msg:?} - Unexpected message");
+    }
+    None
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RequestError<E> {
+    /// Error from the underlying socket
+    Socket(E),
+    /// Request doesn't fit in the buffer
+    Encode(EncodeError),
+    /// Reply was received, but is malformed or truncated
+    Parse(ParseError),
+}
+impl<E> From<E> for RequestError<E> {
+    fn from(err: E) -> Self {
+        RequestError::Socket(err)
+    }
+}
+impl<E: core::fmt::Display> core::fmt::Display for RequestError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            RequestError::Socket(err) => write!(f, "{}", err),
+            RequestError::Encode(err) => write!(f, "{}", err),
+            RequestError::Parse(err) => write!(f, "{}", err),
+        }
+    }
+}
+
 /// Send a request message and try to receive a reply
-pub fn send_request<'a, M: MessageTX<'a>, T: EtherSocket>(
+///
+/// `buffer` holds both the request and the reply, so should be large enough for the
+/// largest frame expected on the interface (e.g. [`ETHERNET_MTU`], or more for jumbo frames).
+/// Returns `None` if nothing replied in time, and [`RequestError::Parse`] if the reply is
+/// malformed.
+pub fn send_request<'a, M: MessageTX<'a>, T: EtherSocket, B: AsMut<[u8]> + ?Sized>(
     socket: &mut T,
-    buffer: &'a mut [u8; 1500],
+    buffer: &'a mut B,
     destination: EtherAddr,
     request: M,
-) -> Result<Option<M::Response>, T::Error>
+) -> Result<Option<M::Response>, RequestError<T::Error>>
 where
-    M::Response: From<&'a [u8]>,
+    M::Response: TryFrom<&'a [u8], Error = ParseError>,
 {
-    let bytes = request.encode(buffer);
+    let buffer = buffer.as_mut();
+    let bytes = request.encode(buffer).map_err(RequestError::Encode)?;
     socket.sendto(destination, bytes)?;
 
-    let mut result = None;
+    let mut received = None;
     while let Some((addr, data)) = socket.recvfrom(buffer, Some(Duration::from_millis(100)))? {
-        if destination.is_unicast() && addr != destination {
-            continue;
-        }
-        let msg = UnknownMessage(data);
-        if msg.mmv() == M::MMV && msg.mmtype() == M::MMTYPE.cnf() {
-            result = Some(M::Response::from(buffer));
+        if is_response::<M>(destination, addr, data) {
+            received = Some((addr, data.len()));
             break;
-        } else if msg.mmtype() == MMType::CM_MME_ERROR.ind() {
-            let error = MMEError(data);
-            warn!("[{addr:?}] {error:?}");
-        } else {
-            warn!("[{addr:?}] {msg:?} - Unexpected message");
         }
     }
-    Ok(result)
+
+    let buffer: &'a [u8] = buffer;
+    match received {
+        Some((addr, size)) => parse_response::<M, T::Error>(addr, &buffer[..size]).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Whether `data` from `addr` is the reply to request `M` sent to `destination`
+///
+/// Error indications and unexpected messages are logged.
+fn is_response<'a, M: MessageTX<'a>>(destination: EtherAddr, addr: EtherAddr, data: &[u8]) -> bool {
+    if destination.is_unicast() && addr != destination {
+        return false;
+    }
+    let msg = match UnknownMessage::try_from(data) {
+        Ok(msg) => msg,
+        Err(err) => {
+            warn!("[{addr:?}] {err}");
+            return false;
+        }
+    };
+    if msg.mmv() == M::MMV && msg.mmtype() == M::MMTYPE.cnf() {
+        return true;
+    } else if msg.mmtype() == MMType::CM_MME_ERROR.ind() {
+        match MMEError::try_from(data) {
+            Ok(error) => warn!("[{addr:?}] {error:?}"),
+            Err(err) => warn!("[{addr:?}] {msg:?} - {err}"),
+        }
+    } else {
+        warn!("[{addr:?}] {msg:?} - Unexpected message");
+    }
+    false
+}
+
+fn parse_response<'a, M: MessageTX<'a>, E>(
+    addr: EtherAddr,
+    data: &'a [u8],
+) -> Result<M::Response, RequestError<E>>
+where
+    M::Response: TryFrom<&'a [u8], Error = ParseError>,
+{
+    M::Response::try_from(data).map_err(|err| {
+        warn!("[{addr:?}] {err}");
+        RequestError::Parse(err)
+    })
 }
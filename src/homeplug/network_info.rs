@@ -1,5 +1,6 @@
 use super::*;
 use crate::*;
+use core::convert::TryInto;
 
 pub struct NetworkInfoRequest;
 impl<'a> MessageTX<'a> for NetworkInfoRequest {
@@ -9,6 +10,30 @@ impl<'a> MessageTX<'a> for NetworkInfoRequest {
 }
 
 pub struct NetworkInfoEntry<'a>(&'a [u8]);
+impl NetworkInfoEntry<'_> {
+    pub fn nid(&self) -> [u8; 7] {
+        self.0[0..7].try_into().unwrap()
+    }
+    pub fn snid(&self) -> u8 {
+        self.0[7]
+    }
+    pub fn tei(&self) -> u8 {
+        self.0[8]
+    }
+    pub fn role(&self) -> StationRole {
+        StationRole(self.0[9])
+    }
+    pub fn cco_addr(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.0[10..=15])
+    }
+    pub fn access(&self) -> u8 {
+        self.0[16]
+    }
+    /// Number of neighboring networks coordinating with this one
+    pub fn neighbors(&self) -> u8 {
+        self.0[17]
+    }
+}
 impl core::fmt::Debug for NetworkInfoEntry<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         let data = self.0;
@@ -18,7 +43,7 @@ impl core::fmt::Debug for NetworkInfoEntry<'_> {
         let station_role = StationRole(data[9]);
         let cco_macaddr = EtherAddr::from_slice(&data[10..=15]);
         let access = data[16];
-        let num_cord_nws = data[16];
+        let num_cord_nws = data[17];
         write!(
             f,
             "NET[{nid:02x?}/{snid}] tei={tei} role={station_role:?} CCo={cco_macaddr:?} access={access} neighbors={num_cord_nws}",
@@ -29,8 +54,9 @@ impl core::fmt::Debug for NetworkInfoEntry<'_> {
 }
 
 #[derive(Eq, PartialEq, Hash)]
-pub struct NetworkInfo<'a>(pub &'a [u8]);
+pub struct NetworkInfo<'a>(pub(crate) &'a [u8]);
 impl NetworkInfo<'_> {
+    #[allow(mismatched_lifetime_syntaxes, clippy::implied_bounds_in_impls)]
     pub fn networks(&self) -> impl ExactSizeIterator + Iterator<Item = NetworkInfoEntry> + '_ {
         let count = self.payload()[0] as usize;
         self.payload()[1..]
@@ -52,14 +78,19 @@ impl core::fmt::Debug for NetworkInfo<'_> {
         Ok(())
     }
 }
-impl<'a> From<&'a [u8]> for NetworkInfo<'a> {
-    fn from(data: &'a [u8]) -> Self {
-        Self(data)
+impl<'a> TryFrom<&'a [u8]> for NetworkInfo<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 1)?;
+        expect_payload(payload, 1 + payload[0] as usize * 18)?;
+        Ok(Self(data))
     }
 }
 
 #[repr(transparent)]
-#[derive(Default, PartialEq, Eq, Copy, Clone)]
+#[derive(Default, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StationRole(pub u8);
 impl StationRole {
     /// Station
@@ -79,3 +110,51 @@ impl core::fmt::Debug for StationRole {
         }
     }
 }
+
+#[cfg(feature = "alloc")]
+pub use owned::*;
+
+#[cfg(feature = "alloc")]
+mod owned {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Decoded copy of a [`NetworkInfoEntry`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedNetworkInfoEntry {
+        pub nid: [u8; 7],
+        pub snid: u8,
+        pub tei: u8,
+        pub role: StationRole,
+        pub cco_addr: EtherAddr,
+        pub access: u8,
+        pub neighbors: u8,
+    }
+
+    /// Decoded copy of a [`NetworkInfo`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedNetworkInfo {
+        pub networks: Vec<OwnedNetworkInfoEntry>,
+    }
+
+    impl NetworkInfo<'_> {
+        pub fn to_owned(&self) -> OwnedNetworkInfo {
+            let networks = self.networks().map(|network| OwnedNetworkInfoEntry {
+                nid: network.nid(),
+                snid: network.snid(),
+                tei: network.tei(),
+                role: network.role(),
+                cco_addr: network.cco_addr(),
+                access: network.access(),
+                neighbors: network.neighbors(),
+            });
+            OwnedNetworkInfo {
+                networks: networks.collect(),
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use super::*;
+use crate::*;
+
+const STATION_SIZE: usize = 10;
+
+/// CM_NW_STATS - Request the average PHY rates between the device and every other station
+pub struct NetworkStatsRequest;
+impl<'a> MessageTX<'a> for NetworkStatsRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_NW_STATS;
+    type Response = NetworkStats<'a>;
+}
+
+pub struct NetworkStatsStation<'a>(&'a [u8]);
+impl NetworkStatsStation<'_> {
+    pub fn addr(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.0[0..6])
+    }
+    /// Average coded transmit PHY rate to this station in Mbps
+    pub fn tx_rate(&self) -> u16 {
+        u16::from_le_bytes([self.0[6], self.0[7]])
+    }
+    /// Average coded receive PHY rate from this station in Mbps
+    pub fn rx_rate(&self) -> u16 {
+        u16::from_le_bytes([self.0[8], self.0[9]])
+    }
+}
+impl core::fmt::Debug for NetworkStatsStation<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "STA[{:?}] tx={}Mbps rx={}Mbps",
+            self.addr(),
+            self.tx_rate(),
+            self.rx_rate()
+        )
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct NetworkStats<'a>(pub(crate) &'a [u8]);
+impl NetworkStats<'_> {
+    pub fn stations(&self) -> impl ExactSizeIterator<Item = NetworkStatsStation<'_>> {
+        let count = self.payload()[0] as usize;
+        self.payload()[1..]
+            .chunks_exact(STATION_SIZE)
+            .take(count)
+            .map(NetworkStatsStation)
+    }
+}
+impl MessageReader for NetworkStats<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for NetworkStats<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "NetworkStats")?;
+        f.debug_list().entries(self.stations()).finish()
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for NetworkStats<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 1)?;
+        expect_payload(payload, 1 + payload[0] as usize * STATION_SIZE)?;
+        Ok(Self(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn stations() {
+        let data = [
+            0x01, 0x49, 0x60, 0x00, 0x00, // CM_NW_STATS.CNF
+            0x02, // Stations
+            0x00, 0xb0, 0x52, 0x00, 0x00, 0x01, 0x78, 0x00, 0x75, 0x00, // 120/117 Mbps
+            0x00, 0x80, 0xe1, 0x00, 0x00, 0x02, 0x09, 0x00, 0x08, 0x00, // 9/8 Mbps
+        ];
+        let stats = NetworkStats::try_from(&data[..]).unwrap();
+        let mut stations = stats
+            .stations()
+            .map(|station| (station.addr(), station.tx_rate(), station.rx_rate()));
+        assert_eq!(stations.next(), Some((EtherAddr([0x00, 0xb0, 0x52, 0x00, 0x00, 0x01]), 120, 117)));
+        assert_eq!(stations.next(), Some((EtherAddr([0x00, 0x80, 0xe1, 0x00, 0x00, 0x02]), 9, 8)));
+        assert_eq!(stations.next(), None);
+
+        // Trailing padding isn't decoded as stations
+        let mut padded = [0; 60];
+        padded[..data.len()].copy_from_slice(&data);
+        padded[5] = 1;
+        assert_eq!(NetworkStats::try_from(&padded[..]).unwrap().stations().len(), 1);
+
+        // The count must fit in the payload, and the count itself must be present
+        let mut overcounted = data;
+        overcounted[5] = 3;
+        assert_eq!(
+            NetworkStats::try_from(&overcounted[..]),
+            Err(ParseError::TruncatedPayload { expected: 31, actual: 21 })
+        );
+        assert!(NetworkStats::try_from(&data[..5]).is_err());
+        assert!(NetworkStats::try_from(&data[..15]).is_err());
+    }
+}
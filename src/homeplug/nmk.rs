@@ -6,15 +6,26 @@ pub const NMK_HomePlugAV: [u8; 16] = [
 pub const NMK_HomePlugAV0123: [u8; 16] = [
     0xb5, 0x93, 0x19, 0xd7, 0xe8, 0x15, 0x7b, 0xa0, 0x01, 0xb0, 0x18, 0x66, 0x9c, 0xce, 0xe3, 0x0d,
 ];
+/// Device Access Key for the device password "HomePlugAV"
+pub const DAK_HomePlugAV: [u8; 16] = [
+    0x68, 0x9f, 0x07, 0x4b, 0x8b, 0x02, 0x75, 0xa2, 0x71, 0x0b, 0x0b, 0x57, 0x79, 0xad, 0x16, 0x30,
+];
 
+/// Salt for Network Passwords (NPW)
 #[cfg(feature = "sha2")]
-pub fn generate_nmk(s: &str) -> [u8; 16] {
+const NMK_SALT: [u8; 8] = [0x08, 0x85, 0x6d, 0xaf, 0x7c, 0xf5, 0x81, 0x86];
+/// Salt for Device Passwords (DPW)
+#[cfg(feature = "sha2")]
+const DAK_SALT: [u8; 8] = [0x08, 0x85, 0x6d, 0xaf, 0x7c, 0xf5, 0x81, 0x85];
+
+/// Derive a key from a password (PBKDF1 with SHA-256 and 1000 iterations)
+#[cfg(feature = "sha2")]
+fn generate_key(s: &str, salt: [u8; 8]) -> [u8; 16] {
     use core::convert::TryInto;
     use sha2::{Digest, Sha256};
-    let magic = [0x08, 0x85, 0x6d, 0xaf, 0x7c, 0xf5, 0x81, 0x86];
     let mut hasher = Sha256::new();
     hasher.update(s);
-    hasher.update(magic);
+    hasher.update(salt);
     let mut hash = hasher.finalize();
     for _ in 0..999 {
         let mut hasher = Sha256::new();
@@ -24,12 +35,29 @@ pub fn generate_nmk(s: &str) -> [u8; 16] {
     hash[..16].try_into().unwrap()
 }
 
+/// Network Membership Key for a network password
+#[cfg(feature = "sha2")]
+pub fn generate_nmk(s: &str) -> [u8; 16] {
+    generate_key(s, NMK_SALT)
+}
+
+/// Device Access Key for the device password (DPW) printed on an adapter
+#[cfg(feature = "sha2")]
+pub fn generate_dak(dpw: &str) -> [u8; 16] {
+    generate_key(dpw, DAK_SALT)
+}
+
 #[repr(transparent)]
 #[derive(Default, PartialEq, Eq, Copy, Clone)]
 pub struct SecurityLevel(pub u8);
 impl SecurityLevel {
     pub const SIMPLE: Self = Self(0x00);
     pub const SECURE: Self = Self(0x01);
+
+    /// Security level a NID was derived with
+    pub fn of_nid(nid: [u8; 7]) -> Self {
+        Self((nid[6] >> 4) & 0x03)
+    }
 }
 impl core::fmt::Debug for SecurityLevel {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -58,7 +86,7 @@ pub fn generate_nid(nmk: [u8; 16], security: SecurityLevel) -> [u8; 7] {
     hash[..7].try_into().unwrap()
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "sha2"))]
 mod tests {
     use super::*;
 
@@ -97,6 +125,16 @@ mod tests {
         );
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn device_keys() {
+        assert_eq!(generate_dak("HomePlugAV"), DAK_HomePlugAV);
+        assert_eq!(
+            generate_dak("GHJK-PLMN-BVCX-ASDF"),
+            [0xa2, 0xf2, 0x75, 0x88, 0xee, 0x08, 0xa3, 0x6c, 0x68, 0x58, 0x64, 0x7f, 0x02, 0x08, 0xec, 0x0c],
+        );
+    }
+
     #[test]
     fn standard_nids() {
         assert_eq!(
@@ -107,5 +145,7 @@ mod tests {
             generate_nid(NMK_HomePlugAV0123, SecurityLevel::SECURE),
             [0x02, 0x6b, 0xcb, 0xa5, 0x35, 0x4e, 0x18]
         );
+        let nid = generate_nid(NMK_HomePlugAV0123, SecurityLevel::SECURE);
+        assert_eq!(SecurityLevel::of_nid(nid), SecurityLevel::SECURE);
     }
 }
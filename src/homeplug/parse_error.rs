@@ -0,0 +1,131 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Message is too short to contain a management message header
+    TruncatedHeader { length: usize },
+    /// Management message version this library doesn't understand
+    UnsupportedVersion(MMV),
+    /// Payload is shorter than its fixed fields and declared records require
+    TruncatedPayload { expected: usize, actual: usize },
+    /// Records are declared with a size that can't be valid
+    InvalidRecordSize(usize),
+    /// Fragment number is outside the declared number of fragments
+    InvalidFragment { number: usize, count: usize },
+}
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            ParseError::TruncatedHeader { length } => {
+                write!(f, "Message too short for header ({} bytes)", length)
+            }
+            ParseError::UnsupportedVersion(mmv) => {
+                write!(f, "Unsupported message version {:?}", mmv)
+            }
+            ParseError::TruncatedPayload { expected, actual } => write!(
+                f,
+                "Payload truncated ({} bytes, expected {})",
+                actual, expected
+            ),
+            ParseError::InvalidRecordSize(size) => write!(f, "Invalid record size ({})", size),
+            ParseError::InvalidFragment { number, count } => {
+                write!(f, "Invalid fragment number ({}/{})", number, count)
+            }
+        }
+    }
+}
+
+/// Check the header of `bytes` and return the payload
+pub fn parse_payload(bytes: &[u8]) -> Result<&[u8], ParseError> {
+    if bytes.len() < 3 {
+        return Err(ParseError::TruncatedHeader {
+            length: bytes.len(),
+        });
+    }
+    let msg = UnknownMessage(bytes);
+    let size = header_size(msg.mmv(), msg.mmtype());
+    if size == usize::MAX {
+        return Err(ParseError::UnsupportedVersion(msg.mmv()));
+    }
+    if bytes.len() < size {
+        return Err(ParseError::TruncatedHeader {
+            length: bytes.len(),
+        });
+    }
+    Ok(&bytes[size..])
+}
+
+/// Ensure `payload` is at least `expected` bytes long
+pub fn expect_payload(payload: &[u8], expected: usize) -> Result<(), ParseError> {
+    if payload.len() < expected {
+        return Err(ParseError::TruncatedPayload {
+            expected,
+            actual: payload.len(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn headers() {
+        assert_eq!(
+            UnknownMessage::try_from(&[0x01, 0x14][..]).unwrap_err(),
+            ParseError::TruncatedHeader { length: 2 }
+        );
+        assert_eq!(
+            UnknownMessage::try_from(&[0x07, 0x14, 0x00][..]).unwrap_err(),
+            ParseError::UnsupportedVersion(MMV(0x07))
+        );
+        assert_eq!(
+            UnknownMessage::try_from(&[0x01, 0x14, 0x00, 0x00][..]).unwrap_err(),
+            ParseError::TruncatedHeader { length: 4 }
+        );
+        assert!(UnknownMessage::try_from(&[0x01, 0x14, 0x00, 0x00, 0x00][..]).is_ok());
+    }
+
+    #[test]
+    fn payloads() {
+        // CC_DISCOVER_LIST.CNF claiming two stations but carrying one
+        let mut data = [0u8; 5 + 1 + 12];
+        data[..3].copy_from_slice(&[0x01, 0x15, 0x00]);
+        data[5] = 2;
+        assert_eq!(
+            DiscoverList::try_from(&data[..]).unwrap_err(),
+            ParseError::TruncatedPayload {
+                expected: 25,
+                actual: 13
+            }
+        );
+        data[5] = 1;
+        assert_eq!(
+            DiscoverList::try_from(&data[..]).unwrap().stations().len(),
+            1
+        );
+
+        // Broadcom property records with a zero record size
+        let data = [
+            0x02, 0x5d, 0xa0, 0, 0, 0x00, 0x1f, 0x84, 0x01, 0x01, 0x00, 0x00,
+        ];
+        assert_eq!(
+            broadcom::GetProperty::try_from(&data[..]).unwrap_err(),
+            ParseError::InvalidRecordSize(0)
+        );
+
+        // Short replies are rejected rather than panicking in accessors
+        let data = [0x01, 0x41, 0x60, 0x00, 0x00, 0x01, b'a'];
+        assert!(HFID::try_from(&data[..]).is_err());
+        let data = [0x01, 0x35, 0x60, 0x00, 0x00, 0x01];
+        assert!(StationCapabilities::try_from(&data[..]).is_err());
+        let data = [0x01, 0x46, 0x60, 0x00, 0x00, 0x00, 0x01];
+        assert!(MMEError::try_from(&data[..]).is_err());
+        let data = [
+            0x01, 0x21, 0x60, 0x00, 0x00, 0x01, 0x01, 0x02, 0, 0, 0, 0, 0, 0,
+        ];
+        assert!(BridgeInfo::try_from(&data[..]).is_err());
+    }
+}
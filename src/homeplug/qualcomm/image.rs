@@ -0,0 +1,228 @@
+use super::*;
+use crate::*;
+use core::convert::TryInto;
+
+/// Qualcomm image checksum: the complement of all little-endian 32-bit words XORed together
+///
+/// A trailing partial word is zero padded. An image that embeds its own checksum is
+/// valid when the checksum of the whole image is zero.
+pub fn checksum32(data: &[u8]) -> u32 {
+    let mut checksum = 0u32;
+    for word in data.chunks(4) {
+        let mut bytes = [0; 4];
+        bytes[..word.len()].copy_from_slice(word);
+        checksum ^= u32::from_le_bytes(bytes);
+    }
+    !checksum
+}
+
+fn le_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+mod pib {
+    pub const FW_VERSION: usize = 0;
+    pub const PIB_VERSION: usize = 1;
+    pub const LENGTH: usize = 4;
+    pub const CHECKSUM: usize = 8;
+    pub const MAC: usize = 12;
+    pub const DAK: usize = 18;
+    pub const MFG: usize = 36;
+    pub const NMK: usize = 100;
+    pub const USR: usize = 116;
+    pub const NET: usize = 180;
+    pub const SIZE: usize = 244;
+}
+
+/// Header of a Parameter Information Block (`.pib` file, or module [`ModuleId::PIB`])
+#[derive(Copy, Clone)]
+pub struct PibHeader<'a>(&'a [u8]);
+impl<'a> PibHeader<'a> {
+    /// Size of the fixed header fields
+    pub const SIZE: usize = pib::SIZE;
+
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(data, Self::SIZE)?;
+        Ok(Self(data))
+    }
+    pub fn firmware_version(&self) -> u8 {
+        self.0[pib::FW_VERSION]
+    }
+    pub fn pib_version(&self) -> u8 {
+        self.0[pib::PIB_VERSION]
+    }
+    /// Length of the whole PIB, including this header
+    pub fn length(&self) -> usize {
+        u16::from_le_bytes([self.0[pib::LENGTH], self.0[pib::LENGTH + 1]]) as usize
+    }
+    pub fn checksum(&self) -> u32 {
+        le_u32(self.0, pib::CHECKSUM)
+    }
+    pub fn addr(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.0[pib::MAC..pib::MAC + 6])
+    }
+    pub fn dak(&self) -> [u8; 16] {
+        self.0[pib::DAK..pib::DAK + 16].try_into().unwrap()
+    }
+    pub fn nmk(&self) -> [u8; 16] {
+        self.0[pib::NMK..pib::NMK + 16].try_into().unwrap()
+    }
+    pub fn hfid_manufacturer(&self) -> &str {
+        hfid_str(&self.0[pib::MFG..pib::MFG + 64])
+    }
+    pub fn hfid_user(&self) -> &str {
+        hfid_str(&self.0[pib::USR..pib::USR + 64])
+    }
+    pub fn hfid_network(&self) -> &str {
+        hfid_str(&self.0[pib::NET..pib::NET + 64])
+    }
+    /// Whether `image` holds a complete PIB with a valid checksum
+    pub fn verify(&self) -> bool {
+        let length = self.length();
+        length >= Self::SIZE && length <= self.0.len() && checksum32(&self.0[..length]) == 0
+    }
+}
+impl core::fmt::Debug for PibHeader<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "PIB({}.{} length={} mac={:?} mfg='{}' usr='{}')",
+            self.firmware_version(),
+            self.pib_version(),
+            self.length(),
+            self.addr(),
+            self.hfid_manufacturer(),
+            self.hfid_user()
+        )
+    }
+}
+
+fn hfid_str(bytes: &[u8]) -> &str {
+    core::str::from_utf8(bytes)
+        .unwrap_or_default()
+        .trim_end_matches('\0')
+}
+
+/// Fix up the embedded checksum of a PIB after modifying it
+pub fn update_pib_checksum(image: &mut [u8]) -> Result<(), ParseError> {
+    let length = PibHeader::parse(image)?.length();
+    expect_payload(image, length)?;
+    image[pib::CHECKSUM..pib::CHECKSUM + 4].copy_from_slice(&[0; 4]);
+    let checksum = checksum32(&image[..length]);
+    image[pib::CHECKSUM..pib::CHECKSUM + 4].copy_from_slice(&checksum.to_le_bytes());
+    Ok(())
+}
+
+mod nvm {
+    pub const HEADER_VERSION: usize = 0;
+    pub const IMAGE_ROM_ADDRESS: usize = 4;
+    pub const IMAGE_ADDRESS: usize = 8;
+    pub const IMAGE_LENGTH: usize = 12;
+    pub const IMAGE_CHECKSUM: usize = 16;
+    pub const ENTRY_POINT: usize = 20;
+    pub const MINOR_VERSION: usize = 24;
+    pub const IMAGE_TYPE: usize = 25;
+    pub const NEXT_HEADER: usize = 28;
+    pub const SIZE: usize = 36;
+}
+
+/// Version 1 image header of an `.nvm` firmware file (INT6x00, AR7x00 and QCA7x00 families)
+///
+/// An NVM file is a chain of headers, each followed by its image.
+#[derive(Copy, Clone)]
+pub struct NvmHeader<'a> {
+    header: &'a [u8],
+    image: &'a [u8],
+}
+impl<'a> NvmHeader<'a> {
+    pub const SIZE: usize = nvm::SIZE;
+    pub const VERSION: u32 = 0x6000_0000;
+
+    /// Parse the header at the start of `data`, and locate the image that follows it
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(data, Self::SIZE)?;
+        let header = &data[..Self::SIZE];
+        let length = le_u32(header, nvm::IMAGE_LENGTH) as usize;
+        expect_payload(&data[Self::SIZE..], length)?;
+        let image = &data[Self::SIZE..Self::SIZE + length];
+        Ok(Self { header, image })
+    }
+    pub fn version(&self) -> u32 {
+        le_u32(self.header, nvm::HEADER_VERSION)
+    }
+    pub fn minor_version(&self) -> u8 {
+        self.header[nvm::MINOR_VERSION]
+    }
+    pub fn image_type(&self) -> u8 {
+        self.header[nvm::IMAGE_TYPE]
+    }
+    pub fn rom_address(&self) -> u32 {
+        le_u32(self.header, nvm::IMAGE_ROM_ADDRESS)
+    }
+    pub fn address(&self) -> u32 {
+        le_u32(self.header, nvm::IMAGE_ADDRESS)
+    }
+    pub fn entry_point(&self) -> u32 {
+        le_u32(self.header, nvm::ENTRY_POINT)
+    }
+    pub fn image_checksum(&self) -> u32 {
+        le_u32(self.header, nvm::IMAGE_CHECKSUM)
+    }
+    /// Offset of the next header from the start of the file, or zero for the last image
+    pub fn next_header(&self) -> usize {
+        le_u32(self.header, nvm::NEXT_HEADER) as usize
+    }
+    pub fn image(&self) -> &'a [u8] {
+        self.image
+    }
+    /// Whether the header and image checksums are both valid
+    pub fn verify(&self) -> bool {
+        self.version() == Self::VERSION
+            && checksum32(self.header) == 0
+            && checksum32(self.image) == self.image_checksum()
+    }
+}
+impl core::fmt::Debug for NvmHeader<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "NVM(type={} address=0x{:08x} length={} entry=0x{:08x})",
+            self.image_type(),
+            self.address(),
+            self.image.len(),
+            self.entry_point()
+        )
+    }
+}
+
+/// Iterate over the images of an `.nvm` file
+pub fn nvm_images(file: &[u8]) -> impl Iterator<Item = Result<NvmHeader<'_>, ParseError>> {
+    let mut offset = Some(0);
+    core::iter::from_fn(move || {
+        let current = offset?;
+        let header = match file.get(current..) {
+            Some(data) => NvmHeader::parse(data),
+            None => Err(ParseError::TruncatedPayload {
+                expected: current + NvmHeader::SIZE,
+                actual: file.len(),
+            }),
+        };
+        offset = match &header {
+            Ok(header) if header.next_header() > current => Some(header.next_header()),
+            _ => None,
+        };
+        Some(header)
+    })
+}
+
+/// Check every image header and checksum in an `.nvm` file
+pub fn verify_nvm(file: &[u8]) -> bool {
+    let mut images = 0;
+    for header in nvm_images(file) {
+        match header {
+            Ok(header) if header.verify() => images += 1,
+            _ => return false,
+        }
+    }
+    images > 0
+}
@@ -0,0 +1,197 @@
+use super::*;
+use crate::*;
+use core::convert::TryInto;
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct Direction(pub u8);
+impl Direction {
+    pub const TX: Direction = Direction(0x00);
+    pub const RX: Direction = Direction(0x01);
+    pub const BOTH: Direction = Direction(0x02);
+}
+impl core::fmt::Debug for Direction {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::TX => write!(f, "TX"),
+            Self::RX => write!(f, "RX"),
+            Self::BOTH => write!(f, "TX+RX"),
+            _ => write!(f, "Direction{:02x}", self.0),
+        }
+    }
+}
+
+/// VS_LNK_STATS - Request link statistics between the device and a peer station
+pub struct LinkCountersRequest {
+    /// Clear the counters after reading them
+    pub clear: bool,
+    pub direction: Direction,
+    /// Link identifier (priority 0-3, or a connection LID)
+    pub lid: u8,
+    pub peer: EtherAddr,
+}
+impl<'a> MessageTX<'a> for LinkCountersRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_LNK_STATS;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = LinkCounters<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.clear as u8)?;
+        frame.push_u8(self.direction.0)?;
+        frame.push_u8(self.lid)?;
+        frame.push(&self.peer.0)
+    }
+}
+
+// Layouts follow `plc/LinkStatistics.c` from open-plc-utils
+const HEADER_SIZE: usize = 4;
+const TX_SIZE: usize = 5 * 8;
+/// Receive counters, followed by the number of intervals
+const RX_SIZE: usize = 6 * 8 + 1;
+const INTERVAL_SIZE: usize = 1 + 4 * 8;
+
+/// Transmit counters of a link
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TxLinkCounters {
+    pub mpdu_acked: u64,
+    pub mpdu_collisions: u64,
+    pub mpdu_failed: u64,
+    pub pb_passed: u64,
+    pub pb_failed: u64,
+}
+
+/// Receive counters of a link
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RxLinkCounters {
+    pub mpdu_acked: u64,
+    pub mpdu_failed: u64,
+    pub pb_passed: u64,
+    pub pb_failed: u64,
+    /// Sum of the turbo decoder bit errors in PBs that passed
+    pub turbo_ber_passed: u64,
+    /// Sum of the turbo decoder bit errors in PBs that failed
+    pub turbo_ber_failed: u64,
+}
+
+/// Receive counters of a link for one tone map interval
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RxIntervalCounters {
+    /// PHY rate of the interval in Mbps
+    pub phy_rate: u8,
+    pub pb_passed: u64,
+    pub pb_failed: u64,
+    pub turbo_ber_passed: u64,
+    pub turbo_ber_failed: u64,
+}
+
+fn counter(data: &[u8], index: usize) -> u64 {
+    u64::from_le_bytes(data[index * 8..index * 8 + 8].try_into().unwrap())
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct LinkCounters<'a>(pub(crate) &'a [u8]);
+impl LinkCounters<'_> {
+    pub fn status(&self) -> Status {
+        Status(self.payload()[0])
+    }
+    pub fn direction(&self) -> Direction {
+        Direction(self.payload()[1])
+    }
+    pub fn lid(&self) -> u8 {
+        self.payload()[2]
+    }
+    pub fn tei(&self) -> u8 {
+        self.payload()[3]
+    }
+    pub fn tx(&self) -> Option<TxLinkCounters> {
+        if !self.status().is_success() || self.direction() == Direction::RX {
+            return None;
+        }
+        let data = &self.payload()[HEADER_SIZE..];
+        Some(TxLinkCounters {
+            mpdu_acked: counter(data, 0),
+            mpdu_collisions: counter(data, 1),
+            mpdu_failed: counter(data, 2),
+            pb_passed: counter(data, 3),
+            pb_failed: counter(data, 4),
+        })
+    }
+    fn rx_data(&self) -> Option<&[u8]> {
+        let offset = match self.direction() {
+            _ if !self.status().is_success() => return None,
+            Direction::RX => HEADER_SIZE,
+            Direction::BOTH => HEADER_SIZE + TX_SIZE,
+            _ => return None,
+        };
+        Some(&self.payload()[offset..])
+    }
+    pub fn rx(&self) -> Option<RxLinkCounters> {
+        let data = self.rx_data()?;
+        Some(RxLinkCounters {
+            mpdu_acked: counter(data, 0),
+            mpdu_failed: counter(data, 1),
+            pb_passed: counter(data, 2),
+            pb_failed: counter(data, 3),
+            turbo_ber_passed: counter(data, 4),
+            turbo_ber_failed: counter(data, 5),
+        })
+    }
+    /// Receive counters broken down by tone map interval
+    pub fn rx_intervals(&self) -> impl ExactSizeIterator<Item = RxIntervalCounters> + '_ {
+        let data = self.rx_data().unwrap_or(&[]);
+        let count = data.get(RX_SIZE - 1).copied().unwrap_or(0) as usize;
+        data.get(RX_SIZE..)
+            .unwrap_or(&[])
+            .chunks_exact(INTERVAL_SIZE)
+            .take(count)
+            .map(|interval| RxIntervalCounters {
+                phy_rate: interval[0],
+                pb_passed: counter(&interval[1..], 0),
+                pb_failed: counter(&interval[1..], 1),
+                turbo_ber_passed: counter(&interval[1..], 2),
+                turbo_ber_failed: counter(&interval[1..], 3),
+            })
+    }
+}
+impl MessageReader for LinkCounters<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for LinkCounters<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "LinkCounters({:?} tei={}", self.status(), self.tei())?;
+        if let Some(tx) = self.tx() {
+            write!(f, " {:?}", tx)?;
+        }
+        if let Some(rx) = self.rx() {
+            write!(f, " {:?}", rx)?;
+        }
+        write!(f, ")")
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for LinkCounters<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 1)?;
+        if Status(payload[0]).is_success() {
+            expect_payload(payload, HEADER_SIZE)?;
+            let rx = match Direction(payload[1]) {
+                Direction::TX => None,
+                Direction::RX => Some(HEADER_SIZE),
+                _ => Some(HEADER_SIZE + TX_SIZE),
+            };
+            match rx {
+                Some(offset) => {
+                    expect_payload(payload, offset + RX_SIZE)?;
+                    let intervals = payload[offset + RX_SIZE - 1] as usize;
+                    expect_payload(payload, offset + RX_SIZE + intervals * INTERVAL_SIZE)?;
+                }
+                None => expect_payload(payload, HEADER_SIZE + TX_SIZE)?,
+            }
+        }
+        Ok(Self(data))
+    }
+}
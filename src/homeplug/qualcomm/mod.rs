@@ -0,0 +1,230 @@
+//! Qualcomm/Atheros vendor specific management messages (as used by open-plc-utils)
+//!
+//! These use the HomePlug AV 1.0 header, without fragmentation fields.
+
+use super::*;
+
+mod image;
+mod link_counters;
+mod module;
+mod phy_rates;
+mod push_button;
+mod reset_device;
+mod set_nmk;
+mod software_version;
+mod transfer;
+
+pub use image::*;
+pub use link_counters::*;
+pub use module::*;
+pub use phy_rates::*;
+pub use push_button::*;
+pub use reset_device::*;
+pub use set_nmk::*;
+pub use software_version::*;
+pub use transfer::*;
+
+pub const VS_SW_VER: MMType = MMType(0xa000);
+pub const VS_WR_MEM: MMType = MMType(0xa004);
+pub const VS_RD_MEM: MMType = MMType(0xa008);
+pub const VS_ST_MAC: MMType = MMType(0xa00c);
+pub const VS_GET_NVM: MMType = MMType(0xa010);
+pub const VS_RS_DEV: MMType = MMType(0xa01c);
+pub const VS_WR_MOD: MMType = MMType(0xa020);
+pub const VS_RD_MOD: MMType = MMType(0xa024);
+pub const VS_MOD_NVM: MMType = MMType(0xa028);
+pub const VS_WD_RPT: MMType = MMType(0xa02c);
+pub const VS_LNK_STATS: MMType = MMType(0xa030);
+pub const VS_SNIFFER: MMType = MMType(0xa034);
+pub const VS_NW_INFO: MMType = MMType(0xa038);
+pub const VS_CP_RPT: MMType = MMType(0xa040);
+pub const VS_ARPC: MMType = MMType(0xa044);
+pub const VS_PB_ENC: MMType = MMType(0xa04c);
+pub const VS_SET_KEY: MMType = MMType(0xa050);
+pub const VS_MFG_STRING: MMType = MMType(0xa054);
+pub const VS_RD_CBLOCK: MMType = MMType(0xa058);
+pub const VS_SET_SDRAM: MMType = MMType(0xa05c);
+pub const VS_HOST_ACTION: MMType = MMType(0xa060);
+pub const VS_OP_ATTRIBUTES: MMType = MMType(0xa068);
+pub const VS_ENET_SETTINGS: MMType = MMType(0xa06c);
+pub const VS_TONE_MAP_CHAR: MMType = MMType(0xa070);
+pub const VS_NW_INFO_STATS: MMType = MMType(0xa074);
+pub const VS_FAC_DEFAULTS: MMType = MMType(0xa07c);
+
+/// Name of a Qualcomm vendor message type
+pub fn mmtype_name(mmtype: MMType) -> Option<&'static str> {
+    Some(match mmtype.base() {
+        VS_SW_VER => "VS_SW_VER",
+        VS_WR_MEM => "VS_WR_MEM",
+        VS_RD_MEM => "VS_RD_MEM",
+        VS_ST_MAC => "VS_ST_MAC",
+        VS_GET_NVM => "VS_GET_NVM",
+        VS_RS_DEV => "VS_RS_DEV",
+        VS_WR_MOD => "VS_WR_MOD",
+        VS_RD_MOD => "VS_RD_MOD",
+        VS_MOD_NVM => "VS_MOD_NVM",
+        VS_WD_RPT => "VS_WD_RPT",
+        VS_LNK_STATS => "VS_LNK_STATS",
+        VS_SNIFFER => "VS_SNIFFER",
+        VS_NW_INFO => "VS_NW_INFO",
+        VS_CP_RPT => "VS_CP_RPT",
+        VS_ARPC => "VS_ARPC",
+        VS_PB_ENC => "VS_PB_ENC",
+        VS_SET_KEY => "VS_SET_KEY",
+        VS_MFG_STRING => "VS_MFG_STRING",
+        VS_RD_CBLOCK => "VS_RD_CBLOCK",
+        VS_SET_SDRAM => "VS_SET_SDRAM",
+        VS_HOST_ACTION => "VS_HOST_ACTION",
+        VS_OP_ATTRIBUTES => "VS_OP_ATTRIBUTES",
+        VS_ENET_SETTINGS => "VS_ENET_SETTINGS",
+        VS_TONE_MAP_CHAR => "VS_TONE_MAP_CHAR",
+        VS_NW_INFO_STATS => "VS_NW_INFO_STATS",
+        VS_FAC_DEFAULTS => "VS_FAC_DEFAULTS",
+        _ => return None,
+    })
+}
+
+/// MSTATUS field of vendor confirmations
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct Status(pub u8);
+impl Status {
+    pub const SUCCESS: Status = Status(0x00);
+    pub fn is_success(&self) -> bool {
+        *self == Self::SUCCESS
+    }
+}
+impl core::fmt::Debug for Status {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::SUCCESS => write!(f, "Success"),
+            _ => write!(f, "Failure(0x{:02x})", self.0),
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct DeviceId(pub u8);
+impl DeviceId {
+    pub const INT6000: DeviceId = DeviceId(0x01);
+    pub const INT6300: DeviceId = DeviceId(0x02);
+    pub const INT6400: DeviceId = DeviceId(0x03);
+    pub const AR7400: DeviceId = DeviceId(0x04);
+    pub const AR6405: DeviceId = DeviceId(0x05);
+    pub const QCA7450: DeviceId = DeviceId(0x20);
+    pub const QCA7420: DeviceId = DeviceId(0x21);
+    pub const QCA6410: DeviceId = DeviceId(0x22);
+    pub const QCA7000: DeviceId = DeviceId(0x23);
+    pub const QCA7005: DeviceId = DeviceId(0x24);
+    pub const QCA7500: DeviceId = DeviceId(0x25);
+
+    pub fn name(&self) -> Option<&'static str> {
+        DEVICE_NAMES
+            .iter()
+            .find(|(id, _)| *id == self.0)
+            .map(|(_, name)| *name)
+    }
+}
+/// Names of the known [`DeviceId`]s
+pub(crate) const DEVICE_NAMES: &[(u8, &str)] = &[
+    (DeviceId::INT6000.0, "INT6000"),
+    (DeviceId::INT6300.0, "INT6300"),
+    (DeviceId::INT6400.0, "INT6400"),
+    (DeviceId::AR7400.0, "AR7400"),
+    (DeviceId::AR6405.0, "AR6405"),
+    (DeviceId::QCA7450.0, "QCA7450"),
+    (DeviceId::QCA7420.0, "QCA7420"),
+    (DeviceId::QCA6410.0, "QCA6410"),
+    (DeviceId::QCA7000.0, "QCA7000"),
+    (DeviceId::QCA7005.0, "QCA7005"),
+    (DeviceId::QCA7500.0, "QCA7500"),
+];
+impl core::fmt::Debug for DeviceId {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if let Some(name) = self.name() {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "Unknown(0x{:02x})", self.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn phy_rates() {
+        let data = [
+            0x00, 0x39, 0xa0, 0x00, 0xb0, 0x52, // VS_NW_INFO.CNF
+            0x01, // One network
+            0xb0, 0xf2, 0xe6, 0x95, 0x66, 0x6b, 0x03, 0x05, 0x01, 0x02, // NID, SNID, TEI, Role
+            0x00, 0xb0, 0x52, 0x00, 0x00, 0x01, 0x01, 0x02, // CCo, CCo TEI, Stations
+            0x00, 0xb0, 0x52, 0x00, 0x00, 0x02, 0x02, 0x02, 0x00, 0x00, 0x00, 0x00, 0x99, 85, 91,
+            0x00, 0xb0, 0x52, 0x00, 0x00, 0x03, 0x03, 0x02, 0x00, 0x00, 0x00, 0x00, 0x98, 120, 117,
+        ];
+        let rates = PhyRates::try_from(&data[..]).unwrap();
+        assert_eq!(rates.oui(), OUI::QUALCOMM);
+        let network = rates.networks().next().unwrap();
+        assert_eq!(network.role(), StationRole::CCO);
+        let stations: [(u8, u8); 2] = [(85, 91), (120, 117)];
+        assert!(network.stations().map(|s| (s.tx_rate(), s.rx_rate())).eq(stations));
+
+        // Station count larger than the message
+        assert!(PhyRates::try_from(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn link_counters() {
+        // VS_LNK_STATS.CNF for both directions, laid out as in open-plc-utils LinkStatistics.c
+        let mut data = [0; 6 + 4 + 40 + 49 + 33];
+        data[..6].copy_from_slice(&[0x00, 0x31, 0xa0, 0x00, 0xb0, 0x52]);
+        data[6..10].copy_from_slice(&[0x00, 0x02, 0x00, 0x03]); // Status, Direction, LID, TEI
+        let mut counters = (1u64..).map(u64::to_le_bytes);
+        // Transmit and receive counters
+        for counter in data[10..98].chunks_exact_mut(8) {
+            counter.copy_from_slice(&counters.next().unwrap());
+        }
+        data[98] = 1; // One interval
+        data[99] = 100; // PHY rate
+        for counter in data[100..].chunks_exact_mut(8) {
+            counter.copy_from_slice(&counters.next().unwrap());
+        }
+
+        let stats = LinkCounters::try_from(&data[..]).unwrap();
+        assert_eq!(stats.direction(), Direction::BOTH);
+        assert_eq!(stats.tei(), 3);
+        let tx = stats.tx().unwrap();
+        assert_eq!(
+            (tx.mpdu_acked, tx.mpdu_collisions, tx.mpdu_failed),
+            (1, 2, 3)
+        );
+        assert_eq!((tx.pb_passed, tx.pb_failed), (4, 5));
+        let rx = stats.rx().unwrap();
+        assert_eq!(
+            (rx.mpdu_acked, rx.mpdu_failed, rx.pb_passed, rx.pb_failed),
+            (6, 7, 8, 9)
+        );
+        assert_eq!((rx.turbo_ber_passed, rx.turbo_ber_failed), (10, 11));
+        let intervals: [RxIntervalCounters; 1] = [RxIntervalCounters {
+            phy_rate: 100,
+            pb_passed: 12,
+            pb_failed: 13,
+            turbo_ber_passed: 14,
+            turbo_ber_failed: 15,
+        }];
+        assert!(stats.rx_intervals().eq(intervals));
+
+        // Interval count larger than the message
+        assert!(LinkCounters::try_from(&data[..data.len() - 1]).is_err());
+        // Transmit only replies have no receive counters
+        data[7] = 0x00;
+        let stats = LinkCounters::try_from(&data[..50]).unwrap();
+        assert_eq!(stats.tx().unwrap().pb_failed, 5);
+        assert_eq!(stats.rx(), None);
+        assert_eq!(stats.rx_intervals().len(), 0);
+    }
+}
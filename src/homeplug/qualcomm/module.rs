@@ -0,0 +1,219 @@
+use super::*;
+use crate::*;
+use core::convert::TryInto;
+
+/// Largest module chunk carried by a single VS_RD_MOD/VS_WR_MOD message
+pub const MODULE_CHUNK_SIZE: usize = 1024;
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct ModuleId(pub u8);
+impl ModuleId {
+    /// Firmware image (NVM file)
+    pub const FIRMWARE: ModuleId = ModuleId(0x01);
+    /// Parameter Information Block (PIB file)
+    pub const PIB: ModuleId = ModuleId(0x02);
+    /// Firmware and PIB together, for committing both to flash at once
+    pub const FIRMWARE_AND_PIB: ModuleId = ModuleId(0x03);
+}
+impl core::fmt::Debug for ModuleId {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::FIRMWARE => write!(f, "Firmware"),
+            Self::PIB => write!(f, "PIB"),
+            Self::FIRMWARE_AND_PIB => write!(f, "Firmware+PIB"),
+            _ => write!(f, "Module{:02x}", self.0),
+        }
+    }
+}
+
+/// VS_RD_MOD - Read a chunk of a module from device memory
+pub struct ReadModuleRequest {
+    pub module: ModuleId,
+    pub offset: u32,
+    pub length: u16,
+}
+impl<'a> MessageTX<'a> for ReadModuleRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_RD_MOD;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = ReadModule<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.module.0)?;
+        frame.zeros(1)?;
+        frame.push_u16_le(self.length)?;
+        frame.push_u32_le(self.offset)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct ReadModule<'a>(pub(crate) &'a [u8]);
+impl ReadModule<'_> {
+    pub fn status(&self) -> Status {
+        Status(self.payload()[0])
+    }
+    pub fn module(&self) -> ModuleId {
+        ModuleId(self.payload()[4])
+    }
+    pub fn length(&self) -> usize {
+        u16::from_le_bytes([self.payload()[6], self.payload()[7]]) as usize
+    }
+    pub fn offset(&self) -> u32 {
+        u32::from_le_bytes(self.payload()[8..12].try_into().unwrap())
+    }
+    pub fn checksum(&self) -> u32 {
+        u32::from_le_bytes(self.payload()[12..16].try_into().unwrap())
+    }
+    pub fn data(&self) -> &[u8] {
+        &self.payload()[16..16 + self.length()]
+    }
+    /// Whether the data matches the checksum sent with it
+    pub fn is_valid(&self) -> bool {
+        checksum32(self.data()) == self.checksum()
+    }
+}
+impl MessageReader for ReadModule<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for ReadModule<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "ReadModule({:?} {:?} offset={} length={})",
+            self.status(),
+            self.module(),
+            self.offset(),
+            self.length()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for ReadModule<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 1)?;
+        if Status(payload[0]).is_success() {
+            expect_payload(payload, 16)?;
+            let length = u16::from_le_bytes([payload[6], payload[7]]) as usize;
+            expect_payload(payload, 16 + length)?;
+        }
+        Ok(Self(data))
+    }
+}
+
+/// VS_WR_MOD - Write a chunk of a module to device memory
+pub struct WriteModuleRequest<'d> {
+    pub module: ModuleId,
+    pub offset: u32,
+    pub data: &'d [u8],
+}
+impl<'a> MessageTX<'a> for WriteModuleRequest<'_> {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_WR_MOD;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = WriteModule<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.module.0)?;
+        frame.zeros(1)?;
+        frame.push_u16_le(length_u16(self.data)?)?;
+        frame.push_u32_le(self.offset)?;
+        frame.push_u32_le(checksum32(self.data))?;
+        frame.push(self.data)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct WriteModule<'a>(pub(crate) &'a [u8]);
+impl WriteModule<'_> {
+    pub fn status(&self) -> Status {
+        Status(self.payload()[0])
+    }
+    pub fn module(&self) -> ModuleId {
+        ModuleId(self.payload()[1])
+    }
+    pub fn length(&self) -> usize {
+        u16::from_le_bytes([self.payload()[3], self.payload()[4]]) as usize
+    }
+    pub fn offset(&self) -> u32 {
+        u32::from_le_bytes(self.payload()[5..9].try_into().unwrap())
+    }
+}
+impl MessageReader for WriteModule<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for WriteModule<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "WriteModule({:?}", self.status())?;
+        if self.status().is_success() {
+            write!(
+                f,
+                " {:?} offset={} length={}",
+                self.module(),
+                self.offset(),
+                self.length()
+            )?;
+        }
+        write!(f, ")")
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for WriteModule<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 1)?;
+        if Status(payload[0]).is_success() {
+            expect_payload(payload, 9)?;
+        }
+        Ok(Self(data))
+    }
+}
+
+/// VS_MOD_NVM - Commit previously written modules to flash memory
+pub struct CommitModuleRequest {
+    pub module: ModuleId,
+}
+impl<'a> MessageTX<'a> for CommitModuleRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_MOD_NVM;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = CommitModule<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.module.0)?;
+        frame.zeros(1)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct CommitModule<'a>(pub(crate) &'a [u8]);
+impl CommitModule<'_> {
+    pub fn status(&self) -> Status {
+        Status(self.payload()[0])
+    }
+    pub fn module(&self) -> ModuleId {
+        ModuleId(self.payload()[1])
+    }
+}
+impl MessageReader for CommitModule<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for CommitModule<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "CommitModule({:?} {:?})", self.status(), self.module())
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for CommitModule<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 2)?;
+        Ok(Self(data))
+    }
+}
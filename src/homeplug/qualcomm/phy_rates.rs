@@ -0,0 +1,145 @@
+use super::*;
+use crate::*;
+
+const NETWORK_SIZE: usize = 18;
+const STATION_SIZE: usize = 15;
+
+/// VS_NW_INFO - Request the networks and stations known to the device, with PHY rates
+pub struct PhyRatesRequest;
+impl<'a> MessageTX<'a> for PhyRatesRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_NW_INFO;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = PhyRates<'a>;
+}
+
+pub struct PhyRatesStation<'a>(&'a [u8]);
+impl PhyRatesStation<'_> {
+    pub fn addr(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.0[0..6])
+    }
+    pub fn tei(&self) -> u8 {
+        self.0[6]
+    }
+    /// Address of the bridged destination behind this station
+    pub fn bridged_addr(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.0[7..13])
+    }
+    /// Average transmit PHY rate to this station in Mbps
+    pub fn tx_rate(&self) -> u8 {
+        self.0[13]
+    }
+    /// Average receive PHY rate from this station in Mbps
+    pub fn rx_rate(&self) -> u8 {
+        self.0[14]
+    }
+}
+impl core::fmt::Debug for PhyRatesStation<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "STA[{:?}] tei={} bda={:?} tx={}Mbps rx={}Mbps",
+            self.addr(),
+            self.tei(),
+            self.bridged_addr(),
+            self.tx_rate(),
+            self.rx_rate()
+        )
+    }
+}
+
+pub struct PhyRatesNetwork<'a>(&'a [u8]);
+impl PhyRatesNetwork<'_> {
+    pub fn nid(&self) -> [u8; 7] {
+        let mut nid = [0; 7];
+        nid.copy_from_slice(&self.0[0..7]);
+        nid
+    }
+    pub fn snid(&self) -> u8 {
+        self.0[7]
+    }
+    pub fn tei(&self) -> u8 {
+        self.0[8]
+    }
+    pub fn role(&self) -> StationRole {
+        StationRole(self.0[9])
+    }
+    pub fn cco_addr(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.0[10..16])
+    }
+    pub fn cco_tei(&self) -> u8 {
+        self.0[16]
+    }
+    pub fn stations(&self) -> impl ExactSizeIterator<Item = PhyRatesStation<'_>> {
+        let count = self.0[17] as usize;
+        self.0[NETWORK_SIZE..]
+            .chunks_exact(STATION_SIZE)
+            .take(count)
+            .map(PhyRatesStation)
+    }
+}
+impl core::fmt::Debug for PhyRatesNetwork<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        writeln!(
+            f,
+            "NET[{:02x?}/{}] tei={} role={:?} CCo={:?}/{}",
+            self.nid(),
+            self.snid(),
+            self.tei(),
+            self.role(),
+            self.cco_addr(),
+            self.cco_tei()
+        )?;
+        for station in self.stations() {
+            writeln!(f, "  {:?}", station)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct PhyRates<'a>(pub(crate) &'a [u8]);
+impl PhyRates<'_> {
+    pub fn networks(&self) -> impl Iterator<Item = PhyRatesNetwork<'_>> {
+        let payload = self.payload();
+        let mut remaining = payload[0];
+        let mut data = &payload[1..];
+        core::iter::from_fn(move || {
+            if remaining == 0 {
+                return None;
+            }
+            remaining -= 1;
+            let size = NETWORK_SIZE + data[17] as usize * STATION_SIZE;
+            let (network, rest) = data.split_at(size);
+            data = rest;
+            Some(PhyRatesNetwork(network))
+        })
+    }
+}
+impl MessageReader for PhyRates<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for PhyRates<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for network in self.networks() {
+            write!(f, "{:?}", network)?;
+        }
+        Ok(())
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for PhyRates<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 1)?;
+        let mut offset = 1;
+        for _ in 0..payload[0] {
+            expect_payload(payload, offset + NETWORK_SIZE)?;
+            offset += NETWORK_SIZE + payload[offset + 17] as usize * STATION_SIZE;
+            expect_payload(payload, offset)?;
+        }
+        Ok(Self(data))
+    }
+}
@@ -0,0 +1,73 @@
+use super::*;
+use crate::*;
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct PushButtonAction(pub u8);
+impl PushButtonAction {
+    /// Start Simple Connect, joining (or accepting) another adapter
+    pub const JOIN: Self = Self(0x01);
+    /// Leave the current network, by picking a random key
+    pub const LEAVE: Self = Self(0x02);
+    /// Report network membership
+    pub const STATUS: Self = Self(0x03);
+}
+impl core::fmt::Debug for PushButtonAction {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::JOIN => write!(f, "JOIN"),
+            Self::LEAVE => write!(f, "LEAVE"),
+            Self::STATUS => write!(f, "STATUS"),
+            _ => write!(f, "PushButtonAction{:02x}", self.0),
+        }
+    }
+}
+
+/// VS_PB_ENC - Act as if the adapter's Simple Connect button was pressed
+pub struct PushButtonRequest {
+    pub action: PushButtonAction,
+}
+impl<'a> MessageTX<'a> for PushButtonRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_PB_ENC;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = PushButton<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.action.0)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct PushButton<'a>(pub(crate) &'a [u8]);
+impl PushButton<'_> {
+    pub fn status(&self) -> Status {
+        Status(self.payload()[0])
+    }
+    /// Network membership, for [`PushButtonAction::STATUS`]
+    pub fn avln_status(&self) -> u8 {
+        self.payload()[1]
+    }
+}
+impl MessageReader for PushButton<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for PushButton<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "PushButton({:?} status={})",
+            self.status(),
+            self.avln_status()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for PushButton<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 2)?;
+        Ok(Self(data))
+    }
+}
@@ -0,0 +1,36 @@
+use super::*;
+use crate::*;
+
+/// VS_RS_DEV - Reset the device
+pub struct ResetDeviceRequest;
+impl<'a> MessageTX<'a> for ResetDeviceRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_RS_DEV;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = ResetDevice<'a>;
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct ResetDevice<'a>(pub(crate) &'a [u8]);
+impl ResetDevice<'_> {
+    pub fn status(&self) -> Status {
+        Status(self.payload()[0])
+    }
+}
+impl MessageReader for ResetDevice<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for ResetDevice<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "ResetDevice({:?})", self.status())
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for ResetDevice<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 1)?;
+        Ok(Self(data))
+    }
+}
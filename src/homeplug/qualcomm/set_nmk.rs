@@ -0,0 +1,65 @@
+use super::*;
+use crate::*;
+
+/// VS_SET_KEY - Set the network membership key
+///
+/// Without a destination (`peks` of [`EKS_NONE`]) the key is set on the local adapter,
+/// otherwise it is sent to the remote station `rda`, encrypted with its DAK.
+pub struct SetNmkRequest {
+    pub eks: u8,
+    pub nmk: [u8; 16],
+    pub peks: u8,
+    pub rda: EtherAddr,
+    pub dak: [u8; 16],
+}
+impl SetNmkRequest {
+    /// Set `nmk` on the adapter receiving the request
+    pub fn local(nmk: [u8; 16]) -> SetNmkRequest {
+        SetNmkRequest {
+            eks: EKS_NMK,
+            nmk,
+            peks: EKS_NONE,
+            rda: EtherAddr::NULL,
+            dak: [0; 16],
+        }
+    }
+}
+impl<'a> MessageTX<'a> for SetNmkRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_SET_KEY;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = SetNmk<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.eks)?;
+        frame.push(&self.nmk)?;
+        frame.push_u8(self.peks)?;
+        frame.push(&self.rda.0)?;
+        frame.push(&self.dak)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct SetNmk<'a>(pub(crate) &'a [u8]);
+impl SetNmk<'_> {
+    pub fn status(&self) -> Status {
+        Status(self.payload()[0])
+    }
+}
+impl MessageReader for SetNmk<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for SetNmk<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "SetNmk({:?})", self.status())
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for SetNmk<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 1)?;
+        Ok(Self(data))
+    }
+}
@@ -0,0 +1,57 @@
+use super::*;
+use crate::*;
+use core::str;
+
+/// VS_SW_VER - Request firmware version and hardware identity
+pub struct SoftwareVersionRequest;
+impl<'a> MessageTX<'a> for SoftwareVersionRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_0;
+    const MMTYPE: MMType = VS_SW_VER;
+    const OUI: OUI = OUI::QUALCOMM;
+    type Response = SoftwareVersion<'a>;
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct SoftwareVersion<'a>(pub(crate) &'a [u8]);
+impl SoftwareVersion<'_> {
+    pub fn status(&self) -> Status {
+        Status(self.payload()[0])
+    }
+    pub fn device(&self) -> DeviceId {
+        DeviceId(self.payload()[1])
+    }
+    pub fn version_bytes(&self) -> &[u8] {
+        let length = self.payload()[2] as usize;
+        &self.payload()[3..3 + length]
+    }
+    pub fn version(&self) -> &str {
+        str::from_utf8(self.version_bytes())
+            .unwrap_or_default()
+            .trim_end_matches('\0')
+    }
+}
+impl MessageReader for SoftwareVersion<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for SoftwareVersion<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "SoftwareVersion({:?} {:?} '{}')",
+            self.status(),
+            self.device(),
+            self.version()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for SoftwareVersion<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 3)?;
+        expect_payload(payload, 3 + payload[2] as usize)?;
+        Ok(Self(data))
+    }
+}
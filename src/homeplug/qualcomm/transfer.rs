@@ -0,0 +1,491 @@
+//! Chunked module transfers for backing up, restoring and flashing PIB and NVM images
+//!
+//! Each chunk is retried a few times before giving up. Errors report the offset
+//! the transfer reached, so it can be resumed by passing that offset as `start`.
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use super::*;
+use crate::*;
+use log::warn;
+
+/// Attempts made for each chunk before giving up
+const RETRIES: usize = 3;
+
+/// Position of a module transfer, reported after each chunk
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub module: ModuleId,
+    pub offset: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransferError<E> {
+    /// Error from the underlying socket
+    Socket(E),
+    /// Device didn't answer the chunk at `offset`
+    NoResponse { offset: usize },
+    /// Device rejected the chunk at `offset`
+    Failed { status: Status, offset: usize },
+    /// Device answered with a `CM_MME_ERROR.IND` instead of a confirmation
+    Rejected(ErrorType),
+    /// Chunk at `offset` kept arriving with a bad checksum
+    Checksum { offset: usize },
+    /// Image header or checksum isn't valid
+    InvalidImage,
+    /// Buffer can't hold the whole module
+    BufferTooSmall { required: usize },
+    /// Request couldn't be encoded
+    Encode(EncodeError),
+    /// Reply was malformed or truncated
+    Parse(ParseError),
+}
+impl<E> TransferError<E> {
+    /// Offset to resume the transfer from, if it failed partway through
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            TransferError::NoResponse { offset }
+            | TransferError::Failed { offset, .. }
+            | TransferError::Checksum { offset } => Some(offset),
+            _ => None,
+        }
+    }
+}
+impl<E> From<RequestError<E>> for TransferError<E> {
+    fn from(err: RequestError<E>) -> Self {
+        match err {
+            RequestError::Socket(err) => TransferError::Socket(err),
+            RequestError::Encode(err) => TransferError::Encode(err),
+            RequestError::Parse(err) => TransferError::Parse(err),
+        }
+    }
+}
+impl<E: core::fmt::Display> core::fmt::Display for TransferError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            TransferError::Socket(err) => write!(f, "{}", err),
+            TransferError::NoResponse { offset } => write!(f, "No response at offset {}", offset),
+            TransferError::Failed { status, offset } => {
+                write!(f, "Transfer failed at offset {} ({:?})", offset, status)
+            }
+            TransferError::Rejected(error) => write!(f, "Request rejected ({:?})", error),
+            TransferError::Checksum { offset } => write!(f, "Bad checksum at offset {}", offset),
+            TransferError::InvalidImage => write!(f, "Invalid image"),
+            TransferError::BufferTooSmall { required } => {
+                write!(f, "Buffer too small ({} bytes required)", required)
+            }
+            TransferError::Encode(err) => write!(f, "{}", err),
+            TransferError::Parse(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Read the module chunk at `offset` into `chunk`, returning the number of bytes read
+fn read_chunk<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    module: ModuleId,
+    offset: usize,
+    chunk: &mut [u8],
+) -> Result<usize, TransferError<T::Error>> {
+    let mut buffer = [0; ETHERNET_MTU];
+    let mut error = TransferError::NoResponse { offset };
+    for _ in 0..RETRIES {
+        let request = ReadModuleRequest {
+            module,
+            offset: offset as u32,
+            length: chunk.len() as u16,
+        };
+        let response = match send_request(socket, &mut buffer, destination, request) {
+            Ok(Some(response)) => response,
+            Ok(None) => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let status = response.status();
+        if !status.is_success() {
+            return Err(TransferError::Failed { status, offset });
+        }
+        let length = response.length();
+        if response.offset() as usize != offset || length == 0 || length > chunk.len() {
+            warn!("[{destination:?}] {response:?} - Unexpected chunk");
+            continue;
+        }
+        if !response.is_valid() {
+            warn!("[{destination:?}] {response:?} - Bad checksum");
+            error = TransferError::Checksum { offset };
+            continue;
+        }
+        chunk[..length].copy_from_slice(response.data());
+        return Ok(length);
+    }
+    Err(error)
+}
+
+/// Read `image.len()` bytes of `module`, starting from `start`
+pub fn read_module<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    module: ModuleId,
+    image: &mut [u8],
+    start: usize,
+    mut progress: impl FnMut(Progress),
+) -> Result<(), TransferError<T::Error>> {
+    let total = image.len();
+    let mut offset = start;
+    while offset < total {
+        let end = total.min(offset + MODULE_CHUNK_SIZE);
+        offset += read_chunk(socket, destination, module, offset, &mut image[offset..end])?;
+        progress(Progress {
+            module,
+            offset,
+            total,
+        });
+    }
+    Ok(())
+}
+
+/// Read the whole PIB of a device into `image`, returning its length
+///
+/// The PIB checksum is verified once the transfer is complete.
+pub fn read_pib<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    image: &mut [u8],
+    mut progress: impl FnMut(Progress),
+) -> Result<usize, TransferError<T::Error>> {
+    let module = ModuleId::PIB;
+    let first = image.len().min(MODULE_CHUNK_SIZE);
+    if first < PibHeader::SIZE {
+        return Err(TransferError::BufferTooSmall {
+            required: PibHeader::SIZE,
+        });
+    }
+    let received = read_chunk(socket, destination, module, 0, &mut image[..first])?;
+    let length = match PibHeader::parse(&image[..received]) {
+        Ok(header) => header.length(),
+        Err(_) => return Err(TransferError::InvalidImage),
+    };
+    if length > image.len() {
+        return Err(TransferError::BufferTooSmall { required: length });
+    }
+    progress(Progress {
+        module,
+        offset: received.min(length),
+        total: length,
+    });
+    read_module(
+        socket,
+        destination,
+        module,
+        &mut image[..length],
+        received.min(length),
+        progress,
+    )?;
+    match PibHeader::parse(&image[..length]) {
+        Ok(header) if header.verify() => Ok(length),
+        _ => Err(TransferError::InvalidImage),
+    }
+}
+
+/// Write `image` to the device's copy of `module` in memory, starting from `start`
+///
+/// The module isn't stored in flash until it is committed with [`commit_module`].
+pub fn write_module<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    module: ModuleId,
+    image: &[u8],
+    start: usize,
+    mut progress: impl FnMut(Progress),
+) -> Result<(), TransferError<T::Error>> {
+    let total = image.len();
+    let mut buffer = [0; ETHERNET_MTU];
+    let mut offset = start;
+    'chunks: while offset < total {
+        let data = &image[offset..total.min(offset + MODULE_CHUNK_SIZE)];
+        for _ in 0..RETRIES {
+            let request = WriteModuleRequest {
+                module,
+                offset: offset as u32,
+                data,
+            };
+            let response = match send_request(socket, &mut buffer, destination, request) {
+                Ok(Some(response)) => response,
+                Ok(None) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let status = response.status();
+            if !status.is_success() {
+                return Err(TransferError::Failed { status, offset });
+            }
+            if response.offset() as usize != offset || response.length() != data.len() {
+                warn!("[{destination:?}] {response:?} - Unexpected chunk");
+                continue;
+            }
+            offset += data.len();
+            progress(Progress {
+                module,
+                offset,
+                total,
+            });
+            continue 'chunks;
+        }
+        return Err(TransferError::NoResponse { offset });
+    }
+    Ok(())
+}
+
+/// The `CM_MME_ERROR.IND` in `data`, if `destination` sent it about request `M`
+fn error_for<'a, 'd, M: MessageTX<'a>>(
+    destination: EtherAddr,
+    addr: EtherAddr,
+    data: &'d [u8],
+) -> Option<MMEError<'d>> {
+    if destination.is_unicast() && addr != destination {
+        return None;
+    }
+    let msg = UnknownMessage::try_from(data).ok()?;
+    if msg.mmtype() != MMType::CM_MME_ERROR.ind() {
+        return None;
+    }
+    let error = MMEError::try_from(data).ok()?;
+    (error.error_mmv() == M::MMV && error.error_mmtype() == M::MMTYPE).then_some(error)
+}
+
+/// End of the wait for a confirmation, however many unrelated frames arrive meanwhile
+#[cfg(feature = "std")]
+struct Deadline(std::time::Instant);
+#[cfg(feature = "std")]
+impl Deadline {
+    fn after(timeout: Duration) -> Self {
+        Self(std::time::Instant::now() + timeout)
+    }
+    /// Time left to wait, or `None` once the deadline has passed
+    fn remaining(&mut self) -> Option<Duration> {
+        let now = std::time::Instant::now();
+        (now < self.0).then(|| self.0 - now)
+    }
+}
+
+/// Without a clock, the wait ends after [`NO_STD_RECEIVES`] receives of up to `timeout` each
+#[cfg(not(feature = "std"))]
+struct Deadline {
+    timeout: Duration,
+    receives: usize,
+}
+#[cfg(not(feature = "std"))]
+const NO_STD_RECEIVES: usize = 16;
+#[cfg(not(feature = "std"))]
+impl Deadline {
+    fn after(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            receives: NO_STD_RECEIVES,
+        }
+    }
+    fn remaining(&mut self) -> Option<Duration> {
+        self.receives = self.receives.checked_sub(1)?;
+        Some(self.timeout)
+    }
+}
+
+/// Store previously written modules in flash memory
+///
+/// Writing flash can take several seconds, so the confirmation is awaited for up to `timeout`.
+pub fn commit_module<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    module: ModuleId,
+    timeout: Duration,
+) -> Result<(), TransferError<T::Error>> {
+    type M = CommitModuleRequest;
+    let mut buffer = [0; ETHERNET_MTU];
+    let bytes = CommitModuleRequest { module }
+        .encode(&mut buffer)
+        .map_err(TransferError::Encode)?;
+    socket
+        .sendto(destination, bytes)
+        .map_err(TransferError::Socket)?;
+
+    let mut deadline = Deadline::after(timeout);
+    while let Some(remaining) = deadline.remaining() {
+        let (addr, data) = match socket
+            .recvfrom(&mut buffer, Some(remaining))
+            .map_err(TransferError::Socket)?
+        {
+            Some(received) => received,
+            None => break,
+        };
+        if let Some(error) = error_for::<M>(destination, addr, data) {
+            warn!("[{addr:?}] {error:?}");
+            return Err(TransferError::Rejected(error.error()));
+        }
+        if !is_response::<M>(destination, addr, data) {
+            continue;
+        }
+        return match CommitModule::try_from(data) {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => Err(TransferError::Failed {
+                status: response.status(),
+                offset: 0,
+            }),
+            Err(err) => {
+                warn!("[{addr:?}] {err}");
+                Err(TransferError::Parse(err))
+            }
+        };
+    }
+    Err(TransferError::NoResponse { offset: 0 })
+}
+
+/// Check that `image` is a valid PIB or NVM file for `module`
+///
+/// Modules other than [`ModuleId::PIB`] and [`ModuleId::FIRMWARE`] aren't checked.
+pub fn verify_module(module: ModuleId, image: &[u8]) -> bool {
+    match module {
+        ModuleId::PIB => PibHeader::parse(image)
+            .is_ok_and(|header| header.verify() && header.length() == image.len()),
+        ModuleId::FIRMWARE => verify_nvm(image),
+        _ => true,
+    }
+}
+
+/// Verify, write and commit a module image to flash
+pub fn flash_module<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    module: ModuleId,
+    image: &[u8],
+    progress: impl FnMut(Progress),
+) -> Result<(), TransferError<T::Error>> {
+    if !verify_module(module, image) {
+        return Err(TransferError::InvalidImage);
+    }
+    write_module(socket, destination, module, image, 0, progress)?;
+    commit_module(socket, destination, module, Duration::from_secs(10))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::homeplug::emulator::*;
+    use crate::memory::*;
+    use std::vec::Vec;
+
+    const HOST: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x01]);
+    const QCA: EtherAddr = EtherAddr([0x00, 0xb0, 0x52, 0x11, 0x22, 0x33]);
+
+    fn pib(length: usize) -> Vec<u8> {
+        let mut pib: Vec<u8> = (0..length).map(|i| i as u8).collect();
+        pib[4..6].copy_from_slice(&(length as u16).to_le_bytes());
+        pib[12..18].copy_from_slice(&QCA.0);
+        pib[116..180].iter_mut().for_each(|b| *b = 0);
+        pib[116..124].copy_from_slice(b"Upstairs");
+        update_pib_checksum(&mut pib).unwrap();
+        pib
+    }
+
+    fn nvm(length: usize) -> Vec<u8> {
+        let image: Vec<u8> = (0..length).map(|i| (i * 7) as u8).collect();
+        let mut nvm = std::vec![0; NvmHeader::SIZE];
+        nvm[0..4].copy_from_slice(&NvmHeader::VERSION.to_le_bytes());
+        nvm[12..16].copy_from_slice(&(length as u32).to_le_bytes());
+        nvm[16..20].copy_from_slice(&checksum32(&image).to_le_bytes());
+        let checksum = checksum32(&nvm);
+        nvm[32..36].copy_from_slice(&checksum.to_le_bytes());
+        nvm.extend(image);
+        nvm
+    }
+
+    #[test]
+    fn images() {
+        let mut pib = pib(3000);
+        let header = PibHeader::parse(&pib).unwrap();
+        assert!(header.verify());
+        assert_eq!(header.addr(), QCA);
+        assert_eq!(header.hfid_user(), "Upstairs");
+        pib[2000] ^= 1;
+        assert!(!verify_module(ModuleId::PIB, &pib));
+
+        let mut nvm = nvm(5000);
+        assert!(verify_module(ModuleId::FIRMWARE, &nvm));
+        assert_eq!(nvm_images(&nvm).count(), 1);
+        nvm[40] ^= 1;
+        assert!(!verify_nvm(&nvm));
+        assert!(NvmHeader::parse(&nvm[..1000]).is_err());
+
+        // A next header past the end of the file is an error, not a panic
+        nvm[40] ^= 1;
+        let past_end = nvm.len() as u32 + 100;
+        nvm[28..32].copy_from_slice(&past_end.to_le_bytes());
+        let mut images = nvm_images(&nvm);
+        assert!(images.next().unwrap().is_ok());
+        assert!(images.next().unwrap().is_err());
+        assert!(images.next().is_none());
+        assert!(!verify_module(ModuleId::FIRMWARE, &nvm));
+    }
+
+    #[test]
+    fn flash_and_read() {
+        let wire = MemoryWire::new();
+        let emulator = Emulator::new(DeviceState::qualcomm(QCA));
+        let _device = emulator.spawn(&wire.interface("qca", QCA)).unwrap();
+        let host = wire.interface("host", HOST);
+        let mut socket = host.open(EtherType::HOMEPLUG_AV).unwrap();
+
+        let pib = pib(3000);
+        let nvm = nvm(5000);
+        let mut reports = Vec::new();
+        flash_module(&mut socket, QCA, ModuleId::PIB, &pib, |p| reports.push(p)).unwrap();
+        assert_eq!(
+            reports.iter().map(|p| p.offset).collect::<Vec<_>>(),
+            [1024, 2048, 3000]
+        );
+        assert_eq!(emulator.state().module(ModuleId::PIB), Some(&pib[..]));
+
+        // Interrupted transfers can be resumed from the reported offset
+        write_module(
+            &mut socket,
+            QCA,
+            ModuleId::FIRMWARE,
+            &nvm[..2048],
+            0,
+            |_| {},
+        )
+        .unwrap();
+        write_module(&mut socket, QCA, ModuleId::FIRMWARE, &nvm, 2048, |_| {}).unwrap();
+        assert_eq!(emulator.state().module(ModuleId::FIRMWARE), None);
+        let timeout = Duration::from_secs(1);
+        commit_module(&mut socket, QCA, ModuleId::FIRMWARE, timeout).unwrap();
+        assert_eq!(emulator.state().module(ModuleId::FIRMWARE), Some(&nvm[..]));
+
+        let mut buffer = std::vec![0; 16384];
+        let length = read_pib(&mut socket, QCA, &mut buffer, |_| {}).unwrap();
+        assert_eq!(&buffer[..length], &pib[..]);
+        let mut small = [0; 2000];
+        assert!(matches!(
+            read_pib(&mut socket, QCA, &mut small, |_| {}),
+            Err(TransferError::BufferTooSmall { required: 3000 })
+        ));
+
+        assert!(matches!(
+            flash_module(&mut socket, QCA, ModuleId::FIRMWARE, &nvm[..4000], |_| {}),
+            Err(TransferError::InvalidImage)
+        ));
+        let error = read_module(&mut socket, QCA, ModuleId::PIB, &mut buffer, 0, |_| {});
+        assert_eq!(error.unwrap_err().offset(), Some(3000));
+
+        // Devices without the vendor message answer with an error, rather than timing out
+        const BCM: EtherAddr = EtherAddr([0x00, 0x1f, 0x84, 0x44, 0x55, 0x66]);
+        let broadcom = Emulator::new(DeviceState::broadcom(BCM));
+        let _broadcom = broadcom.spawn(&wire.interface("bcm", BCM)).unwrap();
+        assert!(matches!(
+            commit_module(&mut socket, BCM, ModuleId::FIRMWARE, timeout),
+            Err(TransferError::Rejected(ErrorType::NOT_SUPPORTED))
+        ));
+    }
+}
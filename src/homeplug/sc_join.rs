@@ -0,0 +1,53 @@
+use super::*;
+use core::convert::TryInto;
+
+/// CM_SC_JOIN - Join a network in Simple Connect mode (as when its button is pressed)
+pub struct ScJoinRequest {
+    pub cco_capability: u8,
+}
+impl<'a> MessageTX<'a> for ScJoinRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_SC_JOIN;
+    type Response = ScJoin<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.cco_capability)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct ScJoin<'a>(pub(crate) &'a [u8]);
+impl ScJoin<'_> {
+    /// Network the confirming station is a member of
+    pub fn nid(&self) -> [u8; 7] {
+        self.payload()[0..7].try_into().unwrap()
+    }
+    pub fn avln_status(&self) -> u8 {
+        self.payload()[7]
+    }
+    pub fn cco_capability(&self) -> u8 {
+        self.payload()[8]
+    }
+}
+impl MessageReader for ScJoin<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for ScJoin<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "ScJoin(NID:{:02x?} status={})",
+            self.nid(),
+            self.avln_status()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for ScJoin<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 9)?;
+        Ok(Self(data))
+    }
+}
@@ -0,0 +1,323 @@
+use super::*;
+use core::convert::TryInto;
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct KeyType(pub u8);
+impl KeyType {
+    pub const DAK: Self = Self(0x00);
+    pub const NMK: Self = Self(0x01);
+    pub const NEK: Self = Self(0x02);
+    pub const TEK: Self = Self(0x03);
+    pub const HASH_KEY: Self = Self(0x04);
+    pub const NONCE_ONLY: Self = Self(0x05);
+}
+impl core::fmt::Debug for KeyType {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::DAK => write!(f, "DAK"),
+            Self::NMK => write!(f, "NMK"),
+            Self::NEK => write!(f, "NEK"),
+            Self::TEK => write!(f, "TEK"),
+            Self::HASH_KEY => write!(f, "HASH_KEY"),
+            Self::NONCE_ONLY => write!(f, "NONCE_ONLY"),
+            _ => write!(f, "KeyType{:02x}", self.0),
+        }
+    }
+}
+
+/// Protocol a key exchange message belongs to (PID)
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct ProtocolId(pub u8);
+impl ProtocolId {
+    pub const AUTHENTICATE: Self = Self(0x00);
+    pub const PROVIDE_NEK: Self = Self(0x01);
+    pub const PROVIDE_NMK_DAK: Self = Self(0x02);
+    pub const PROVIDE_NMK_UKE: Self = Self(0x03);
+    /// Key provided by the host (Higher Layer Entity) to its local station
+    pub const HLE: Self = Self(0x04);
+}
+impl core::fmt::Debug for ProtocolId {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::AUTHENTICATE => write!(f, "AUTHENTICATE"),
+            Self::PROVIDE_NEK => write!(f, "PROVIDE_NEK"),
+            Self::PROVIDE_NMK_DAK => write!(f, "PROVIDE_NMK_DAK"),
+            Self::PROVIDE_NMK_UKE => write!(f, "PROVIDE_NMK_UKE"),
+            Self::HLE => write!(f, "HLE"),
+            _ => write!(f, "ProtocolId{:02x}", self.0),
+        }
+    }
+}
+
+/// Encryption key select value used for the NMK
+pub const EKS_NMK: u8 = 0x01;
+/// Encryption key select value meaning "no key"
+pub const EKS_NONE: u8 = 0x0f;
+
+/// CM_SET_KEY - Provide a key to a station
+pub struct SetKeyRequest {
+    pub key_type: KeyType,
+    pub my_nonce: u32,
+    pub your_nonce: u32,
+    pub pid: ProtocolId,
+    pub prn: u16,
+    pub pmn: u8,
+    pub cco_capability: u8,
+    pub nid: [u8; 7],
+    pub new_eks: u8,
+    pub new_key: Option<[u8; 16]>,
+}
+impl SetKeyRequest {
+    /// Request for the local station to join the network using `nmk`
+    pub fn nmk(nmk: [u8; 16], nid: [u8; 7]) -> SetKeyRequest {
+        SetKeyRequest {
+            key_type: KeyType::NMK,
+            // Nonces protect exchanges with the CCo, the local station just echoes them
+            my_nonce: 0,
+            your_nonce: 0,
+            pid: ProtocolId::HLE,
+            prn: 0,
+            pmn: 0,
+            cco_capability: 0,
+            nid,
+            new_eks: EKS_NMK,
+            new_key: Some(nmk),
+        }
+    }
+}
+impl<'a> MessageTX<'a> for SetKeyRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_SET_KEY;
+    type Response = SetKey<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.key_type.0)?;
+        frame.push_u32_le(self.my_nonce)?;
+        frame.push_u32_le(self.your_nonce)?;
+        frame.push_u8(self.pid.0)?;
+        frame.push_u16_le(self.prn)?;
+        frame.push_u8(self.pmn)?;
+        frame.push_u8(self.cco_capability)?;
+        frame.push(&self.nid)?;
+        frame.push_u8(self.new_eks)?;
+        match self.new_key {
+            Some(key) => frame.push(&key),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct SetKey<'a>(pub(crate) &'a [u8]);
+impl SetKey<'_> {
+    pub fn is_success(&self) -> bool {
+        self.payload()[0] == 0
+    }
+    pub fn my_nonce(&self) -> u32 {
+        u32::from_le_bytes(self.payload()[1..5].try_into().unwrap())
+    }
+    pub fn your_nonce(&self) -> u32 {
+        u32::from_le_bytes(self.payload()[5..9].try_into().unwrap())
+    }
+    pub fn pid(&self) -> ProtocolId {
+        ProtocolId(self.payload()[9])
+    }
+    pub fn prn(&self) -> u16 {
+        u16::from_le_bytes([self.payload()[10], self.payload()[11]])
+    }
+    pub fn pmn(&self) -> u8 {
+        self.payload()[12]
+    }
+    pub fn cco_capability(&self) -> u8 {
+        self.payload()[13]
+    }
+}
+impl MessageReader for SetKey<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for SetKey<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let result = if self.is_success() {
+            "Success"
+        } else {
+            "Failure"
+        };
+        write!(f, "SetKey({} {:?})", result, self.pid())
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for SetKey<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 14)?;
+        Ok(Self(data))
+    }
+}
+
+/// Derive the NMK and NID for `password`, and program them into the adapter at `destination`
+///
+/// The adapter's vendor is found with `CM_STA_CAP`. Qualcomm adapters are programmed with
+/// `VS_SET_KEY`, which carries no NID: they derive it from their own PIB security level, so
+/// only [`SecurityLevel::SIMPLE`] is supported for them. Broadcom have no documented vendor
+/// message for this, so they (like everything else) are sent the standard `CM_SET_KEY`.
+///
+/// Returns the new NID, or `None` if the adapter didn't accept the key.
+#[cfg(feature = "sha2")]
+pub fn set_network_password<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    password: &str,
+    security: SecurityLevel,
+) -> Result<Option<[u8; 7]>, RequestError<T::Error>> {
+    let nmk = generate_nmk(password);
+    let nid = generate_nid(nmk, security);
+
+    let mut buffer = [0; ETHERNET_MTU];
+    let oui = match send_request(socket, &mut buffer, destination, StationCapabilitiesRequest)? {
+        Some(capabilities) => capabilities.oui(),
+        None => return Ok(None),
+    };
+    let accepted = if oui == crate::OUI::QUALCOMM {
+        if security != SecurityLevel::SIMPLE {
+            warn!("[{destination:?}] {security:?} - Unsupported by Qualcomm devices");
+            return Ok(None);
+        }
+        let request = qualcomm::SetNmkRequest::local(nmk);
+        match send_request(socket, &mut buffer, destination, request)? {
+            Some(response) if response.status().is_success() => true,
+            Some(response) => {
+                warn!("[{destination:?}] {response:?}");
+                false
+            }
+            None => false,
+        }
+    } else {
+        let request = SetKeyRequest::nmk(nmk, nid);
+        match send_request(socket, &mut buffer, destination, request)? {
+            Some(response) if response.is_success() => true,
+            Some(response) => {
+                warn!("[{destination:?}] {response:?}");
+                false
+            }
+            None => false,
+        }
+    };
+    Ok(if accepted { Some(nid) } else { None })
+}
+
+/// Derive the NMK and NID for `password`, and send them to a remote station with a
+/// `CM_SET_KEY` encrypted by the DAK of its device password (`dpw`)
+///
+/// The station doesn't need to be attached to this host, only reachable over the power-line
+/// (e.g. plugged in but not yet a member of any network). `random` should come from a secure
+/// source, and provides the IV (16 bytes), filler and padding (16 bytes), PRN (2 bytes) and
+/// nonce (4 bytes) of the exchange, in that order.
+///
+/// Returns the new NID, or `None` if the station didn't accept the key (or the DAK).
+#[cfg(all(feature = "sha2", feature = "crypto"))]
+pub fn set_remote_network_password<T: EtherSocket>(
+    socket: &mut T,
+    destination: EtherAddr,
+    dpw: &str,
+    password: &str,
+    security: SecurityLevel,
+    random: [u8; 38],
+) -> Result<Option<[u8; 7]>, RequestError<T::Error>> {
+    let (iv, random) = random.split_at(16);
+    let (filler, random) = random.split_at(16);
+    let (prn, nonce) = random.split_at(2);
+    let dak = generate_dak(dpw);
+    let nmk = generate_nmk(password);
+    let nid = generate_nid(nmk, security);
+
+    let pid = ProtocolId::PROVIDE_NMK_DAK;
+    let prn = u16::from_le_bytes(prn.try_into().unwrap());
+    let request = SetKeyRequest {
+        key_type: KeyType::NMK,
+        my_nonce: u32::from_le_bytes(nonce.try_into().unwrap()),
+        your_nonce: 0,
+        pid,
+        prn,
+        pmn: 1,
+        cco_capability: 0,
+        nid,
+        new_eks: EKS_NMK,
+        new_key: Some(nmk),
+    };
+    let mut message = [0; 64];
+    let message = request.encode(&mut message).map_err(RequestError::Encode)?;
+    let indication = EncryptedPayloadIndication {
+        peks: PayloadKeySelect::DAK,
+        avln_status: 0,
+        pid,
+        prn,
+        pmn: 1,
+        iv: iv.try_into().unwrap(),
+        key: dak,
+        random: filler.try_into().unwrap(),
+        message,
+    };
+    let mut buffer = [0; ETHERNET_MTU];
+    let bytes = indication
+        .encode(&mut buffer)
+        .map_err(RequestError::Encode)?;
+    socket.sendto(destination, bytes)?;
+
+    let mut decrypted = [0; ETHERNET_MTU];
+    while let Some((addr, data)) = socket.recvfrom(&mut buffer, Some(Duration::from_millis(100)))? {
+        if addr != destination {
+            continue;
+        }
+        let mmtype = match UnknownMessage::try_from(data) {
+            Ok(msg) => msg.mmtype(),
+            Err(err) => {
+                warn!("[{addr:?}] {err}");
+                continue;
+            }
+        };
+        if mmtype == MMType::CM_ENCRYPTED_PAYLOAD.rsp() {
+            // Only sent when the station couldn't decrypt the request
+            if let Ok(rsp) = EncryptedPayloadRsp::try_from(data) {
+                warn!("[{addr:?}] {rsp:?}");
+            }
+            return Ok(None);
+        } else if mmtype != MMType::CM_ENCRYPTED_PAYLOAD.ind() {
+            warn!("[{addr:?}] {mmtype:?} - Unexpected message");
+            continue;
+        }
+        let ind = match EncryptedPayloadInd::try_from(data) {
+            Ok(ind) => ind,
+            Err(err) => {
+                warn!("[{addr:?}] {err}");
+                continue;
+            }
+        };
+        let key = if ind.peks() == PayloadKeySelect::NMK {
+            nmk
+        } else {
+            dak
+        };
+        let reply = match ind.decrypt(key, &mut decrypted) {
+            Ok(reply) => reply,
+            Err(err) => {
+                warn!("[{addr:?}] {ind:?} - {err}");
+                continue;
+            }
+        };
+        match SetKey::try_from(reply) {
+            Ok(cnf) if cnf.mmtype() == MMType::CM_SET_KEY.cnf() && cnf.prn() == prn => {
+                if cnf.is_success() {
+                    return Ok(Some(nid));
+                }
+                warn!("[{addr:?}] {cnf:?}");
+                return Ok(None);
+            }
+            Ok(cnf) => warn!("[{addr:?}] {cnf:?} - Unexpected message"),
+            Err(err) => warn!("[{addr:?}] {err}"),
+        }
+    }
+    Ok(None)
+}
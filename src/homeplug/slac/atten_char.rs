@@ -0,0 +1,398 @@
+use super::*;
+use core::convert::TryInto;
+
+/// CM_START_ATTEN_CHAR.IND - Broadcast by the PEV before it starts sounding
+pub struct StartAttenCharIndication {
+    pub application_type: ApplicationType,
+    pub security_type: SecurityType,
+    pub num_sounds: u8,
+    /// Time the EVSEs should collect sounds for, in 100ms units
+    pub time_out: u8,
+    pub resp_type: u8,
+    /// The PEV's address
+    pub forwarding_sta: EtherAddr,
+    pub run_id: [u8; 8],
+}
+impl<'a> MessageTX<'a> for StartAttenCharIndication {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_START_ATTEN_CHAR.ind();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.application_type.0)?;
+        frame.push_u8(self.security_type.0)?;
+        frame.push_u8(self.num_sounds)?;
+        frame.push_u8(self.time_out)?;
+        frame.push_u8(self.resp_type)?;
+        frame.push(&self.forwarding_sta.0)?;
+        frame.push(&self.run_id)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct StartAttenCharInd<'a>(pub(crate) &'a [u8]);
+impl StartAttenCharInd<'_> {
+    pub fn application_type(&self) -> ApplicationType {
+        ApplicationType(self.payload()[0])
+    }
+    pub fn security_type(&self) -> SecurityType {
+        SecurityType(self.payload()[1])
+    }
+    pub fn num_sounds(&self) -> u8 {
+        self.payload()[2]
+    }
+    pub fn time_out(&self) -> u8 {
+        self.payload()[3]
+    }
+    pub fn resp_type(&self) -> u8 {
+        self.payload()[4]
+    }
+    pub fn forwarding_sta(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[5..11])
+    }
+    pub fn run_id(&self) -> [u8; 8] {
+        self.payload()[11..19].try_into().unwrap()
+    }
+}
+impl MessageReader for StartAttenCharInd<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for StartAttenCharInd<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "StartAttenCharInd(sounds={} timeout={} RunID:{:02x?})",
+            self.num_sounds(),
+            self.time_out(),
+            self.run_id()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for StartAttenCharInd<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 19)?;
+        Ok(Self(data))
+    }
+}
+
+/// CM_MNBC_SOUND.IND - Sounding message broadcast by the PEV
+pub struct MnbcSoundIndication {
+    pub application_type: ApplicationType,
+    pub security_type: SecurityType,
+    /// Zero for PEV-EVSE matching
+    pub sender_id: [u8; 17],
+    /// Number of sounds remaining after this one
+    pub count: u8,
+    pub run_id: [u8; 8],
+    pub random: [u8; 16],
+}
+impl<'a> MessageTX<'a> for MnbcSoundIndication {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_MNBC_SOUND.ind();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.application_type.0)?;
+        frame.push_u8(self.security_type.0)?;
+        frame.push(&self.sender_id)?;
+        frame.push_u8(self.count)?;
+        frame.push(&self.run_id)?;
+        frame.zeros(8)?;
+        frame.push(&self.random)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct MnbcSoundInd<'a>(pub(crate) &'a [u8]);
+impl MnbcSoundInd<'_> {
+    pub fn application_type(&self) -> ApplicationType {
+        ApplicationType(self.payload()[0])
+    }
+    pub fn security_type(&self) -> SecurityType {
+        SecurityType(self.payload()[1])
+    }
+    pub fn sender_id(&self) -> [u8; 17] {
+        self.payload()[2..19].try_into().unwrap()
+    }
+    pub fn count(&self) -> u8 {
+        self.payload()[19]
+    }
+    pub fn run_id(&self) -> [u8; 8] {
+        self.payload()[20..28].try_into().unwrap()
+    }
+    pub fn random(&self) -> [u8; 16] {
+        self.payload()[36..52].try_into().unwrap()
+    }
+}
+impl MessageReader for MnbcSoundInd<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for MnbcSoundInd<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "MnbcSoundInd(count={} RunID:{:02x?})",
+            self.count(),
+            self.run_id()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for MnbcSoundInd<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 52)?;
+        Ok(Self(data))
+    }
+}
+
+/// CM_ATTEN_PROFILE.IND - Sent by an EVSE's station to its host for each sound it receives
+pub struct AttenProfileIndication<'p> {
+    /// The PEV the sound came from
+    pub pev: EtherAddr,
+    /// Attenuation (dB) of each carrier group
+    pub attenuation: &'p [u8],
+}
+impl<'a> MessageTX<'a> for AttenProfileIndication<'_> {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_ATTEN_PROFILE.ind();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        let groups = self.attenuation.len();
+        if groups > u8::MAX as usize {
+            return Err(EncodeError::FieldTooLarge {
+                length: groups,
+                max: u8::MAX as usize,
+            });
+        }
+        frame.push(&self.pev.0)?;
+        frame.push_u8(groups as u8)?;
+        frame.zeros(1)?;
+        frame.push(self.attenuation)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct AttenProfileInd<'a>(pub(crate) &'a [u8]);
+impl AttenProfileInd<'_> {
+    pub fn pev(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[0..6])
+    }
+    pub fn num_groups(&self) -> usize {
+        self.payload()[6] as usize
+    }
+    pub fn attenuation(&self) -> &[u8] {
+        &self.payload()[8..8 + self.num_groups()]
+    }
+}
+impl MessageReader for AttenProfileInd<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for AttenProfileInd<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "AttenProfileInd(PEV:{:?} {:?})",
+            self.pev(),
+            self.attenuation()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for AttenProfileInd<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 8)?;
+        expect_payload(payload, 8 + payload[6] as usize)?;
+        Ok(Self(data))
+    }
+}
+
+/// CM_ATTEN_CHAR.IND - Sent by an EVSE to the PEV with the attenuation it measured
+pub struct AttenCharIndication<'p> {
+    pub application_type: ApplicationType,
+    pub security_type: SecurityType,
+    /// The PEV's address
+    pub source_address: EtherAddr,
+    pub run_id: [u8; 8],
+    pub source_id: [u8; 17],
+    pub resp_id: [u8; 17],
+    /// Number of sounds the attenuation was averaged over
+    pub num_sounds: u8,
+    /// Average attenuation (dB) of each carrier group
+    pub attenuation: &'p [u8],
+}
+impl<'a> MessageTX<'a> for AttenCharIndication<'_> {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_ATTEN_CHAR.ind();
+    type Response = AttenCharRsp<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        let groups = self.attenuation.len();
+        if groups > u8::MAX as usize {
+            return Err(EncodeError::FieldTooLarge {
+                length: groups,
+                max: u8::MAX as usize,
+            });
+        }
+        frame.push_u8(self.application_type.0)?;
+        frame.push_u8(self.security_type.0)?;
+        frame.push(&self.source_address.0)?;
+        frame.push(&self.run_id)?;
+        frame.push(&self.source_id)?;
+        frame.push(&self.resp_id)?;
+        frame.push_u8(self.num_sounds)?;
+        frame.push_u8(groups as u8)?;
+        frame.push(self.attenuation)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct AttenCharInd<'a>(pub(crate) &'a [u8]);
+impl AttenCharInd<'_> {
+    pub fn application_type(&self) -> ApplicationType {
+        ApplicationType(self.payload()[0])
+    }
+    pub fn security_type(&self) -> SecurityType {
+        SecurityType(self.payload()[1])
+    }
+    pub fn source_address(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[2..8])
+    }
+    pub fn run_id(&self) -> [u8; 8] {
+        self.payload()[8..16].try_into().unwrap()
+    }
+    pub fn source_id(&self) -> [u8; 17] {
+        self.payload()[16..33].try_into().unwrap()
+    }
+    pub fn resp_id(&self) -> [u8; 17] {
+        self.payload()[33..50].try_into().unwrap()
+    }
+    pub fn num_sounds(&self) -> u8 {
+        self.payload()[50]
+    }
+    pub fn num_groups(&self) -> usize {
+        self.payload()[51] as usize
+    }
+    pub fn attenuation(&self) -> &[u8] {
+        &self.payload()[52..52 + self.num_groups()]
+    }
+    /// Mean attenuation over all carrier groups, in dB
+    pub fn average_attenuation(&self) -> u8 {
+        let attenuation = self.attenuation();
+        if attenuation.is_empty() {
+            return u8::MAX;
+        }
+        let total: usize = attenuation.iter().map(|&a| a as usize).sum();
+        (total / attenuation.len()) as u8
+    }
+}
+impl MessageReader for AttenCharInd<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for AttenCharInd<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "AttenCharInd(PEV:{:?} sounds={} average={}dB RunID:{:02x?})",
+            self.source_address(),
+            self.num_sounds(),
+            self.average_attenuation(),
+            self.run_id()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for AttenCharInd<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        let payload = parse_payload(data)?;
+        expect_payload(payload, 52)?;
+        expect_payload(payload, 52 + payload[51] as usize)?;
+        Ok(Self(data))
+    }
+}
+
+/// CM_ATTEN_CHAR.RSP - Sent by the PEV to acknowledge each EVSE's `CM_ATTEN_CHAR.IND`
+pub struct AttenCharResponse {
+    pub application_type: ApplicationType,
+    pub security_type: SecurityType,
+    /// The PEV's address
+    pub source_address: EtherAddr,
+    pub run_id: [u8; 8],
+    pub source_id: [u8; 17],
+    pub resp_id: [u8; 17],
+    /// Zero on success
+    pub result: u8,
+}
+impl<'a> MessageTX<'a> for AttenCharResponse {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_ATTEN_CHAR.rsp();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.application_type.0)?;
+        frame.push_u8(self.security_type.0)?;
+        frame.push(&self.source_address.0)?;
+        frame.push(&self.run_id)?;
+        frame.push(&self.source_id)?;
+        frame.push(&self.resp_id)?;
+        frame.push_u8(self.result)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct AttenCharRsp<'a>(pub(crate) &'a [u8]);
+impl AttenCharRsp<'_> {
+    pub fn application_type(&self) -> ApplicationType {
+        ApplicationType(self.payload()[0])
+    }
+    pub fn security_type(&self) -> SecurityType {
+        SecurityType(self.payload()[1])
+    }
+    pub fn source_address(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[2..8])
+    }
+    pub fn run_id(&self) -> [u8; 8] {
+        self.payload()[8..16].try_into().unwrap()
+    }
+    pub fn source_id(&self) -> [u8; 17] {
+        self.payload()[16..33].try_into().unwrap()
+    }
+    pub fn resp_id(&self) -> [u8; 17] {
+        self.payload()[33..50].try_into().unwrap()
+    }
+    pub fn is_success(&self) -> bool {
+        self.payload()[50] == 0
+    }
+}
+impl MessageReader for AttenCharRsp<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for AttenCharRsp<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let result = if self.is_success() {
+            "Success"
+        } else {
+            "Failure"
+        };
+        write!(f, "AttenCharRsp({} RunID:{:02x?})", result, self.run_id())
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for AttenCharRsp<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 51)?;
+        Ok(Self(data))
+    }
+}
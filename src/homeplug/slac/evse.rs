@@ -0,0 +1,329 @@
+use super::*;
+use log::info;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EvseState {
+    /// Not started
+    Idle,
+    /// Waiting for a PEV's `CM_SLAC_PARM.REQ`
+    WaitParam,
+    /// Waiting for `CM_START_ATTEN_CHAR.IND`
+    WaitStartAttenChar,
+    /// Collecting attenuation profiles of the PEV's sounds
+    Sounding,
+    /// Waiting for the PEV to acknowledge `CM_ATTEN_CHAR.IND`
+    WaitAttenCharRsp,
+    /// Waiting for `CM_SLAC_MATCH.REQ`
+    WaitMatch,
+    Matched,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvseConfig {
+    /// Address of the EVSE (the host running SLAC, not its station)
+    pub address: EtherAddr,
+    pub evse_id: [u8; 17],
+    /// Logical network the PEV joins once matched
+    pub nid: [u8; 7],
+    pub nmk: [u8; 16],
+    /// Number of sounds requested from the PEV
+    pub num_sounds: u8,
+    /// Time to collect sounds for, after `CM_START_ATTEN_CHAR.IND`
+    pub sounding_time: Duration,
+    /// Time to wait for the PEV to start
+    pub init_timeout: Duration,
+}
+impl EvseConfig {
+    /// Configuration with the standard timers
+    pub fn new(address: EtherAddr, nid: [u8; 7], nmk: [u8; 16]) -> EvseConfig {
+        EvseConfig {
+            address,
+            evse_id: [0; 17],
+            nid,
+            nmk,
+            num_sounds: C_EV_MATCH_MNBC,
+            sounding_time: TT_EVSE_MATCH_MNBC,
+            init_timeout: TT_EVSE_SLAC_INIT,
+        }
+    }
+}
+
+/// EVSE (charger) side of SLAC
+///
+/// The EVSE's station must already be a member of the network in [`EvseConfig`], and be
+/// configured to send `CM_ATTEN_PROFILE.IND` for each sound it hears.
+///
+/// Call [`Evse::handle`] with every frame received, and whenever [`Evse::deadline`] passes.
+/// `now` can be measured from any fixed point, as long as it's the same for every call.
+/// After an error the match has failed, and a new `Evse` should be started.
+#[derive(Debug, Clone)]
+pub struct Evse {
+    config: EvseConfig,
+    state: EvseState,
+    deadline: Option<Duration>,
+    pev: EtherAddr,
+    run_id: [u8; 8],
+    retries: usize,
+    /// Number of attenuation profiles received, and their totals for each group
+    profiles: usize,
+    groups: usize,
+    totals: [u32; ATTEN_GROUPS],
+}
+impl Evse {
+    pub fn new(config: EvseConfig) -> Evse {
+        Evse {
+            config,
+            state: EvseState::Idle,
+            deadline: Some(Duration::ZERO),
+            pev: EtherAddr::NULL,
+            run_id: [0; 8],
+            retries: 0,
+            profiles: 0,
+            groups: 0,
+            totals: [0; ATTEN_GROUPS],
+        }
+    }
+    pub fn state(&self) -> EvseState {
+        self.state
+    }
+    /// When [`Evse::handle`] next needs calling, even if nothing is received
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    /// Process a received frame (if any) and expired timers, returning the match once made
+    pub fn handle<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+        frame: Option<(EtherAddr, &[u8])>,
+    ) -> Result<Option<Matched>, SlacError<T::Error>> {
+        let mut matched = None;
+        if let Some((addr, data)) = frame {
+            matched = self.receive(socket, now, addr, data)?;
+        }
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            self.expire(socket, now)?;
+        }
+        Ok(matched)
+    }
+
+    /// Run until a PEV matches, using the system clock
+    #[cfg(feature = "std")]
+    pub fn run<T: EtherSocket>(&mut self, socket: &mut T) -> Result<Matched, SlacError<T::Error>> {
+        run(self, socket)
+    }
+
+    fn receive<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+        addr: EtherAddr,
+        data: &[u8],
+    ) -> Result<Option<Matched>, SlacError<T::Error>> {
+        let mmtype = match UnknownMessage::try_from(data) {
+            Ok(msg) => msg.mmtype(),
+            Err(_) => return Ok(None),
+        };
+        if mmtype == MMType::CM_SLAC_PARM.req() {
+            let waiting = match self.state {
+                EvseState::WaitParam => true,
+                // The PEV didn't get the confirmation, and is retrying
+                EvseState::WaitStartAttenChar => addr == self.pev,
+                _ => false,
+            };
+            if let Some(req) = parse::<SlacParamReq>(addr, data).filter(|_| waiting) {
+                info!("[{addr:?}] {req:?}");
+                if req.application_type() != ApplicationType::PEV_EVSE
+                    || req.security_type() != SecurityType::NONE
+                {
+                    warn!("[{addr:?}] {req:?} - Unsupported");
+                    return Ok(None);
+                }
+                self.pev = addr;
+                self.run_id = req.run_id();
+                let time_out = (self.config.sounding_time.as_millis() / 100).min(255) as u8;
+                let cnf = SlacParamConfirm {
+                    msound_target: EtherAddr::BROADCAST,
+                    num_sounds: self.config.num_sounds,
+                    time_out,
+                    resp_type: 0x01,
+                    forwarding_sta: addr,
+                    application_type: ApplicationType::PEV_EVSE,
+                    security_type: SecurityType::NONE,
+                    run_id: self.run_id,
+                };
+                send(socket, addr, cnf)?;
+                self.state = EvseState::WaitStartAttenChar;
+                self.deadline = Some(now + TT_MATCH_SEQUENCE);
+            }
+        } else if mmtype == MMType::CM_START_ATTEN_CHAR.ind() {
+            let ind = parse::<StartAttenCharInd>(addr, data);
+            if self.state == EvseState::WaitStartAttenChar
+                && ind.is_some_and(|ind| self.is_run(addr, ind.run_id()))
+            {
+                self.start_sounding(now);
+            }
+        } else if mmtype == MMType::CM_MNBC_SOUND.ind() {
+            // The start indications may all have been lost
+            let ind = parse::<MnbcSoundInd>(addr, data);
+            if self.state == EvseState::WaitStartAttenChar
+                && ind.is_some_and(|ind| self.is_run(addr, ind.run_id()))
+            {
+                self.start_sounding(now);
+            }
+        } else if mmtype == MMType::CM_ATTEN_PROFILE.ind() {
+            // Sent by the EVSE's own station, on behalf of the PEV
+            let profile = parse::<AttenProfileInd>(addr, data);
+            if let Some(profile) = profile.filter(|p| p.pev() == self.pev) {
+                if self.state == EvseState::Sounding {
+                    let attenuation = profile.attenuation();
+                    self.groups = attenuation.len().min(ATTEN_GROUPS);
+                    for (total, &atten) in self.totals.iter_mut().zip(attenuation) {
+                        *total += atten as u32;
+                    }
+                    self.profiles += 1;
+                }
+            }
+        } else if mmtype == MMType::CM_ATTEN_CHAR.rsp() {
+            let rsp = parse::<AttenCharRsp>(addr, data);
+            if let Some(rsp) = rsp.filter(|rsp| self.is_run(addr, rsp.run_id())) {
+                if self.state == EvseState::WaitAttenCharRsp {
+                    info!("[{addr:?}] {rsp:?}");
+                    if !rsp.is_success() {
+                        return Err(SlacError::Rejected);
+                    }
+                    self.state = EvseState::WaitMatch;
+                    self.deadline = Some(now + TT_EVSE_MATCH_SESSION);
+                }
+            }
+        } else if mmtype == MMType::CM_VALIDATE.req() {
+            let req = parse::<ValidateReq>(addr, data);
+            if let Some(req) = req.filter(|_| self.state == EvseState::WaitMatch) {
+                if addr == self.pev {
+                    info!("[{addr:?}] {req:?}");
+                    let cnf = ValidateConfirm {
+                        signal_type: req.signal_type(),
+                        toggle_num: 0,
+                        result: ValidateResult::NOT_REQUIRED,
+                    };
+                    send(socket, addr, cnf)?;
+                }
+            }
+        } else if mmtype == MMType::CM_SLAC_MATCH.req() {
+            let waiting = matches!(
+                self.state,
+                // The PEV may not have received the confirmation, and be retrying
+                EvseState::WaitAttenCharRsp | EvseState::WaitMatch | EvseState::Matched
+            );
+            let req = parse::<SlacMatchReq>(addr, data).filter(|_| waiting);
+            if let Some(req) = req.filter(|req| self.is_run(addr, req.run_id())) {
+                info!("[{addr:?}] {req:?}");
+                let cnf = SlacMatchConfirm {
+                    application_type: ApplicationType::PEV_EVSE,
+                    security_type: SecurityType::NONE,
+                    pev_id: req.pev_id(),
+                    pev_mac: req.pev_mac(),
+                    evse_id: self.config.evse_id,
+                    evse_mac: self.config.address,
+                    run_id: self.run_id,
+                    nid: self.config.nid,
+                    nmk: self.config.nmk,
+                };
+                send(socket, addr, cnf)?;
+                if self.state != EvseState::Matched {
+                    self.state = EvseState::Matched;
+                    self.deadline = None;
+                    return Ok(Some(Matched {
+                        pev: self.pev,
+                        evse: self.config.address,
+                        run_id: self.run_id,
+                        nid: self.config.nid,
+                        nmk: self.config.nmk,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn expire<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+    ) -> Result<(), SlacError<T::Error>> {
+        match self.state {
+            EvseState::Idle => {
+                self.state = EvseState::WaitParam;
+                self.deadline = Some(now + self.config.init_timeout);
+            }
+            EvseState::WaitParam => return Err(SlacError::Timeout("TT_EVSE_SLAC_init")),
+            EvseState::WaitStartAttenChar => return Err(SlacError::Timeout("TT_match_sequence")),
+            EvseState::Sounding => {
+                if self.profiles == 0 {
+                    return Err(SlacError::Timeout("TT_EVSE_match_MNBC"));
+                }
+                self.send_atten_char(socket)?;
+                self.state = EvseState::WaitAttenCharRsp;
+                self.retries = 0;
+                self.deadline = Some(now + TT_MATCH_RESPONSE);
+            }
+            EvseState::WaitAttenCharRsp => {
+                if self.retries >= C_EV_MATCH_RETRY {
+                    return Err(SlacError::Timeout("TT_match_response"));
+                }
+                self.send_atten_char(socket)?;
+                self.retries += 1;
+                self.deadline = Some(now + TT_MATCH_RESPONSE);
+            }
+            EvseState::WaitMatch => return Err(SlacError::Timeout("TT_EVSE_match_session")),
+            EvseState::Matched => self.deadline = None,
+        }
+        Ok(())
+    }
+
+    fn is_run(&self, addr: EtherAddr, run_id: [u8; 8]) -> bool {
+        addr == self.pev && run_id == self.run_id
+    }
+
+    fn start_sounding(&mut self, now: Duration) {
+        self.state = EvseState::Sounding;
+        self.profiles = 0;
+        self.groups = 0;
+        self.totals = [0; ATTEN_GROUPS];
+        self.deadline = Some(now + self.config.sounding_time);
+    }
+
+    fn send_atten_char<T: EtherSocket>(&self, socket: &mut T) -> Result<(), SlacError<T::Error>> {
+        let mut attenuation = [0; ATTEN_GROUPS];
+        for (average, total) in attenuation.iter_mut().zip(&self.totals) {
+            *average = (total / self.profiles as u32) as u8;
+        }
+        let ind = AttenCharIndication {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            source_address: self.pev,
+            run_id: self.run_id,
+            source_id: [0; 17],
+            resp_id: [0; 17],
+            num_sounds: self.profiles.min(255) as u8,
+            attenuation: &attenuation[..self.groups],
+        };
+        send(socket, self.pev, ind)?;
+        Ok(())
+    }
+}
+#[cfg(feature = "std")]
+impl Machine for Evse {
+    fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+    fn handle<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+        frame: Option<(EtherAddr, &[u8])>,
+    ) -> Result<Option<Matched>, SlacError<T::Error>> {
+        Evse::handle(self, socket, now, frame)
+    }
+}
@@ -0,0 +1,622 @@
+//! Signal Level Attenuation Characterization (SLAC), as used by ISO 15118-3 and DIN 70121
+//!
+//! SLAC matches an electric vehicle (PEV) with the charger (EVSE) it's plugged into. The PEV
+//! sends sounding messages which every EVSE that hears them measures the attenuation of, then
+//! joins the logical network of the EVSE that heard it loudest.
+//!
+//! Each message has a struct for sending it (e.g. [`SlacParamRequest`]) and a reader for
+//! receiving it, named after its primitive (e.g. [`SlacParamReq`]). [`Evse`] and [`Pev`] run
+//! the matching process itself.
+
+use super::*;
+use core::time::Duration;
+
+mod atten_char;
+mod evse;
+mod pev;
+mod slac_match;
+mod slac_param;
+mod validate;
+
+pub use atten_char::*;
+pub use evse::*;
+pub use pev::*;
+pub use slac_match::*;
+pub use slac_param::*;
+pub use validate::*;
+
+/// TP_EV_batch_msg_interval - Gap between each `CM_START_ATTEN_CHAR` and `CM_MNBC_SOUND`
+pub const TP_EV_BATCH_MSG_INTERVAL: Duration = Duration::from_millis(40);
+/// TT_EV_atten_results - Time the PEV waits for `CM_ATTEN_CHAR` after starting sounding
+pub const TT_EV_ATTEN_RESULTS: Duration = Duration::from_millis(1200);
+/// TT_EVSE_match_MNBC - Time the EVSE collects sounds for
+pub const TT_EVSE_MATCH_MNBC: Duration = Duration::from_millis(600);
+/// TT_match_sequence - Time to wait for the next message of the sequence
+pub const TT_MATCH_SEQUENCE: Duration = Duration::from_millis(400);
+/// TT_match_response - Time to wait for the response to a request
+pub const TT_MATCH_RESPONSE: Duration = Duration::from_millis(200);
+/// TT_EVSE_match_session - Time the EVSE waits for `CM_SLAC_MATCH` after `CM_ATTEN_CHAR`
+pub const TT_EVSE_MATCH_SESSION: Duration = Duration::from_secs(10);
+/// TT_EVSE_SLAC_init - Time the EVSE waits for `CM_SLAC_PARM` after the PEV is plugged in
+pub const TT_EVSE_SLAC_INIT: Duration = Duration::from_secs(50);
+/// C_EV_match_retry - Number of times a request is repeated before giving up
+pub const C_EV_MATCH_RETRY: usize = 2;
+/// C_EV_start_atten_char_inds - Number of `CM_START_ATTEN_CHAR` indications sent
+pub const C_EV_START_ATTEN_CHAR_INDS: usize = 3;
+/// C_EV_match_MNBC - Number of sounds the PEV sends
+pub const C_EV_MATCH_MNBC: u8 = 10;
+
+/// Number of attenuation groups (carrier groups) in an attenuation profile
+pub const ATTEN_GROUPS: usize = 58;
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct ApplicationType(pub u8);
+impl ApplicationType {
+    /// Matching a PEV with an EVSE
+    pub const PEV_EVSE: Self = Self(0x00);
+}
+impl core::fmt::Debug for ApplicationType {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::PEV_EVSE => write!(f, "PEV_EVSE"),
+            _ => write!(f, "ApplicationType{:02x}", self.0),
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct SecurityType(pub u8);
+impl SecurityType {
+    pub const NONE: Self = Self(0x00);
+    pub const PUBLIC_KEY: Self = Self(0x01);
+}
+impl core::fmt::Debug for SecurityType {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::NONE => write!(f, "NONE"),
+            Self::PUBLIC_KEY => write!(f, "PUBLIC_KEY"),
+            _ => write!(f, "SecurityType{:02x}", self.0),
+        }
+    }
+}
+
+/// A PEV and EVSE that have matched, and the logical network they share
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Matched {
+    pub pev: EtherAddr,
+    pub evse: EtherAddr,
+    pub run_id: [u8; 8],
+    pub nid: [u8; 7],
+    /// Network membership key, for the PEV to program into its station with `CM_SET_KEY`
+    pub nmk: [u8; 16],
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SlacError<E> {
+    Request(RequestError<E>),
+    /// The named timer expired before the other side replied
+    Timeout(&'static str),
+    /// The other side rejected the match
+    Rejected,
+}
+impl<E> From<RequestError<E>> for SlacError<E> {
+    fn from(err: RequestError<E>) -> Self {
+        SlacError::Request(err)
+    }
+}
+impl<E: core::fmt::Display> core::fmt::Display for SlacError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SlacError::Request(err) => write!(f, "{}", err),
+            SlacError::Timeout(timer) => write!(f, "SLAC timed out ({})", timer),
+            SlacError::Rejected => write!(f, "SLAC match rejected"),
+        }
+    }
+}
+
+fn send<'a, T: EtherSocket, M: MessageTX<'a>>(
+    socket: &mut T,
+    destination: EtherAddr,
+    message: M,
+) -> Result<(), RequestError<T::Error>> {
+    let mut buffer = [0; ETHERNET_MTU];
+    let bytes = message.encode(&mut buffer).map_err(RequestError::Encode)?;
+    socket.sendto(destination, bytes)?;
+    Ok(())
+}
+
+/// Parse a received SLAC message, logging any that are malformed
+fn parse<'a, M: TryFrom<&'a [u8], Error = ParseError>>(
+    addr: EtherAddr,
+    data: &'a [u8],
+) -> Option<M> {
+    match M::try_from(data) {
+        Ok(message) => Some(message),
+        Err(err) => {
+            warn!("[{addr:?}] {err}");
+            None
+        }
+    }
+}
+
+/// Common interface of [`Evse`] and [`Pev`], so they can share [`run`]
+#[cfg(feature = "std")]
+trait Machine {
+    fn deadline(&self) -> Option<Duration>;
+    fn handle<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+        frame: Option<(EtherAddr, &[u8])>,
+    ) -> Result<Option<Matched>, SlacError<T::Error>>;
+}
+
+/// Drive `machine` from `socket` until it matches or fails
+#[cfg(feature = "std")]
+fn run<T: EtherSocket, M: Machine>(
+    machine: &mut M,
+    socket: &mut T,
+) -> Result<Matched, SlacError<T::Error>> {
+    extern crate std;
+    let start = std::time::Instant::now();
+    let mut buffer = [0; ETHERNET_MTU];
+    loop {
+        let timeout = machine
+            .deadline()
+            .map(|deadline| deadline.saturating_sub(start.elapsed()));
+        // A zero timeout means "wait forever" to some sockets
+        let frame = match timeout {
+            Some(timeout) if timeout.is_zero() => None,
+            timeout => socket
+                .recvfrom(&mut buffer, timeout)
+                .map_err(RequestError::Socket)?,
+        };
+        if let Some(matched) = machine.handle(socket, start.elapsed(), frame)? {
+            return Ok(matched);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::memory::*;
+    use crate::*;
+    use core::convert::Infallible;
+    use core::sync::atomic::{AtomicU8, Ordering};
+    use std::collections::HashSet;
+    use std::thread;
+    use std::vec::Vec;
+
+    const PEV: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x01]);
+    const EVSE: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x02]);
+    const STATION: EtherAddr = EtherAddr([0x00, 0xb0, 0x52, 0, 0, 0x02]);
+    const NID: [u8; 7] = [0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+    const NMK: [u8; 16] = [0x55; 16];
+    const EVSE2: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x03]);
+    const RUN_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    /// Records what's sent, so the state machines can be driven with a fake clock
+    #[derive(Debug, Default)]
+    struct Capture {
+        sent: Vec<(EtherAddr, Vec<u8>)>,
+    }
+    impl Capture {
+        /// Remove and return the messages of type `mmtype` sent so far
+        fn take(&mut self, mmtype: MMType) -> Vec<(EtherAddr, Vec<u8>)> {
+            let is_type = |data: &[u8]| UnknownMessage::try_from(data).unwrap().mmtype() == mmtype;
+            let (taken, rest) = self.sent.drain(..).partition(|(_, data)| is_type(data));
+            self.sent = rest;
+            taken
+        }
+    }
+    impl EtherSocket for Capture {
+        type Error = Infallible;
+        fn sendto(&mut self, destination: EtherAddr, data: &[u8]) -> Result<(), Infallible> {
+            self.sent.push((destination, data.to_vec()));
+            Ok(())
+        }
+        fn recvfrom<'a>(
+            &mut self,
+            _buffer: &'a mut [u8],
+            _timeout: Option<Duration>,
+        ) -> Result<Option<(EtherAddr, &'a [u8])>, Infallible> {
+            Ok(None)
+        }
+    }
+
+    type Handled = Result<Option<Matched>, SlacError<Infallible>>;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn frame<'a, M: MessageTX<'a>>(message: M) -> Vec<u8> {
+        let mut buffer = [0; ETHERNET_MTU];
+        message.encode(&mut buffer).unwrap().to_vec()
+    }
+
+    /// Call `handle` at every deadline up to `until`
+    fn advance<M: Machine>(machine: &mut M, socket: &mut Capture, until: Duration) -> Handled {
+        while let Some(deadline) = machine.deadline().filter(|&deadline| deadline <= until) {
+            if let Some(matched) = machine.handle(socket, deadline, None)? {
+                return Ok(Some(matched));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Different bytes for every sound
+    fn random(bytes: &mut [u8; 16]) {
+        static NEXT: AtomicU8 = AtomicU8::new(0);
+        bytes.fill(NEXT.fetch_add(1, Ordering::Relaxed));
+    }
+
+    fn pev_config() -> PevConfig {
+        PevConfig {
+            address: PEV,
+            pev_id: [0; 17],
+            run_id: RUN_ID,
+            random,
+        }
+    }
+
+    fn param_cnf() -> Vec<u8> {
+        frame(SlacParamConfirm {
+            msound_target: EtherAddr::BROADCAST,
+            num_sounds: C_EV_MATCH_MNBC,
+            time_out: 6,
+            resp_type: 0x01,
+            forwarding_sta: PEV,
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            run_id: RUN_ID,
+        })
+    }
+
+    fn atten_char_ind(attenuation: &[u8]) -> Vec<u8> {
+        frame(AttenCharIndication {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            source_address: PEV,
+            run_id: RUN_ID,
+            source_id: [0; 17],
+            resp_id: [0; 17],
+            num_sounds: C_EV_MATCH_MNBC,
+            attenuation,
+        })
+    }
+
+    fn atten_char_rsp(result: u8) -> Vec<u8> {
+        frame(AttenCharResponse {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            source_address: PEV,
+            run_id: RUN_ID,
+            source_id: [0; 17],
+            resp_id: [0; 17],
+            result,
+        })
+    }
+
+    fn match_cnf(evse: EtherAddr) -> Vec<u8> {
+        frame(SlacMatchConfirm {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            pev_id: [0; 17],
+            pev_mac: PEV,
+            evse_id: [0; 17],
+            evse_mac: evse,
+            run_id: RUN_ID,
+            nid: NID,
+            nmk: NMK,
+        })
+    }
+
+    /// An EVSE that the PEV has started sounding to, at 20ms
+    fn sounding_evse(socket: &mut Capture) -> Evse {
+        let mut evse = Evse::new(EvseConfig::new(EVSE, NID, NMK));
+        assert_eq!(evse.handle(socket, ms(0), None), Ok(None));
+        let req = frame(SlacParamRequest {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            run_id: RUN_ID,
+        });
+        assert_eq!(evse.handle(socket, ms(10), Some((PEV, &req))), Ok(None));
+        assert_eq!(socket.take(MMType::CM_SLAC_PARM.cnf()).len(), 1);
+        let ind = frame(StartAttenCharIndication {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            num_sounds: C_EV_MATCH_MNBC,
+            time_out: 6,
+            resp_type: 0x01,
+            forwarding_sta: PEV,
+            run_id: RUN_ID,
+        });
+        assert_eq!(evse.handle(socket, ms(20), Some((PEV, &ind))), Ok(None));
+        assert_eq!(evse.state(), EvseState::Sounding);
+        evse
+    }
+
+    /// An EVSE that has sent its first `CM_ATTEN_CHAR.IND`, at 620ms
+    fn characterized_evse(socket: &mut Capture) -> Evse {
+        let mut evse = sounding_evse(socket);
+        let profile = frame(AttenProfileIndication {
+            pev: PEV,
+            attenuation: &[20; ATTEN_GROUPS],
+        });
+        assert_eq!(
+            evse.handle(socket, ms(100), Some((STATION, &profile))),
+            Ok(None)
+        );
+        assert_eq!(
+            advance(&mut evse, socket, ms(20) + TT_EVSE_MATCH_MNBC),
+            Ok(None)
+        );
+        assert_eq!(evse.state(), EvseState::WaitAttenCharRsp);
+        let sent = socket.take(MMType::CM_ATTEN_CHAR.ind());
+        assert_eq!(sent.len(), 1);
+        let ind = AttenCharInd::try_from(&sent[0].1[..]).unwrap();
+        assert_eq!(
+            (sent[0].0, ind.attenuation()),
+            (PEV, &[20; ATTEN_GROUPS][..])
+        );
+        evse
+    }
+
+    #[test]
+    fn matching() {
+        let wire = MemoryWire::new();
+        let open = |name, addr| {
+            let interface = wire.interface(name, addr);
+            interface.open(EtherType::HOMEPLUG_AV).unwrap()
+        };
+        let mut pev = open("pev", PEV);
+        let mut evse = open("evse", EVSE);
+        let mut station = open("station", STATION);
+
+        // The EVSE's station reports the attenuation of each sound to its host
+        let station = thread::spawn(move || {
+            let mut buffer = [0; ETHERNET_MTU];
+            let timeout = Some(Duration::from_secs(1));
+            let mut sounds = 0;
+            while let Some((addr, data)) = station.recvfrom(&mut buffer, timeout).unwrap() {
+                let msg = UnknownMessage::try_from(data).unwrap();
+                if msg.mmtype() == MMType::CM_MNBC_SOUND.ind() {
+                    sounds += 1;
+                    let profile = AttenProfileIndication {
+                        pev: addr,
+                        attenuation: &[20; ATTEN_GROUPS],
+                    };
+                    send(&mut station, EVSE, profile).unwrap();
+                }
+            }
+            sounds
+        });
+        let evse = thread::spawn(move || {
+            let config = EvseConfig::new(EVSE, NID, NMK);
+            Evse::new(config).run(&mut evse)
+        });
+
+        let matched = Pev::new(pev_config()).run(&mut pev).unwrap();
+        assert_eq!(matched.evse, EVSE);
+        assert_eq!((matched.nid, matched.nmk), (NID, NMK));
+        assert_eq!(evse.join().unwrap().unwrap(), matched);
+        assert_eq!(station.join().unwrap(), C_EV_MATCH_MNBC);
+    }
+
+    #[test]
+    fn evse_timers() {
+        // Nothing heard while sounding
+        let mut socket = Capture::default();
+        let mut evse = sounding_evse(&mut socket);
+        let expired = evse.handle(&mut socket, ms(20) + TT_EVSE_MATCH_MNBC, None);
+        assert_eq!(expired, Err(SlacError::Timeout("TT_EVSE_match_MNBC")));
+
+        // CM_ATTEN_CHAR.IND is repeated until the PEV responds
+        let mut evse = characterized_evse(&mut socket);
+        let end = ms(620) + TT_MATCH_RESPONSE * (C_EV_MATCH_RETRY as u32 + 1);
+        assert_eq!(advance(&mut evse, &mut socket, end - ms(1)), Ok(None));
+        let resent = socket.take(MMType::CM_ATTEN_CHAR.ind());
+        assert_eq!(resent.len(), C_EV_MATCH_RETRY);
+        let expired = evse.handle(&mut socket, end, None);
+        assert_eq!(expired, Err(SlacError::Timeout("TT_match_response")));
+
+        // The PEV never asks to match
+        let mut evse = characterized_evse(&mut socket);
+        let rsp = atten_char_rsp(0);
+        assert_eq!(
+            evse.handle(&mut socket, ms(700), Some((PEV, &rsp))),
+            Ok(None)
+        );
+        assert_eq!(evse.state(), EvseState::WaitMatch);
+        let end = ms(700) + TT_EVSE_MATCH_SESSION;
+        assert_eq!(evse.handle(&mut socket, end - ms(1), None), Ok(None));
+        let expired = evse.handle(&mut socket, end, None);
+        assert_eq!(expired, Err(SlacError::Timeout("TT_EVSE_match_session")));
+    }
+
+    #[test]
+    fn evse_rejected() {
+        let mut socket = Capture::default();
+        let mut evse = characterized_evse(&mut socket);
+        // Responses from another PEV are ignored
+        let rsp = atten_char_rsp(1);
+        assert_eq!(
+            evse.handle(&mut socket, ms(700), Some((EVSE2, &rsp))),
+            Ok(None)
+        );
+        let rejected = evse.handle(&mut socket, ms(710), Some((PEV, &rsp)));
+        assert_eq!(rejected, Err(SlacError::Rejected));
+    }
+
+    #[test]
+    fn pev_timers() {
+        // No EVSE answers CM_SLAC_PARM.REQ, which is repeated C_EV_match_retry times
+        let mut socket = Capture::default();
+        let mut pev = Pev::new(pev_config());
+        let end = TT_MATCH_RESPONSE * (C_EV_MATCH_RETRY as u32 + 1);
+        assert_eq!(advance(&mut pev, &mut socket, end - ms(1)), Ok(None));
+        let sent = socket.take(MMType::CM_SLAC_PARM.req());
+        assert_eq!(sent.len(), 1 + C_EV_MATCH_RETRY);
+        assert!(sent.iter().all(|(addr, _)| *addr == EtherAddr::BROADCAST));
+        let expired = pev.handle(&mut socket, end, None);
+        assert_eq!(expired, Err(SlacError::Timeout("TT_match_response")));
+
+        // An EVSE answers, but never sends its results
+        let mut pev = Pev::new(pev_config());
+        assert_eq!(pev.handle(&mut socket, ms(0), None), Ok(None));
+        let cnf = param_cnf();
+        assert_eq!(
+            pev.handle(&mut socket, ms(10), Some((EVSE, &cnf))),
+            Ok(None)
+        );
+        let end = TT_MATCH_RESPONSE + TT_EV_ATTEN_RESULTS;
+        assert_eq!(advance(&mut pev, &mut socket, end - ms(1)), Ok(None));
+        assert_eq!(pev.state(), PevState::WaitAttenChar);
+        let starts = socket.take(MMType::CM_START_ATTEN_CHAR.ind());
+        assert_eq!(starts.len(), C_EV_START_ATTEN_CHAR_INDS);
+        let sounds = socket.take(MMType::CM_MNBC_SOUND.ind());
+        assert_eq!(sounds.len(), C_EV_MATCH_MNBC as usize);
+        // Each sound carries its own random bytes
+        let random: HashSet<_> = sounds
+            .iter()
+            .map(|(_, data)| MnbcSoundInd::try_from(&data[..]).unwrap().random())
+            .collect();
+        assert_eq!(random.len(), sounds.len());
+        let expired = pev.handle(&mut socket, end, None);
+        assert_eq!(expired, Err(SlacError::Timeout("TT_EV_atten_results")));
+    }
+
+    #[test]
+    fn pev_choice() {
+        let mut socket = Capture::default();
+        let mut pev = Pev::new(pev_config());
+        assert_eq!(pev.handle(&mut socket, ms(0), None), Ok(None));
+        let cnf = param_cnf();
+        assert_eq!(
+            pev.handle(&mut socket, ms(10), Some((EVSE, &cnf))),
+            Ok(None)
+        );
+        assert_eq!(
+            pev.handle(&mut socket, ms(20), Some((EVSE2, &cnf))),
+            Ok(None)
+        );
+        assert_eq!(advance(&mut pev, &mut socket, ms(1000)), Ok(None));
+        assert_eq!(pev.state(), PevState::WaitAttenChar);
+
+        // The EVSE that heard the sounds loudest (least attenuated) is chosen
+        let far = atten_char_ind(&[30; ATTEN_GROUPS]);
+        let near = atten_char_ind(&[20; ATTEN_GROUPS]);
+        assert_eq!(
+            pev.handle(&mut socket, ms(1000), Some((EVSE, &far))),
+            Ok(None)
+        );
+        assert_eq!(pev.state(), PevState::WaitAttenChar);
+        assert_eq!(
+            pev.handle(&mut socket, ms(1010), Some((EVSE2, &near))),
+            Ok(None)
+        );
+        assert_eq!(pev.state(), PevState::WaitMatch);
+        let responses = socket.take(MMType::CM_ATTEN_CHAR.rsp());
+        let responded: Vec<_> = responses.iter().map(|(addr, _)| *addr).collect();
+        assert_eq!(responded, [EVSE, EVSE2]);
+        let requests = socket.take(MMType::CM_SLAC_MATCH.req());
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].0, EVSE2);
+
+        // CM_SLAC_MATCH.REQ is repeated until the chosen EVSE confirms
+        assert_eq!(
+            advance(&mut pev, &mut socket, ms(1010) + TT_MATCH_RESPONSE),
+            Ok(None)
+        );
+        assert_eq!(socket.take(MMType::CM_SLAC_MATCH.req()).len(), 1);
+        let other = match_cnf(EVSE);
+        assert_eq!(
+            pev.handle(&mut socket, ms(1300), Some((EVSE, &other))),
+            Ok(None)
+        );
+        let cnf = match_cnf(EVSE2);
+        let matched = pev
+            .handle(&mut socket, ms(1310), Some((EVSE2, &cnf)))
+            .unwrap();
+        let expected = Matched {
+            pev: PEV,
+            evse: EVSE2,
+            run_id: RUN_ID,
+            nid: NID,
+            nmk: NMK,
+        };
+        assert_eq!(matched, Some(expected));
+        assert_eq!(pev.deadline(), None);
+    }
+
+    #[test]
+    fn round_trips() {
+        let data = atten_char_ind(&[7; ATTEN_GROUPS]);
+        let ind = AttenCharInd::try_from(&data[..]).unwrap();
+        assert_eq!(ind.mmtype(), MMType::CM_ATTEN_CHAR.ind());
+        assert_eq!((ind.source_address(), ind.run_id()), (PEV, RUN_ID));
+        assert_eq!(
+            (ind.num_sounds(), ind.num_groups()),
+            (C_EV_MATCH_MNBC, ATTEN_GROUPS)
+        );
+        assert_eq!(ind.average_attenuation(), 7);
+
+        let data = atten_char_rsp(0);
+        let rsp = AttenCharRsp::try_from(&data[..]).unwrap();
+        assert_eq!(rsp.mmtype(), MMType::CM_ATTEN_CHAR.rsp());
+        assert_eq!((rsp.source_address(), rsp.run_id()), (PEV, RUN_ID));
+        assert!(rsp.is_success());
+        let data = atten_char_rsp(1);
+        assert!(!AttenCharRsp::try_from(&data[..]).unwrap().is_success());
+
+        let data = frame(SlacMatchRequest {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            pev_id: [0x11; 17],
+            pev_mac: PEV,
+            evse_id: [0x22; 17],
+            evse_mac: EVSE,
+            run_id: RUN_ID,
+        });
+        let req = SlacMatchReq::try_from(&data[..]).unwrap();
+        assert_eq!(req.mmtype(), MMType::CM_SLAC_MATCH.req());
+        assert_eq!((req.pev_id(), req.pev_mac()), ([0x11; 17], PEV));
+        assert_eq!((req.evse_id(), req.evse_mac()), ([0x22; 17], EVSE));
+        assert_eq!(req.run_id(), RUN_ID);
+
+        let data = match_cnf(EVSE);
+        let cnf = SlacMatchCnf::try_from(&data[..]).unwrap();
+        assert_eq!(cnf.mmtype(), MMType::CM_SLAC_MATCH.cnf());
+        assert_eq!((cnf.pev_mac(), cnf.evse_mac()), (PEV, EVSE));
+        assert_eq!((cnf.run_id(), cnf.nid(), cnf.nmk()), (RUN_ID, NID, NMK));
+
+        let data = frame(ValidateRequest {
+            signal_type: 0,
+            timer: 3,
+            result: ValidateResult::READY,
+        });
+        let req = ValidateReq::try_from(&data[..]).unwrap();
+        assert_eq!(req.mmtype(), MMType::CM_VALIDATE.req());
+        assert_eq!((req.signal_type(), req.timer()), (0, 3));
+        assert_eq!(req.result(), ValidateResult::READY);
+
+        let data = frame(ValidateConfirm {
+            signal_type: 0,
+            toggle_num: 2,
+            result: ValidateResult::SUCCESS,
+        });
+        let cnf = ValidateCnf::try_from(&data[..]).unwrap();
+        assert_eq!(cnf.mmtype(), MMType::CM_VALIDATE.cnf());
+        assert_eq!((cnf.signal_type(), cnf.toggle_num()), (0, 2));
+        assert_eq!(cnf.result(), ValidateResult::SUCCESS);
+
+        // Truncated messages are rejected rather than read out of bounds
+        assert!(SlacMatchCnf::try_from(&data[..]).is_err());
+    }
+}
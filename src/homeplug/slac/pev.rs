@@ -0,0 +1,341 @@
+use super::*;
+use log::info;
+
+/// Most EVSEs a PEV keeps track of during a match (e.g. in a car park with shared wiring)
+pub const MAX_EVSES: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PevState {
+    /// Not started
+    Idle,
+    /// Collecting `CM_SLAC_PARM.CNF` from EVSEs
+    WaitParam,
+    /// Sending `CM_START_ATTEN_CHAR.IND`
+    StartAttenChar,
+    /// Sending `CM_MNBC_SOUND.IND`
+    Sounding,
+    /// Collecting `CM_ATTEN_CHAR.IND` from EVSEs
+    WaitAttenChar,
+    /// Waiting for the chosen EVSE's `CM_SLAC_MATCH.CNF`
+    WaitMatch,
+    Matched,
+}
+
+#[derive(Debug, Clone)]
+pub struct PevConfig {
+    /// Address of the PEV (the host running SLAC, not its station)
+    pub address: EtherAddr,
+    pub pev_id: [u8; 17],
+    /// Random identifier for this run
+    pub run_id: [u8; 8],
+    /// Source of the 16 random bytes in each `CM_MNBC_SOUND.IND` (e.g. from `getrandom`)
+    pub random: fn(&mut [u8; 16]),
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Candidate {
+    addr: EtherAddr,
+    /// Average attenuation from `CM_ATTEN_CHAR.IND`, once received
+    attenuation: Option<u8>,
+}
+
+/// PEV (vehicle) side of SLAC
+///
+/// Once matched, the PEV's station should be given the network key with
+/// [`SetKeyRequest::nmk`].
+///
+/// Call [`Pev::handle`] with every frame received, and whenever [`Pev::deadline`] passes.
+/// `now` can be measured from any fixed point, as long as it's the same for every call.
+/// After an error the match has failed, and a new `Pev` should be started.
+#[derive(Debug, Clone)]
+pub struct Pev {
+    config: PevConfig,
+    state: PevState,
+    deadline: Option<Duration>,
+    evses: [Option<Candidate>; MAX_EVSES],
+    num_sounds: u8,
+    time_out: u8,
+    /// Messages sent in the current state, including retries
+    sent: usize,
+    results_deadline: Duration,
+    chosen: EtherAddr,
+}
+impl Pev {
+    pub fn new(config: PevConfig) -> Pev {
+        Pev {
+            config,
+            state: PevState::Idle,
+            deadline: Some(Duration::ZERO),
+            evses: [None; MAX_EVSES],
+            num_sounds: C_EV_MATCH_MNBC,
+            time_out: 0,
+            sent: 0,
+            results_deadline: Duration::ZERO,
+            chosen: EtherAddr::NULL,
+        }
+    }
+    pub fn state(&self) -> PevState {
+        self.state
+    }
+    /// When [`Pev::handle`] next needs calling, even if nothing is received
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    /// Process a received frame (if any) and expired timers, returning the match once made
+    pub fn handle<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+        frame: Option<(EtherAddr, &[u8])>,
+    ) -> Result<Option<Matched>, SlacError<T::Error>> {
+        let mut matched = None;
+        if let Some((addr, data)) = frame {
+            matched = self.receive(socket, now, addr, data)?;
+        }
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            self.expire(socket, now)?;
+        }
+        Ok(matched)
+    }
+
+    /// Run until matched with an EVSE, using the system clock
+    #[cfg(feature = "std")]
+    pub fn run<T: EtherSocket>(&mut self, socket: &mut T) -> Result<Matched, SlacError<T::Error>> {
+        run(self, socket)
+    }
+
+    fn receive<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+        addr: EtherAddr,
+        data: &[u8],
+    ) -> Result<Option<Matched>, SlacError<T::Error>> {
+        let mmtype = match UnknownMessage::try_from(data) {
+            Ok(msg) => msg.mmtype(),
+            Err(_) => return Ok(None),
+        };
+        let run_id = self.config.run_id;
+        if mmtype == MMType::CM_SLAC_PARM.cnf() {
+            let cnf = parse::<SlacParamCnf>(addr, data);
+            if let Some(cnf) = cnf.filter(|cnf| cnf.run_id() == run_id) {
+                if self.state == PevState::WaitParam && self.candidate(addr).is_none() {
+                    info!("[{addr:?}] {cnf:?}");
+                    match self.evses.iter_mut().find(|evse| evse.is_none()) {
+                        Some(slot) => {
+                            *slot = Some(Candidate {
+                                addr,
+                                attenuation: None,
+                            })
+                        }
+                        None => warn!("[{addr:?}] Too many EVSEs, ignored"),
+                    }
+                    if self.time_out == 0 {
+                        self.num_sounds = cnf.num_sounds();
+                        self.time_out = cnf.time_out();
+                    }
+                }
+            }
+        } else if mmtype == MMType::CM_ATTEN_CHAR.ind() {
+            let waiting = matches!(
+                self.state,
+                PevState::StartAttenChar | PevState::Sounding | PevState::WaitAttenChar
+            );
+            let ind = parse::<AttenCharInd>(addr, data).filter(|_| waiting);
+            if let Some(ind) = ind.filter(|ind| ind.run_id() == run_id) {
+                if let Some(candidate) = self.candidate(addr) {
+                    info!("[{addr:?}] {ind:?}");
+                    candidate.attenuation = Some(ind.average_attenuation());
+                    let rsp = AttenCharResponse {
+                        application_type: ApplicationType::PEV_EVSE,
+                        security_type: SecurityType::NONE,
+                        source_address: self.config.address,
+                        run_id,
+                        source_id: ind.source_id(),
+                        resp_id: ind.resp_id(),
+                        result: 0,
+                    };
+                    send(socket, addr, rsp)?;
+                    // No need to wait once every EVSE has answered
+                    let all = self.evses.iter().flatten().all(|e| e.attenuation.is_some());
+                    if all && self.state == PevState::WaitAttenChar {
+                        self.deadline = Some(now);
+                    }
+                }
+            }
+        } else if mmtype == MMType::CM_SLAC_MATCH.cnf() {
+            let cnf = parse::<SlacMatchCnf>(addr, data);
+            let cnf = cnf.filter(|cnf| addr == self.chosen && cnf.run_id() == run_id);
+            if let Some(cnf) = cnf.filter(|_| self.state == PevState::WaitMatch) {
+                info!("[{addr:?}] {cnf:?}");
+                self.state = PevState::Matched;
+                self.deadline = None;
+                return Ok(Some(Matched {
+                    pev: self.config.address,
+                    evse: addr,
+                    run_id,
+                    nid: cnf.nid(),
+                    nmk: cnf.nmk(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn expire<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+    ) -> Result<(), SlacError<T::Error>> {
+        match self.state {
+            PevState::Idle => {
+                self.send_param(socket)?;
+                self.state = PevState::WaitParam;
+                self.sent = 1;
+                self.deadline = Some(now + TT_MATCH_RESPONSE);
+            }
+            PevState::WaitParam if self.evses[0].is_none() => {
+                if self.sent > C_EV_MATCH_RETRY {
+                    return Err(SlacError::Timeout("TT_match_response"));
+                }
+                self.send_param(socket)?;
+                self.sent += 1;
+                self.deadline = Some(now + TT_MATCH_RESPONSE);
+            }
+            PevState::WaitParam => {
+                self.results_deadline = now + TT_EV_ATTEN_RESULTS;
+                self.send_start_atten_char(socket)?;
+                self.state = PevState::StartAttenChar;
+                self.sent = 1;
+                self.deadline = Some(now + TP_EV_BATCH_MSG_INTERVAL);
+            }
+            PevState::StartAttenChar if self.sent < C_EV_START_ATTEN_CHAR_INDS => {
+                self.send_start_atten_char(socket)?;
+                self.sent += 1;
+                self.deadline = Some(now + TP_EV_BATCH_MSG_INTERVAL);
+            }
+            PevState::StartAttenChar => {
+                self.state = PevState::Sounding;
+                self.sent = 0;
+                self.send_sound(socket)?;
+                self.deadline = Some(now + TP_EV_BATCH_MSG_INTERVAL);
+            }
+            PevState::Sounding if self.sent < self.num_sounds as usize => {
+                self.send_sound(socket)?;
+                self.deadline = Some(now + TP_EV_BATCH_MSG_INTERVAL);
+            }
+            PevState::Sounding => {
+                self.state = PevState::WaitAttenChar;
+                self.deadline = Some(self.results_deadline.max(now));
+            }
+            PevState::WaitAttenChar => {
+                // The EVSE that heard the sounds loudest is the one the PEV is plugged in to
+                let chosen = self
+                    .evses
+                    .iter()
+                    .flatten()
+                    .filter_map(|evse| evse.attenuation.map(|atten| (atten, evse.addr)))
+                    .min_by_key(|&(atten, _)| atten);
+                let (attenuation, chosen) = match chosen {
+                    Some(chosen) => chosen,
+                    None => return Err(SlacError::Timeout("TT_EV_atten_results")),
+                };
+                info!("[{chosen:?}] Chosen EVSE ({attenuation}dB)");
+                self.chosen = chosen;
+                self.send_match(socket)?;
+                self.state = PevState::WaitMatch;
+                self.sent = 1;
+                self.deadline = Some(now + TT_MATCH_RESPONSE);
+            }
+            PevState::WaitMatch => {
+                if self.sent > C_EV_MATCH_RETRY {
+                    return Err(SlacError::Timeout("TT_match_response"));
+                }
+                self.send_match(socket)?;
+                self.sent += 1;
+                self.deadline = Some(now + TT_MATCH_RESPONSE);
+            }
+            PevState::Matched => self.deadline = None,
+        }
+        Ok(())
+    }
+
+    fn candidate(&mut self, addr: EtherAddr) -> Option<&mut Candidate> {
+        self.evses
+            .iter_mut()
+            .flatten()
+            .find(|evse| evse.addr == addr)
+    }
+
+    fn send_param<T: EtherSocket>(&self, socket: &mut T) -> Result<(), SlacError<T::Error>> {
+        let req = SlacParamRequest {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            run_id: self.config.run_id,
+        };
+        send(socket, EtherAddr::BROADCAST, req)?;
+        Ok(())
+    }
+
+    fn send_start_atten_char<T: EtherSocket>(
+        &self,
+        socket: &mut T,
+    ) -> Result<(), SlacError<T::Error>> {
+        let ind = StartAttenCharIndication {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            num_sounds: self.num_sounds,
+            time_out: self.time_out,
+            resp_type: 0x01,
+            forwarding_sta: self.config.address,
+            run_id: self.config.run_id,
+        };
+        send(socket, EtherAddr::BROADCAST, ind)?;
+        Ok(())
+    }
+
+    fn send_sound<T: EtherSocket>(&mut self, socket: &mut T) -> Result<(), SlacError<T::Error>> {
+        self.sent += 1;
+        let mut random = [0; 16];
+        (self.config.random)(&mut random);
+        let ind = MnbcSoundIndication {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            sender_id: [0; 17],
+            count: (self.num_sounds as usize).saturating_sub(self.sent) as u8,
+            run_id: self.config.run_id,
+            random,
+        };
+        send(socket, EtherAddr::BROADCAST, ind)?;
+        Ok(())
+    }
+
+    fn send_match<T: EtherSocket>(&self, socket: &mut T) -> Result<(), SlacError<T::Error>> {
+        let req = SlacMatchRequest {
+            application_type: ApplicationType::PEV_EVSE,
+            security_type: SecurityType::NONE,
+            pev_id: self.config.pev_id,
+            pev_mac: self.config.address,
+            evse_id: [0; 17],
+            evse_mac: self.chosen,
+            run_id: self.config.run_id,
+        };
+        send(socket, self.chosen, req)?;
+        Ok(())
+    }
+}
+#[cfg(feature = "std")]
+impl Machine for Pev {
+    fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+    fn handle<T: EtherSocket>(
+        &mut self,
+        socket: &mut T,
+        now: Duration,
+        frame: Option<(EtherAddr, &[u8])>,
+    ) -> Result<Option<Matched>, SlacError<T::Error>> {
+        Pev::handle(self, socket, now, frame)
+    }
+}
@@ -0,0 +1,173 @@
+use super::*;
+use core::convert::TryInto;
+
+/// Length of the variable fields of `CM_SLAC_MATCH.REQ` (MVFLength)
+const REQ_VARIABLE_SIZE: u16 = 0x3e;
+/// Length of the variable fields of `CM_SLAC_MATCH.CNF` (MVFLength)
+const CNF_VARIABLE_SIZE: u16 = 0x56;
+
+/// CM_SLAC_MATCH.REQ - Sent by the PEV to the EVSE it chose
+pub struct SlacMatchRequest {
+    pub application_type: ApplicationType,
+    pub security_type: SecurityType,
+    pub pev_id: [u8; 17],
+    pub pev_mac: EtherAddr,
+    pub evse_id: [u8; 17],
+    pub evse_mac: EtherAddr,
+    pub run_id: [u8; 8],
+}
+impl<'a> MessageTX<'a> for SlacMatchRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_SLAC_MATCH;
+    type Response = SlacMatchCnf<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.application_type.0)?;
+        frame.push_u8(self.security_type.0)?;
+        frame.push_u16_le(REQ_VARIABLE_SIZE)?;
+        frame.push(&self.pev_id)?;
+        frame.push(&self.pev_mac.0)?;
+        frame.push(&self.evse_id)?;
+        frame.push(&self.evse_mac.0)?;
+        frame.push(&self.run_id)?;
+        frame.zeros(8)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct SlacMatchReq<'a>(pub(crate) &'a [u8]);
+impl SlacMatchReq<'_> {
+    pub fn application_type(&self) -> ApplicationType {
+        ApplicationType(self.payload()[0])
+    }
+    pub fn security_type(&self) -> SecurityType {
+        SecurityType(self.payload()[1])
+    }
+    pub fn pev_id(&self) -> [u8; 17] {
+        self.payload()[4..21].try_into().unwrap()
+    }
+    pub fn pev_mac(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[21..27])
+    }
+    pub fn evse_id(&self) -> [u8; 17] {
+        self.payload()[27..44].try_into().unwrap()
+    }
+    pub fn evse_mac(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[44..50])
+    }
+    pub fn run_id(&self) -> [u8; 8] {
+        self.payload()[50..58].try_into().unwrap()
+    }
+}
+impl MessageReader for SlacMatchReq<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for SlacMatchReq<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "SlacMatchReq(PEV:{:?} EVSE:{:?} RunID:{:02x?})",
+            self.pev_mac(),
+            self.evse_mac(),
+            self.run_id()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for SlacMatchReq<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 66)?;
+        Ok(Self(data))
+    }
+}
+
+/// CM_SLAC_MATCH.CNF - Sent by the EVSE with the key of its logical network
+pub struct SlacMatchConfirm {
+    pub application_type: ApplicationType,
+    pub security_type: SecurityType,
+    pub pev_id: [u8; 17],
+    pub pev_mac: EtherAddr,
+    pub evse_id: [u8; 17],
+    pub evse_mac: EtherAddr,
+    pub run_id: [u8; 8],
+    pub nid: [u8; 7],
+    pub nmk: [u8; 16],
+}
+impl<'a> MessageTX<'a> for SlacMatchConfirm {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_SLAC_MATCH.cnf();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.application_type.0)?;
+        frame.push_u8(self.security_type.0)?;
+        frame.push_u16_le(CNF_VARIABLE_SIZE)?;
+        frame.push(&self.pev_id)?;
+        frame.push(&self.pev_mac.0)?;
+        frame.push(&self.evse_id)?;
+        frame.push(&self.evse_mac.0)?;
+        frame.push(&self.run_id)?;
+        frame.zeros(8)?;
+        frame.push(&self.nid)?;
+        frame.zeros(1)?;
+        frame.push(&self.nmk)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct SlacMatchCnf<'a>(pub(crate) &'a [u8]);
+impl SlacMatchCnf<'_> {
+    pub fn application_type(&self) -> ApplicationType {
+        ApplicationType(self.payload()[0])
+    }
+    pub fn security_type(&self) -> SecurityType {
+        SecurityType(self.payload()[1])
+    }
+    pub fn pev_id(&self) -> [u8; 17] {
+        self.payload()[4..21].try_into().unwrap()
+    }
+    pub fn pev_mac(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[21..27])
+    }
+    pub fn evse_id(&self) -> [u8; 17] {
+        self.payload()[27..44].try_into().unwrap()
+    }
+    pub fn evse_mac(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[44..50])
+    }
+    pub fn run_id(&self) -> [u8; 8] {
+        self.payload()[50..58].try_into().unwrap()
+    }
+    pub fn nid(&self) -> [u8; 7] {
+        self.payload()[66..73].try_into().unwrap()
+    }
+    pub fn nmk(&self) -> [u8; 16] {
+        self.payload()[74..90].try_into().unwrap()
+    }
+}
+impl MessageReader for SlacMatchCnf<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for SlacMatchCnf<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "SlacMatchCnf(PEV:{:?} EVSE:{:?} NID:{:02x?} RunID:{:02x?})",
+            self.pev_mac(),
+            self.evse_mac(),
+            self.nid(),
+            self.run_id()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for SlacMatchCnf<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 90)?;
+        Ok(Self(data))
+    }
+}
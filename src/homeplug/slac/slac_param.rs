@@ -0,0 +1,143 @@
+use super::*;
+use core::convert::TryInto;
+
+/// CM_SLAC_PARM.REQ - Broadcast by the PEV to start matching
+pub struct SlacParamRequest {
+    pub application_type: ApplicationType,
+    pub security_type: SecurityType,
+    /// Identifies this matching run in every following message
+    pub run_id: [u8; 8],
+}
+impl<'a> MessageTX<'a> for SlacParamRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_SLAC_PARM;
+    type Response = SlacParamCnf<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.application_type.0)?;
+        frame.push_u8(self.security_type.0)?;
+        frame.push(&self.run_id)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct SlacParamReq<'a>(pub(crate) &'a [u8]);
+impl SlacParamReq<'_> {
+    pub fn application_type(&self) -> ApplicationType {
+        ApplicationType(self.payload()[0])
+    }
+    pub fn security_type(&self) -> SecurityType {
+        SecurityType(self.payload()[1])
+    }
+    pub fn run_id(&self) -> [u8; 8] {
+        self.payload()[2..10].try_into().unwrap()
+    }
+}
+impl MessageReader for SlacParamReq<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for SlacParamReq<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "SlacParamReq({:?} {:?} RunID:{:02x?})",
+            self.application_type(),
+            self.security_type(),
+            self.run_id()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for SlacParamReq<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 10)?;
+        Ok(Self(data))
+    }
+}
+
+/// CM_SLAC_PARM.CNF - Sent by each EVSE that heard the request, with sounding parameters
+pub struct SlacParamConfirm {
+    /// Where the sounds should be sent (broadcast)
+    pub msound_target: EtherAddr,
+    pub num_sounds: u8,
+    /// Time the EVSE collects sounds for, in 100ms units
+    pub time_out: u8,
+    /// 0x01 if the PEV should send sounds on to the EVSE (the only type in use)
+    pub resp_type: u8,
+    /// The PEV's address
+    pub forwarding_sta: EtherAddr,
+    pub application_type: ApplicationType,
+    pub security_type: SecurityType,
+    pub run_id: [u8; 8],
+}
+impl<'a> MessageTX<'a> for SlacParamConfirm {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_SLAC_PARM.cnf();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push(&self.msound_target.0)?;
+        frame.push_u8(self.num_sounds)?;
+        frame.push_u8(self.time_out)?;
+        frame.push_u8(self.resp_type)?;
+        frame.push(&self.forwarding_sta.0)?;
+        frame.push_u8(self.application_type.0)?;
+        frame.push_u8(self.security_type.0)?;
+        frame.push(&self.run_id)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct SlacParamCnf<'a>(pub(crate) &'a [u8]);
+impl SlacParamCnf<'_> {
+    pub fn msound_target(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[0..6])
+    }
+    pub fn num_sounds(&self) -> u8 {
+        self.payload()[6]
+    }
+    pub fn time_out(&self) -> u8 {
+        self.payload()[7]
+    }
+    pub fn resp_type(&self) -> u8 {
+        self.payload()[8]
+    }
+    pub fn forwarding_sta(&self) -> EtherAddr {
+        EtherAddr::from_slice(&self.payload()[9..15])
+    }
+    pub fn application_type(&self) -> ApplicationType {
+        ApplicationType(self.payload()[15])
+    }
+    pub fn security_type(&self) -> SecurityType {
+        SecurityType(self.payload()[16])
+    }
+    pub fn run_id(&self) -> [u8; 8] {
+        self.payload()[17..25].try_into().unwrap()
+    }
+}
+impl MessageReader for SlacParamCnf<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for SlacParamCnf<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "SlacParamCnf(sounds={} timeout={} PEV:{:?} RunID:{:02x?})",
+            self.num_sounds(),
+            self.time_out(),
+            self.forwarding_sta(),
+            self.run_id()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for SlacParamCnf<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 25)?;
+        Ok(Self(data))
+    }
+}
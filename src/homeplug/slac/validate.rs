@@ -0,0 +1,129 @@
+use super::*;
+
+#[repr(transparent)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
+pub struct ValidateResult(pub u8);
+impl ValidateResult {
+    pub const NOT_READY: Self = Self(0x00);
+    pub const READY: Self = Self(0x01);
+    pub const SUCCESS: Self = Self(0x02);
+    pub const FAILURE: Self = Self(0x03);
+    pub const NOT_REQUIRED: Self = Self(0x04);
+}
+impl core::fmt::Debug for ValidateResult {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::NOT_READY => write!(f, "NOT_READY"),
+            Self::READY => write!(f, "READY"),
+            Self::SUCCESS => write!(f, "SUCCESS"),
+            Self::FAILURE => write!(f, "FAILURE"),
+            Self::NOT_REQUIRED => write!(f, "NOT_REQUIRED"),
+            _ => write!(f, "ValidateResult{:02x}", self.0),
+        }
+    }
+}
+
+/// CM_VALIDATE.REQ - Sent by the PEV to confirm a match by toggling the control pilot
+pub struct ValidateRequest {
+    /// Zero for control pilot (S2) toggles
+    pub signal_type: u8,
+    /// Time the PEV will toggle for, in 100ms units (zero in the first request)
+    pub timer: u8,
+    pub result: ValidateResult,
+}
+impl<'a> MessageTX<'a> for ValidateRequest {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_VALIDATE;
+    type Response = ValidateCnf<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.signal_type)?;
+        frame.push_u8(self.timer)?;
+        frame.push_u8(self.result.0)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct ValidateReq<'a>(pub(crate) &'a [u8]);
+impl ValidateReq<'_> {
+    pub fn signal_type(&self) -> u8 {
+        self.payload()[0]
+    }
+    pub fn timer(&self) -> u8 {
+        self.payload()[1]
+    }
+    pub fn result(&self) -> ValidateResult {
+        ValidateResult(self.payload()[2])
+    }
+}
+impl MessageReader for ValidateReq<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for ValidateReq<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "ValidateReq(timer={} {:?})", self.timer(), self.result())
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for ValidateReq<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 3)?;
+        Ok(Self(data))
+    }
+}
+
+/// CM_VALIDATE.CNF - Sent by the EVSE with the number of toggles it saw
+pub struct ValidateConfirm {
+    pub signal_type: u8,
+    pub toggle_num: u8,
+    pub result: ValidateResult,
+}
+impl<'a> MessageTX<'a> for ValidateConfirm {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType::CM_VALIDATE.cnf();
+    type Response = UnknownMessage<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push_u8(self.signal_type)?;
+        frame.push_u8(self.toggle_num)?;
+        frame.push_u8(self.result.0)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct ValidateCnf<'a>(pub(crate) &'a [u8]);
+impl ValidateCnf<'_> {
+    pub fn signal_type(&self) -> u8 {
+        self.payload()[0]
+    }
+    pub fn toggle_num(&self) -> u8 {
+        self.payload()[1]
+    }
+    pub fn result(&self) -> ValidateResult {
+        ValidateResult(self.payload()[2])
+    }
+}
+impl MessageReader for ValidateCnf<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for ValidateCnf<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "ValidateCnf(toggles={} {:?})",
+            self.toggle_num(),
+            self.result()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for ValidateCnf<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 3)?;
+        Ok(Self(data))
+    }
+}
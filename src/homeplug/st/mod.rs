@@ -0,0 +1,51 @@
+//! ST/IoTecha vendor specific management messages (GreenPHY modems)
+//!
+//! ST don't publish their vendor message types or layouts, so this module has no typed
+//! requests for them yet. [`VendorRequest`] sends any vendor message under the ST OUI, for
+//! use with the chip documentation, until they can be added here with their own types.
+//!
+//! The modems implement the standard HomePlug AV requests (`CM_STA_CAP`, `CM_HFID`,
+//! `CM_SET_KEY`, ...), which is what [`crate::manage`] uses to manage them.
+
+use super::*;
+use crate::*;
+
+/// Vendor specific request with type `MMTYPE` (a `.req`), and a raw payload
+pub struct VendorRequest<'p, const MMTYPE: u16> {
+    pub payload: &'p [u8],
+}
+impl<'a, const MMTYPE: u16> MessageTX<'a> for VendorRequest<'_, MMTYPE> {
+    const MMV: MMV = MMV::HOMEPLUG_AV_1_1;
+    const MMTYPE: MMType = MMType(MMTYPE);
+    const OUI: OUI = OUI::ST;
+    type Response = VendorResponse<'a>;
+
+    fn set_payload(&self, frame: &mut FrameWriter) -> Result<(), EncodeError> {
+        frame.push(self.payload)
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+pub struct VendorResponse<'a>(pub(crate) &'a [u8]);
+impl MessageReader for VendorResponse<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.0
+    }
+}
+impl core::fmt::Debug for VendorResponse<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "VendorResponse({:?} {:02x?})",
+            self.mmtype(),
+            self.payload()
+        )
+    }
+}
+impl<'a> TryFrom<&'a [u8]> for VendorResponse<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        parse_payload(data)?;
+        Ok(Self(data))
+    }
+}
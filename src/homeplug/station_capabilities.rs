@@ -3,7 +3,8 @@ use crate::*;
 use core::convert::TryInto;
 
 #[repr(transparent)]
-#[derive(Default, PartialEq, Eq, Copy, Clone)]
+#[derive(Default, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StationVersion(pub u8);
 impl StationVersion {
     pub const HOMEPLUG_AV_1_1: Self = Self(0x00);
@@ -26,7 +27,7 @@ impl<'a> MessageTX<'a> for StationCapabilitiesRequest {
     type Response = StationCapabilities<'a>;
 }
 
-pub struct StationCapabilities<'a>(pub &'a [u8]);
+pub struct StationCapabilities<'a>(pub(crate) &'a [u8]);
 impl StationCapabilities<'_> {
     pub fn version(&self) -> StationVersion {
         StationVersion(self.payload()[0])
@@ -37,6 +38,46 @@ impl StationCapabilities<'_> {
     pub fn oui(&self) -> OUI {
         OUI(self.payload()[7..=9].try_into().unwrap())
     }
+    pub fn auto_connect(&self) -> bool {
+        self.payload()[10] != 0
+    }
+    pub fn smoothing(&self) -> bool {
+        self.payload()[11] != 0
+    }
+    pub fn cco_level(&self) -> u8 {
+        self.payload()[12]
+    }
+    pub fn proxy(&self) -> bool {
+        self.payload()[13] != 0
+    }
+    pub fn backup_cco(&self) -> bool {
+        self.payload()[15] != 0
+    }
+    pub fn soft_handover(&self) -> bool {
+        self.payload()[16] != 0
+    }
+    pub fn two_symbol_fc(&self) -> bool {
+        self.payload()[17] != 0
+    }
+    /// Maximum frame length, in units of 1.28µs
+    pub fn max_frame_length(&self) -> u16 {
+        u16::from_le_bytes([self.payload()[18], self.payload()[19]])
+    }
+    pub fn homeplug_1_1(&self) -> bool {
+        self.payload()[20] != 0
+    }
+    pub fn homeplug_1_0_1(&self) -> bool {
+        self.payload()[21] != 0
+    }
+    pub fn regulatory_domain(&self) -> u8 {
+        self.payload()[22]
+    }
+    pub fn bidirectional_bursting(&self) -> u8 {
+        self.payload()[23]
+    }
+    pub fn implementation_version(&self) -> [u8; 2] {
+        [self.payload()[24], self.payload()[25]]
+    }
 }
 impl MessageReader for StationCapabilities<'_> {
     fn bytes(&self) -> &[u8] {
@@ -98,8 +139,63 @@ impl core::fmt::Debug for StationCapabilities<'_> {
         Ok(())
     }
 }
-impl<'a> From<&'a [u8]> for StationCapabilities<'a> {
-    fn from(data: &'a [u8]) -> Self {
-        Self(data)
+impl<'a> TryFrom<&'a [u8]> for StationCapabilities<'a> {
+    type Error = ParseError;
+    fn try_from(data: &'a [u8]) -> Result<Self, ParseError> {
+        expect_payload(parse_payload(data)?, 26)?;
+        Ok(Self(data))
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use owned::*;
+
+#[cfg(feature = "alloc")]
+mod owned {
+    use super::*;
+
+    /// Decoded copy of [`StationCapabilities`]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct OwnedStationCapabilities {
+        pub version: StationVersion,
+        pub addr: EtherAddr,
+        pub oui: OUI,
+        pub auto_connect: bool,
+        pub smoothing: bool,
+        pub cco_level: u8,
+        pub proxy: bool,
+        pub backup_cco: bool,
+        pub soft_handover: bool,
+        pub two_symbol_fc: bool,
+        pub max_frame_length: u16,
+        pub homeplug_1_1: bool,
+        pub homeplug_1_0_1: bool,
+        pub regulatory_domain: u8,
+        pub bidirectional_bursting: u8,
+        pub implementation_version: [u8; 2],
+    }
+
+    impl StationCapabilities<'_> {
+        pub fn to_owned(&self) -> OwnedStationCapabilities {
+            OwnedStationCapabilities {
+                version: self.version(),
+                addr: self.addr(),
+                oui: self.oui(),
+                auto_connect: self.auto_connect(),
+                smoothing: self.smoothing(),
+                cco_level: self.cco_level(),
+                proxy: self.proxy(),
+                backup_cco: self.backup_cco(),
+                soft_handover: self.soft_handover(),
+                two_symbol_fc: self.two_symbol_fc(),
+                max_frame_length: self.max_frame_length(),
+                homeplug_1_1: self.homeplug_1_1(),
+                homeplug_1_0_1: self.homeplug_1_0_1(),
+                regulatory_domain: self.regulatory_domain(),
+                bidirectional_bursting: self.bidirectional_bursting(),
+                implementation_version: self.implementation_version(),
+            }
+        }
     }
 }
@@ -16,9 +16,20 @@ pub mod linux;
 
 pub mod homeplug;
 
+#[cfg(feature = "std")]
+pub mod manage;
+
+#[cfg(feature = "std")]
+pub mod memory;
+
+#[cfg(feature = "std")]
+pub mod pcap;
+
 mod ether_addr;
 mod ether_type;
 mod oui;
+#[cfg(feature = "serde")]
+mod serde_hex;
 
 use core::time::Duration;
 
@@ -46,6 +57,22 @@ pub trait EtherInterface: core::fmt::Debug + core::fmt::Display + Send + Sync +
     fn is_loopback(&self) -> bool;
 }
 
+/// Asynchronous equivalent of [`EtherSocket`] for use with tokio
+#[cfg(feature = "tokio")]
+pub trait AsyncEtherSocket: core::fmt::Debug + Send + Sync + 'static {
+    type Error: core::fmt::Debug + core::fmt::Display + Send + Sync + 'static;
+    fn sendto(
+        &mut self,
+        destination: EtherAddr,
+        data: &[u8],
+    ) -> impl core::future::Future<Output = Result<(), Self::Error>> + Send;
+    fn recvfrom<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        timeout: Option<Duration>,
+    ) -> impl core::future::Future<Output = Result<Option<(EtherAddr, &'a [u8])>, Self::Error>> + Send;
+}
+
 /// Return an iterator of accessible platform ethernet interfaces
 pub fn platform_interfaces(
 ) -> Result<impl Iterator<Item = impl EtherInterface>, impl core::fmt::Debug> {
@@ -9,7 +9,7 @@ use libc::{AF_PACKET, ARPHRD_ETHER, SOCK_DGRAM, SOL_SOCKET, SO_RCVTIMEO};
 use std::ffi::CStr;
 use std::format;
 use std::io::{Error, ErrorKind, Result};
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
 
 #[derive(Debug)]
 pub struct LinuxRawSocket {
@@ -57,9 +57,8 @@ impl LinuxRawSocket {
         Ok(())
     }
 }
-impl EtherSocket for LinuxRawSocket {
-    type Error = Error;
-    fn sendto(&mut self, destination: EtherAddr, data: &[u8]) -> Result<()> {
+impl LinuxRawSocket {
+    fn send(&self, destination: EtherAddr, data: &[u8]) -> Result<()> {
         unsafe {
             let sa = sockaddr_ll {
                 sll_family: AF_PACKET as u16,
@@ -80,31 +79,10 @@ impl EtherSocket for LinuxRawSocket {
             Ok(())
         }
     }
-    fn recvfrom<'a>(
-        &mut self,
-        buffer: &'a mut [u8],
-        timeout: Option<Duration>,
-    ) -> Result<Option<(EtherAddr, &'a [u8])>> {
+    /// Receive a single frame, returning its source and size
+    #[allow(clippy::io_other_error)]
+    fn receive(&self, buffer: &mut [u8]) -> Result<(EtherAddr, usize)> {
         unsafe {
-            let tv = if let Some(timeout) = timeout {
-                timeval {
-                    tv_sec: timeout.as_secs() as time_t,
-                    tv_usec: timeout.subsec_micros() as suseconds_t,
-                }
-            } else {
-                timeval {
-                    tv_sec: 0,
-                    tv_usec: 0,
-                }
-            };
-            setsockopt(
-                self.fd,
-                SOL_SOCKET,
-                SO_RCVTIMEO,
-                &tv as *const _ as *const c_void,
-                size_of::<timeval>() as u32,
-            );
-
             let mut sa = sockaddr_ll {
                 sll_family: AF_PACKET as u16,
                 sll_protocol: self.ethertype.as_be_u16(),
@@ -120,20 +98,129 @@ impl EtherSocket for LinuxRawSocket {
             let mut addrlen = size_of::<sockaddr_ll>() as u32;
             let size = recvfrom(self.fd, buf, len, 0, addr, &mut addrlen);
             if size == -1 {
-                let e = Error::last_os_error();
-                if e.kind() == ErrorKind::WouldBlock {
-                    return Ok(None);
-                } else {
-                    return Err(e);
-                }
+                return Err(Error::last_os_error());
             }
             let addr = EtherAddr(sa.sll_addr[..6].try_into().unwrap());
             if size as usize > buffer.len() {
                 let msg = format!("Packet from {:?} too big ({}>{})", addr, size, buffer.len());
                 return Err(Error::new(ErrorKind::Other, msg));
             }
-            let data = &buffer[..size as usize];
-            Ok(Some((addr, data)))
+            Ok((addr, size as usize))
+        }
+    }
+}
+impl EtherSocket for LinuxRawSocket {
+    type Error = Error;
+    fn sendto(&mut self, destination: EtherAddr, data: &[u8]) -> Result<()> {
+        self.send(destination, data)
+    }
+    fn recvfrom<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<Option<(EtherAddr, &'a [u8])>> {
+        let tv = if let Some(timeout) = timeout {
+            timeval {
+                tv_sec: timeout.as_secs() as time_t,
+                tv_usec: timeout.subsec_micros() as suseconds_t,
+            }
+        } else {
+            timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            }
+        };
+        unsafe {
+            setsockopt(
+                self.fd,
+                SOL_SOCKET,
+                SO_RCVTIMEO,
+                &tv as *const _ as *const c_void,
+                size_of::<timeval>() as u32,
+            );
+        }
+        match self.receive(buffer) {
+            Ok((addr, size)) => Ok(Some((addr, &buffer[..size]))),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+impl AsRawFd for LinuxRawSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use self::tokio_socket::*;
+
+#[cfg(feature = "tokio")]
+mod tokio_socket {
+    use super::*;
+    use libc::{fcntl, F_GETFL, F_SETFL, O_NONBLOCK};
+    use tokio::io::unix::AsyncFd;
+
+    impl LinuxRawSocket {
+        /// Switch to non-blocking mode and register with the current tokio runtime
+        pub fn into_async(self) -> Result<AsyncLinuxRawSocket> {
+            unsafe {
+                let flags = fcntl(self.fd, F_GETFL);
+                if flags == -1 || fcntl(self.fd, F_SETFL, flags | O_NONBLOCK) == -1 {
+                    return Err(Error::last_os_error());
+                }
+            }
+            // The socket owns its fd and only closes it when dropped
+            let inner = unsafe { AsyncFd::register(self) };
+            Ok(AsyncLinuxRawSocket {
+                inner: inner.map_err(|err| err.into_parts().1)?,
+            })
+        }
+    }
+
+    /// [`LinuxRawSocket`] driven by the tokio reactor
+    #[derive(Debug)]
+    pub struct AsyncLinuxRawSocket {
+        inner: AsyncFd<LinuxRawSocket>,
+    }
+    impl AsyncLinuxRawSocket {
+        pub fn get_ref(&self) -> &LinuxRawSocket {
+            self.inner.get_ref()
+        }
+    }
+    impl AsyncEtherSocket for AsyncLinuxRawSocket {
+        type Error = Error;
+        async fn sendto(&mut self, destination: EtherAddr, data: &[u8]) -> Result<()> {
+            loop {
+                let mut guard = self.inner.writable().await?;
+                if let Ok(result) = guard.try_io(|inner| inner.get_ref().send(destination, data)) {
+                    return result;
+                }
+            }
+        }
+        async fn recvfrom<'a>(
+            &mut self,
+            buffer: &'a mut [u8],
+            timeout: Option<Duration>,
+        ) -> Result<Option<(EtherAddr, &'a [u8])>> {
+            let inner = &self.inner;
+            let receive = async {
+                loop {
+                    let mut guard = inner.readable().await?;
+                    if let Ok(result) = guard.try_io(|inner| inner.get_ref().receive(buffer)) {
+                        return result;
+                    }
+                }
+            };
+            let received = match timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, receive).await {
+                    Ok(received) => received,
+                    Err(_) => return Ok(None),
+                },
+                None => receive.await,
+            };
+            let (addr, size) = received?;
+            Ok(Some((addr, &buffer[..size])))
         }
     }
 }
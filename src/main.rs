@@ -0,0 +1,659 @@
+use clap::{App, Arg};
+use log::{debug, info, warn};
+use powerline::{homeplug::*, *};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Output formats for scan, find and dump
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+    Table,
+}
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "table" => Ok(Format::Table),
+            _ => Err(format!("Unknown format ('{s}')")),
+        }
+    }
+}
+
+/// Quote a CSV field if it contains separators, quotes or newlines
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn print_csv_row(fields: &[String]) {
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    println!("{}", fields.join(","));
+}
+
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+    let print_row = |fields: Vec<&str>| {
+        let line: Vec<String> = fields
+            .iter()
+            .zip(&widths)
+            .map(|(field, &width)| format!("{field:width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+    print_row(headers.to_vec());
+    let rules: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    print_row(rules.iter().map(String::as_str).collect());
+    for row in rows {
+        print_row(row.iter().map(String::as_str).collect());
+    }
+}
+
+fn oui_string(oui: OUI) -> String {
+    format!("{:02x}:{:02x}:{:02x}", oui[0], oui[1], oui[2])
+}
+
+const DEVICE_HEADERS: &[&str] = &[
+    "interface",
+    "address",
+    "version",
+    "oui",
+    "vendor",
+    "bridged",
+    "rates",
+    "hfid",
+    "manufacturer",
+    "firmware",
+];
+
+/// Average PHY rates to each peer, e.g. `120/117Mbps(00:1f:84:44:55:66)`
+fn rates_string(device: &manage::DeviceInfo) -> String {
+    let rates: Vec<String> = device
+        .rates
+        .iter()
+        .map(|rate| format!("{}/{}Mbps({:?})", rate.tx, rate.rx, rate.peer))
+        .collect();
+    rates.join(" ")
+}
+
+fn device_fields(device: &manage::DeviceInfo) -> Vec<String> {
+    let bridged: Vec<String> = device
+        .bridged
+        .iter()
+        .map(|addr| format!("{addr:?}"))
+        .collect();
+    vec![
+        device.interface.clone(),
+        format!("{:?}", device.address),
+        device.version.map(|v| format!("{v:?}")).unwrap_or_default(),
+        oui_string(device.oui),
+        device.oui.name().unwrap_or_default().to_string(),
+        bridged.join(" "),
+        rates_string(device),
+        device.hfid.clone().unwrap_or_default(),
+        device.manufacturer.clone().unwrap_or_default(),
+        device.firmware.clone().unwrap_or_default(),
+    ]
+}
+
+fn device_json(device: &manage::DeviceInfo) -> serde_json::Value {
+    let bridged: Vec<String> = device
+        .bridged
+        .iter()
+        .map(|addr| format!("{addr:?}"))
+        .collect();
+    let rates: Vec<_> = device
+        .rates
+        .iter()
+        .map(|rate| {
+            serde_json::json!({
+                "peer": format!("{:?}", rate.peer),
+                "tx": rate.tx,
+                "rx": rate.rx,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "interface": device.interface,
+        "address": format!("{:?}", device.address),
+        "version": device.version.map(|v| format!("{v:?}")),
+        "oui": oui_string(device.oui),
+        "vendor": device.oui.name(),
+        "bridged": bridged,
+        "rates": rates,
+        "hfid": device.hfid,
+        "manufacturer": device.manufacturer,
+        "firmware": device.firmware,
+    })
+}
+
+fn print_devices(format: Format, devices: &[manage::DeviceInfo]) {
+    match format {
+        Format::Text => {
+            for device in devices {
+                let interface = &device.interface;
+                let address = device.address;
+                let version = device.version.unwrap_or_default();
+                let oui = device.oui;
+                let bridged = device.bridged.len();
+                let mut rates = rates_string(device);
+                if !rates.is_empty() {
+                    rates.insert(0, ' ');
+                }
+                let name = device.hfid.as_deref().unwrap_or_default();
+                println!(
+                    "{interface}: [{address:?}] {version:?} {oui:?} {bridged}Ethers{rates} '{name}'"
+                );
+            }
+        }
+        Format::Json => {
+            let devices: Vec<_> = devices.iter().map(device_json).collect();
+            println!("{}", serde_json::to_string_pretty(&devices).unwrap());
+        }
+        Format::Csv => {
+            print_csv_row(
+                &DEVICE_HEADERS
+                    .iter()
+                    .map(|h| h.to_string())
+                    .collect::<Vec<_>>(),
+            );
+            for device in devices {
+                print_csv_row(&device_fields(device));
+            }
+        }
+        Format::Table => {
+            let rows: Vec<_> = devices.iter().map(device_fields).collect();
+            print_table(DEVICE_HEADERS, &rows);
+        }
+    }
+}
+
+/// Interfaces selected by `--interface`, warning about any that don't exist
+fn select<T: EtherInterface>(
+    interfaces: impl Iterator<Item = T>,
+    filter: Option<&HashSet<String>>,
+) -> Vec<T> {
+    let interfaces = manage::select_interfaces(interfaces, filter);
+    let mut unknown: Vec<&String> = filter
+        .into_iter()
+        .flatten()
+        .filter(|name| !interfaces.iter().any(|interface| interface.name() == *name))
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort();
+        warn!("");
+        warn!("Unknown interfaces specified: {unknown:?}");
+    }
+    interfaces
+}
+
+fn scan<T: EtherInterface>(interfaces: Vec<T>, format: Format) {
+    let mut devices = vec![];
+    for interface in &interfaces {
+        let header = format!("Scanning Interface {interface:?}");
+        info!("");
+        info!("{header}");
+        info!("{}", "-".repeat(header.len()));
+        match manage::scan_interface(interface) {
+            Ok(found) => devices.extend(found),
+            Err(err) => info!("{interface}: Failed to scan ({err})"),
+        }
+    }
+    print_devices(format, &devices);
+}
+
+fn print_found(format: Format, addr: EtherAddr, found: Option<(&str, OUI)>) {
+    let interface = found.map(|(interface, _)| interface);
+    let vendor = found.and_then(|(_, oui)| oui.name());
+    match format {
+        Format::Text => match interface {
+            Some(interface) => println!("{addr:?}: Found on {interface}"),
+            None => println!("{addr:?}: Not found"),
+        },
+        Format::Json => {
+            let record = serde_json::json!({
+                "address": format!("{addr:?}"),
+                "interface": interface,
+                "vendor": vendor,
+            });
+            println!("{}", serde_json::to_string_pretty(&record).unwrap());
+        }
+        Format::Csv | Format::Table => {
+            let headers = ["address", "interface", "vendor"];
+            let row = vec![
+                format!("{addr:?}"),
+                interface.unwrap_or_default().to_string(),
+                vendor.unwrap_or_default().to_string(),
+            ];
+            if format == Format::Csv {
+                print_csv_row(&headers.map(String::from));
+                print_csv_row(&row);
+            } else {
+                print_table(&headers, &[row]);
+            }
+        }
+    }
+}
+
+/// How `dump` prints each message
+#[derive(Copy, Clone)]
+struct Output {
+    format: Format,
+    decode: bool,
+}
+impl Output {
+    const HEADERS: [&'static str; 6] = ["interface", "source", "version", "type", "name", "length"];
+
+    /// Print the column headings, before any messages
+    fn header(&self) {
+        match self.format {
+            Format::Csv => print_csv_row(&Self::HEADERS.map(String::from)),
+            Format::Table => {
+                let [interface, source, version, mmtype, name, length] = Self::HEADERS;
+                println!("{interface:16} {source:17} {version:8} {mmtype:6} {length:6} {name}");
+            }
+            Format::Text | Format::Json => {}
+        }
+    }
+
+    fn message(&self, interface: &str, addr: EtherAddr, data: &[u8]) {
+        if self.format == Format::Text {
+            return print_message(interface, addr, data, self.decode);
+        }
+        let dissection = dissect::dissect(data);
+        let (version, mmtype) = match data {
+            [mmv, low, high, ..] => (
+                format!("{:?}", MMV(*mmv)),
+                format!("{:04x}", u16::from_le_bytes([*low, *high])),
+            ),
+            _ => Default::default(),
+        };
+        match self.format {
+            Format::Json => {
+                let mut record = serde_json::json!({
+                    "interface": interface,
+                    "source": format!("{addr:?}"),
+                    "version": version,
+                    "type": mmtype,
+                    "name": dissection.name,
+                    "length": data.len(),
+                });
+                if self.decode {
+                    record["fields"] = dissection.fields.iter().map(field_json).collect();
+                    record["error"] = dissection.error.map(|err| err.to_string()).into();
+                }
+                // One message per line (JSON Lines)
+                println!("{record}");
+            }
+            Format::Csv => print_csv_row(&[
+                interface.to_string(),
+                format!("{addr:?}"),
+                version,
+                mmtype,
+                dissection.name,
+                data.len().to_string(),
+            ]),
+            Format::Table => {
+                let length = data.len();
+                let name = dissection.name;
+                println!("{interface:16} {addr:17?} {version:8} {mmtype:6} {length:<6} {name}");
+            }
+            Format::Text => unreachable!(),
+        }
+    }
+}
+
+fn field_json(field: &dissect::Field) -> serde_json::Value {
+    let value = match field.value {
+        dissect::Value::None => serde_json::Value::Null,
+        _ => match field.value.as_u64() {
+            Some(value) => value.into(),
+            None => field.value.to_string().into(),
+        },
+    };
+    let mut json = serde_json::json!({ "name": field.name, "value": value });
+    if !field.children.is_empty() {
+        json["children"] = field.children.iter().map(field_json).collect();
+    }
+    json
+}
+
+fn dump<T: EtherInterface>(
+    interfaces: impl Iterator<Item = T>,
+    pcap: Option<&str>,
+    output: Output,
+) {
+    let mut writer = pcap.map(|path| {
+        let file = std::io::BufWriter::new(std::fs::File::create(path).unwrap());
+        pcap::PcapNgWriter::new(file).unwrap()
+    });
+    let mut sockets = vec![];
+    for interface in interfaces {
+        match interface.open(EtherType::HOMEPLUG_AV) {
+            Ok(socket) => {
+                let index = writer
+                    .as_mut()
+                    .map(|writer| writer.add_interface(interface.name()).unwrap());
+                sockets.push((interface, socket, index));
+            }
+            Err(err) => {
+                warn!("Failed to listen on '{interface:?}': {err}");
+            }
+        }
+    }
+    let writer = std::sync::Arc::new(std::sync::Mutex::new(writer));
+    let mut threads = vec![];
+    for (interface, mut socket, index) in sockets {
+        let writer = writer.clone();
+        threads.push(std::thread::spawn(move || {
+            debug!("Listening for messages on {interface:?}");
+            let mut buffer = [0; 1500];
+            while let Some((addr, data)) = socket.recvfrom(&mut buffer, None).unwrap() {
+                output.message(&interface.to_string(), addr, data);
+                if let (Some(writer), Some(index)) = (writer.lock().unwrap().as_mut(), index) {
+                    let timestamp = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default();
+                    // Sockets don't report the destination, so record the frame as addressed to us
+                    let frame = pcap::CapturedFrame {
+                        timestamp,
+                        interface: index,
+                        destination: interface.address(),
+                        source: addr,
+                        ethertype: EtherType::HOMEPLUG_AV,
+                        payload: data.to_vec(),
+                    };
+                    writer.write_frame(&frame).unwrap();
+                    writer.flush().unwrap();
+                }
+            }
+        }));
+    }
+    for t in threads {
+        t.join().unwrap();
+    }
+}
+
+fn print_message(interface: &str, addr: EtherAddr, data: &[u8], decode: bool) {
+    if decode {
+        print!(
+            "{interface:w$} [{addr:?}] {}",
+            dissect::dissect(data),
+            w = 16
+        );
+    } else {
+        match UnknownMessage::try_from(data) {
+            Ok(msg) => println!("{interface:w$} [{addr:?}] {msg:?}", w = 16),
+            Err(err) => println!("{interface:w$} [{addr:?}] {err}", w = 16),
+        }
+    }
+}
+
+fn replay(path: &str, output: Output) {
+    let file = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+    let mut reader = pcap::PcapReader::new(file).unwrap();
+    while let Some(frame) = reader.next_frame().unwrap() {
+        if frame.ethertype != EtherType::HOMEPLUG_AV && frame.ethertype != EtherType::MEDIAXTREAM {
+            continue;
+        }
+        let interface = reader.interface_name(frame.interface).unwrap_or(path);
+        output.message(interface, frame.source, &frame.payload);
+    }
+}
+
+fn valid_etheraddr(s: String) -> Result<(), String> {
+    EtherAddr::from_str(&s)
+        .map(|_| ())
+        .map_err(|_| format!("Invalid MAC address ('{s}')"))
+}
+
+fn valid_seconds(s: String) -> Result<(), String> {
+    u64::from_str(&s)
+        .map(|_| ())
+        .map_err(|_| format!("Invalid number of seconds ('{s}')"))
+}
+
+struct Logger;
+impl log::Log for Logger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}", record.args());
+        }
+    }
+    fn flush(&self) {}
+}
+
+fn main() {
+    log::set_logger(&Logger).unwrap();
+
+    let matches = App::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about("Power-line communication (HomePlug AV) management utility")
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .help("Increase verbosity")
+                .global(true)
+                .multiple(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("interfaces")
+                .long("interface")
+                .help("Select the interface(s) to discover with")
+                .multiple(true)
+                .number_of_values(1)
+                .use_delimiter(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .help("Output format of scan, find and dump")
+                .possible_values(&["text", "json", "csv", "table"])
+                .default_value("text")
+                .global(true),
+        )
+        .subcommand(App::new("scan").about("Discover and list devices"))
+        .subcommand(
+            App::new("find")
+                .about("Find which interface a specific device is reachable")
+                .arg(
+                    Arg::with_name("device")
+                        .required(true)
+                        .validator(valid_etheraddr),
+                ),
+        )
+        .subcommand(
+            App::new("set-name")
+                .about("Set the name of a device")
+                .args(&[
+                    Arg::with_name("device")
+                        .required(true)
+                        .validator(valid_etheraddr),
+                    Arg::with_name("name").required(true),
+                ]),
+        )
+        .subcommand(
+            App::new("set-password")
+                .about("Set the network password of a device")
+                .args(&[
+                    Arg::with_name("device")
+                        .required(true)
+                        .validator(valid_etheraddr),
+                    Arg::with_name("password").required(true),
+                    Arg::with_name("secure")
+                        .long("secure")
+                        .help("Use the secure security level when deriving the NID"),
+                    Arg::with_name("dpw")
+                        .long("device-password")
+                        .takes_value(true)
+                        .value_name("DPW")
+                        .help("Set the key of a remote device, using the password on its label"),
+                ]),
+        )
+        .subcommand(
+            App::new("pair")
+                .about("Start Simple Connect pairing, as if the button was pressed")
+                .args(&[
+                    Arg::with_name("device")
+                        .required(true)
+                        .validator(valid_etheraddr),
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .takes_value(true)
+                        .value_name("SECONDS")
+                        .default_value("120")
+                        .validator(valid_seconds)
+                        .help("How long to wait for another device to join"),
+                ]),
+        )
+        .subcommand(
+            App::new("dump").about("Dump all messages").args(&[
+                Arg::with_name("pcap")
+                    .long("pcap")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("Also write the messages to a pcapng capture"),
+                Arg::with_name("read")
+                    .long("read")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .conflicts_with("pcap")
+                    .help("Dump the messages from a pcap or pcapng capture instead"),
+                Arg::with_name("decode")
+                    .long("decode")
+                    .help("Decode every field of each message"),
+            ]),
+        )
+        .get_matches();
+
+    match matches.occurrences_of("verbose") {
+        0 => log::set_max_level(log::LevelFilter::Warn),
+        1 => log::set_max_level(log::LevelFilter::Info),
+        2 => log::set_max_level(log::LevelFilter::Debug),
+        _ => log::set_max_level(log::LevelFilter::Trace),
+    }
+    debug!("Debuglevel: {}", log::max_level());
+
+    let filter = matches
+        .values_of_lossy("interfaces")
+        .map(HashSet::from_iter);
+
+    if let Some(filter) = &filter {
+        debug!("Interfaces: {filter:?}");
+    } else {
+        debug!("Interfaces: ALL");
+    }
+
+    let format = Format::from_str(&matches.value_of_lossy("format").unwrap()).unwrap();
+    let interfaces = platform_interfaces().unwrap();
+
+    match matches.subcommand() {
+        ("find", Some(args)) => {
+            let addr = EtherAddr::from_str(&args.value_of_lossy("device").unwrap()).unwrap();
+            let found = manage::find_device(select(interfaces, filter.as_ref()), addr).unwrap();
+            print_found(
+                format,
+                addr,
+                found.as_ref().map(|(i, oui)| (i.name(), *oui)),
+            );
+        }
+        ("set-name", Some(args)) => {
+            let addr = EtherAddr::from_str(&args.value_of_lossy("device").unwrap()).unwrap();
+            let name = args.value_of_lossy("name").unwrap();
+            match manage::find_device(select(interfaces, filter.as_ref()), addr).unwrap() {
+                Some((interface, oui)) => {
+                    match manage::set_device_name(&interface, addr, oui, &name) {
+                        Ok(()) => println!("{addr:?}: Name set"),
+                        Err(manage::DriverError::NoResponse) => println!("{addr:?}: No reply"),
+                        Err(err) => println!("{addr:?}: {err}"),
+                    }
+                }
+                None => println!("{addr:?}: Not found"),
+            }
+        }
+        ("set-password", Some(args)) => {
+            let addr = EtherAddr::from_str(&args.value_of_lossy("device").unwrap()).unwrap();
+            let password = args.value_of_lossy("password").unwrap();
+            let security = if args.is_present("secure") {
+                SecurityLevel::SECURE
+            } else {
+                SecurityLevel::SIMPLE
+            };
+            let interfaces = select(interfaces, filter.as_ref());
+            let nid = match args.value_of_lossy("dpw") {
+                Some(dpw) => {
+                    let nid = manage::set_remote_device_password(
+                        interfaces, addr, &dpw, &password, security,
+                    );
+                    nid.unwrap()
+                }
+                None => match manage::find_device(interfaces, addr).unwrap() {
+                    Some((interface, oui)) => {
+                        let nid =
+                            manage::set_device_password(&interface, addr, oui, &password, security);
+                        nid.unwrap()
+                    }
+                    None => {
+                        println!("{addr:?}: Not found");
+                        return;
+                    }
+                },
+            };
+            match nid {
+                Some(nid) => println!("{addr:?}: Network key set (NID {nid:02x?})"),
+                None => println!("{addr:?}: Network key not accepted"),
+            }
+        }
+        ("pair", Some(args)) => {
+            let addr = EtherAddr::from_str(&args.value_of_lossy("device").unwrap()).unwrap();
+            let timeout = u64::from_str(&args.value_of_lossy("timeout").unwrap()).unwrap();
+            match manage::find_device(select(interfaces, filter.as_ref()), addr).unwrap() {
+                Some((interface, oui)) => {
+                    let timeout = Duration::from_secs(timeout);
+                    match manage::simple_connect(&interface, addr, oui, timeout).unwrap() {
+                        Some(nid) => println!("{addr:?}: Paired (NID {nid:02x?})"),
+                        None => println!("{addr:?}: Not paired"),
+                    }
+                }
+                None => println!("{addr:?}: Not found"),
+            }
+        }
+        ("dump", Some(args)) => {
+            let output = Output {
+                format,
+                decode: args.is_present("decode"),
+            };
+            output.header();
+            match args.value_of("read") {
+                Some(path) => replay(path, output),
+                None => dump(interfaces, args.value_of("pcap"), output),
+            }
+        }
+        ("scan", _) | ("", _) => {
+            scan(select(interfaces, filter.as_ref()), format);
+        }
+        _ => panic!(),
+    }
+}
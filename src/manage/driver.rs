@@ -0,0 +1,434 @@
+//! Per-chipset implementations of common device operations
+//!
+//! [`DeviceDriver`]'s provided methods use the standard HomePlug AV messages, so a driver
+//! only overrides the operations its chipset does differently. Use [`driver`] to pick one
+//! from the OUI a station reports in `CM_STA_CAP`.
+
+extern crate std;
+
+use super::name_to_string;
+use crate::homeplug::*;
+use crate::*;
+use core::convert::TryFrom;
+use log::{info, warn};
+use std::format;
+use std::string::{String, ToString};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DriverError<E> {
+    Request(RequestError<E>),
+    /// Device didn't reply
+    NoResponse,
+    /// Device replied, but didn't carry out the request
+    Rejected,
+    /// Operation isn't available on this chipset
+    Unsupported,
+}
+impl<E> From<RequestError<E>> for DriverError<E> {
+    fn from(err: RequestError<E>) -> Self {
+        DriverError::Request(err)
+    }
+}
+impl<E: core::fmt::Display> core::fmt::Display for DriverError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DriverError::Request(err) => write!(f, "{}", err),
+            DriverError::NoResponse => write!(f, "No reply"),
+            DriverError::Rejected => write!(f, "Request rejected"),
+            DriverError::Unsupported => write!(f, "Not supported by this device"),
+        }
+    }
+}
+
+/// Counters for the link between a device and one of its peers
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LinkStatistics {
+    /// Standard `CM_LINK_STATS` statistics
+    HomePlug {
+        tx: Option<TxLinkStatistics>,
+        rx: Option<RxLinkStatistics>,
+    },
+    /// Qualcomm `VS_LNK_STATS` counters
+    Qualcomm {
+        tx: Option<qualcomm::TxLinkCounters>,
+        rx: Option<qualcomm::RxLinkCounters>,
+    },
+}
+
+fn open<T: EtherInterface>(
+    interface: &T,
+    ethertype: EtherType,
+) -> Result<T::Socket, DriverError<T::Error>> {
+    interface
+        .open(ethertype)
+        .map_err(|err| DriverError::Request(RequestError::Socket(err)))
+}
+
+/// How long to wait for the reply to a request sent with [`request`]
+const REPLY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Send a request, treating a `CM_MME_ERROR.IND` instead of the reply as a rejection
+///
+/// Every driver request goes through here, so the same device behaviour is reported as the
+/// same [`DriverError`] whichever message was sent.
+fn request<'a, T: EtherInterface, M: MessageTX<'a>>(
+    interface: &T,
+    addr: EtherAddr,
+    request: M,
+) -> Result<Reply, DriverError<T::Error>> {
+    request_over(interface, EtherType::HOMEPLUG_AV, addr, request)
+}
+
+/// [`request`] over a vendor `ethertype`
+fn request_over<'a, T: EtherInterface, M: MessageTX<'a>>(
+    interface: &T,
+    ethertype: EtherType,
+    addr: EtherAddr,
+    request: M,
+) -> Result<Reply, DriverError<T::Error>> {
+    let mut client = Client::new(open(interface, ethertype)?);
+    match client.request(addr, request, REPLY_TIMEOUT)? {
+        Some(reply) => match reply.error() {
+            Some(error) => {
+                warn!("[{addr:?}] {error:?}");
+                Err(DriverError::Rejected)
+            }
+            None => Ok(reply),
+        },
+        None => Err(DriverError::NoResponse),
+    }
+}
+
+fn parse<'a, R, E>(reply: &'a Reply) -> Result<R, DriverError<E>>
+where
+    R: TryFrom<&'a [u8], Error = ParseError>,
+{
+    reply
+        .parse()
+        .map_err(|err| DriverError::Request(RequestError::Parse(err)))
+}
+
+/// Log a parsed reply, which is a rejection unless it reports `success`
+fn check<M: core::fmt::Debug, E>(
+    addr: EtherAddr,
+    m: &M,
+    success: bool,
+) -> Result<(), DriverError<E>> {
+    if success {
+        info!("[{addr:?}] {m:?}");
+        Ok(())
+    } else {
+        warn!("[{addr:?}] {m:?}");
+        Err(DriverError::Rejected)
+    }
+}
+
+fn hfid<T: EtherInterface>(
+    interface: &T,
+    addr: EtherAddr,
+    message: HFIDRequest,
+) -> Result<String, DriverError<T::Error>> {
+    let reply = request(interface, addr, message)?;
+    let m: HFID = parse(&reply)?;
+    check(addr, &m, m.req_type() != HFIDReqType::FAILURE)?;
+    Ok(m.hfid().to_string())
+}
+
+/// Operations on a device, implemented for a family of chipsets
+pub trait DeviceDriver<T: EtherInterface>: Send + Sync {
+    /// Chipset family, for logging
+    fn chipset(&self) -> &'static str;
+
+    /// User assigned name (HFID)
+    fn get_name(&self, interface: &T, addr: EtherAddr) -> Result<String, DriverError<T::Error>> {
+        hfid(interface, addr, HFIDRequest::GetUsr)
+    }
+
+    /// Set the user assigned name, truncated to 64 bytes
+    fn set_name(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+        name: &[u8; 64],
+    ) -> Result<(), DriverError<T::Error>> {
+        let reply = request(interface, addr, HFIDRequest::SetUsr { hfid: *name })?;
+        let m: HFID = parse(&reply)?;
+        check(addr, &m, m.req_type() != HFIDReqType::FAILURE)
+    }
+
+    /// Firmware name or version
+    fn firmware_version(
+        &self,
+        _interface: &T,
+        _addr: EtherAddr,
+    ) -> Result<String, DriverError<T::Error>> {
+        Err(DriverError::Unsupported)
+    }
+
+    /// Manufacturer assigned (hardware) name
+    fn hardware_name(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+    ) -> Result<String, DriverError<T::Error>> {
+        hfid(interface, addr, HFIDRequest::GetMfg)
+    }
+
+    /// Restart the device
+    fn reset(&self, _interface: &T, _addr: EtherAddr) -> Result<(), DriverError<T::Error>> {
+        Err(DriverError::Unsupported)
+    }
+
+    /// Join the network identified by `nid`, using the network membership key `nmk`
+    fn set_key(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+        nmk: [u8; 16],
+        nid: [u8; 7],
+    ) -> Result<(), DriverError<T::Error>> {
+        let reply = request(interface, addr, SetKeyRequest::nmk(nmk, nid))?;
+        let m: SetKey = parse(&reply)?;
+        check(addr, &m, m.is_success())
+    }
+
+    /// Start Simple Connect pairing, as if the device's push button was pressed
+    fn simple_connect(&self, interface: &T, addr: EtherAddr) -> Result<(), DriverError<T::Error>> {
+        // The confirmation has no result, devices that can't pair send an error instead
+        let reply = request(interface, addr, ScJoinRequest { cco_capability: 0 })?;
+        let m: ScJoin = parse(&reply)?;
+        info!("[{addr:?}] {m:?}");
+        Ok(())
+    }
+
+    /// Counters for the data link between the device and `peer`, on the device's network
+    fn link_stats(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+        peer: EtherAddr,
+    ) -> Result<LinkStatistics, DriverError<T::Error>> {
+        let reply = request(interface, addr, NetworkInfoRequest)?;
+        let info: NetworkInfo = parse(&reply)?;
+        let nid = match info.networks().next() {
+            Some(network) => network.nid(),
+            None => return Err(DriverError::Rejected),
+        };
+        // The transmit and receive links are requested separately
+        let link = |receive: bool| {
+            let message = LinkStatsRequest {
+                req_type: LinkStatsReqType::GET,
+                req_id: receive as u8,
+                nid,
+                lid: 0,
+                receive,
+                management: false,
+                peer,
+            };
+            request(interface, addr, message)
+        };
+        let (tx, rx) = (link(false)?, link(true)?);
+        let (tx, rx): (LinkStats, LinkStats) = (parse(&tx)?, parse(&rx)?);
+        check(addr, &tx, tx.is_success())?;
+        check(addr, &rx, rx.is_success())?;
+        Ok(LinkStatistics::HomePlug {
+            tx: tx.tx(),
+            rx: rx.rx(),
+        })
+    }
+}
+
+/// Devices with no vendor specific support, using only standard HomePlug AV messages
+pub struct HomePlugDriver;
+impl<T: EtherInterface> DeviceDriver<T> for HomePlugDriver {
+    fn chipset(&self) -> &'static str {
+        "HomePlug AV"
+    }
+}
+
+/// Qualcomm and Atheros devices
+pub struct QualcommDriver;
+impl<T: EtherInterface> DeviceDriver<T> for QualcommDriver {
+    fn chipset(&self) -> &'static str {
+        "Qualcomm"
+    }
+
+    fn firmware_version(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+    ) -> Result<String, DriverError<T::Error>> {
+        let reply = request(interface, addr, qualcomm::SoftwareVersionRequest)?;
+        let m: qualcomm::SoftwareVersion = parse(&reply)?;
+        check(addr, &m, m.status().is_success())?;
+        Ok(m.version().to_string())
+    }
+
+    fn reset(&self, interface: &T, addr: EtherAddr) -> Result<(), DriverError<T::Error>> {
+        let reply = request(interface, addr, qualcomm::ResetDeviceRequest)?;
+        let m: qualcomm::ResetDevice = parse(&reply)?;
+        check(addr, &m, m.status().is_success())
+    }
+
+    /// Qualcomm devices derive the NID from their own PIB security level, which is assumed to
+    /// be simple, so only NIDs of [`SecurityLevel::SIMPLE`] are supported
+    fn set_key(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+        nmk: [u8; 16],
+        nid: [u8; 7],
+    ) -> Result<(), DriverError<T::Error>> {
+        if SecurityLevel::of_nid(nid) != SecurityLevel::SIMPLE {
+            return Err(DriverError::Unsupported);
+        }
+        let reply = request(interface, addr, qualcomm::SetNmkRequest::local(nmk))?;
+        let m: qualcomm::SetNmk = parse(&reply)?;
+        check(addr, &m, m.status().is_success())
+    }
+
+    fn simple_connect(&self, interface: &T, addr: EtherAddr) -> Result<(), DriverError<T::Error>> {
+        let action = qualcomm::PushButtonAction::JOIN;
+        let reply = request(interface, addr, qualcomm::PushButtonRequest { action })?;
+        let m: qualcomm::PushButton = parse(&reply)?;
+        check(addr, &m, m.status().is_success())
+    }
+
+    fn link_stats(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+        peer: EtherAddr,
+    ) -> Result<LinkStatistics, DriverError<T::Error>> {
+        let message = qualcomm::LinkCountersRequest {
+            clear: false,
+            direction: qualcomm::Direction::BOTH,
+            lid: 0,
+            peer,
+        };
+        let reply = request(interface, addr, message)?;
+        let m: qualcomm::LinkCounters = parse(&reply)?;
+        check(addr, &m, m.status().is_success())?;
+        Ok(LinkStatistics::Qualcomm {
+            tx: m.tx(),
+            rx: m.rx(),
+        })
+    }
+}
+
+/// Broadcom and Gigle devices
+///
+/// These don't implement the standard HomePlug AV HFID requests, and expose names as
+/// properties over the Mediaxtream ethertype instead.
+pub struct BroadcomDriver;
+impl BroadcomDriver {
+    /// Sequence numbers of each request, echoed in its reply
+    const SEQ_GET_NAME: u8 = 0x77;
+    const SEQ_GET_FIRMWARE: u8 = 0x78;
+    const SEQ_GET_HARDWARE: u8 = 0x79;
+    const SEQ_SET_NAME: u8 = 0x80;
+
+    fn property<T: EtherInterface>(
+        interface: &T,
+        addr: EtherAddr,
+        seq: u8,
+        property: broadcom::Property,
+    ) -> Result<String, DriverError<T::Error>> {
+        let message = broadcom::GetPropertyRequest { seq, property };
+        let reply = request_over(interface, EtherType::MEDIAXTREAM, addr, message)?;
+        let m: broadcom::GetProperty = parse(&reply)?;
+        check(addr, &m, m.first().is_some())?;
+        Ok(m.first().map(name_to_string).unwrap_or_default())
+    }
+}
+impl<T: EtherInterface> DeviceDriver<T> for BroadcomDriver {
+    fn chipset(&self) -> &'static str {
+        "Broadcom"
+    }
+
+    fn get_name(&self, interface: &T, addr: EtherAddr) -> Result<String, DriverError<T::Error>> {
+        Self::property(
+            interface,
+            addr,
+            Self::SEQ_GET_NAME,
+            broadcom::Property::HFID_USER,
+        )
+    }
+
+    fn set_name(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+        name: &[u8; 64],
+    ) -> Result<(), DriverError<T::Error>> {
+        let message = broadcom::SetPropertyRequest {
+            seq: Self::SEQ_SET_NAME,
+            property: broadcom::Property::HFID_USER,
+            data: *name,
+        };
+        let reply = request_over(interface, EtherType::MEDIAXTREAM, addr, message)?;
+        let m: broadcom::SetProperty = parse(&reply)?;
+        check(addr, &m, m.is_success())
+    }
+
+    fn firmware_version(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+    ) -> Result<String, DriverError<T::Error>> {
+        Self::property(
+            interface,
+            addr,
+            Self::SEQ_GET_FIRMWARE,
+            broadcom::Property::NAME_A0,
+        )
+    }
+
+    fn hardware_name(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+    ) -> Result<String, DriverError<T::Error>> {
+        Self::property(
+            interface,
+            addr,
+            Self::SEQ_GET_HARDWARE,
+            broadcom::Property::NAME_B0,
+        )
+    }
+}
+
+/// ST and IoTecha GreenPHY modems
+///
+/// Their vendor messages aren't publicly documented (see [`crate::homeplug::st`]), so these
+/// use the standard messages.
+pub struct StDriver;
+impl<T: EtherInterface> DeviceDriver<T> for StDriver {
+    fn chipset(&self) -> &'static str {
+        "ST"
+    }
+
+    /// Implementation version from `CM_STA_CAP`, as hex
+    fn firmware_version(
+        &self,
+        interface: &T,
+        addr: EtherAddr,
+    ) -> Result<String, DriverError<T::Error>> {
+        let reply = request(interface, addr, StationCapabilitiesRequest)?;
+        let m: StationCapabilities = parse(&reply)?;
+        let [high, low] = m.implementation_version();
+        Ok(format!("{high:02x}{low:02x}"))
+    }
+}
+
+/// Driver for devices reporting `oui` in `CM_STA_CAP`
+///
+/// New chipsets are supported by adding a [`DeviceDriver`] implementation here.
+pub fn driver<T: EtherInterface>(oui: OUI) -> &'static dyn DeviceDriver<T> {
+    match oui {
+        OUI::QUALCOMM => &QualcommDriver,
+        OUI::BROADCOM => &BroadcomDriver,
+        OUI::ST => &StDriver,
+        _ => &HomePlugDriver,
+    }
+}
@@ -0,0 +1,616 @@
+//! Vendor aware device management built on the message level API
+//!
+//! Chipsets differ in which messages they support for the same operation (e.g. Broadcom
+//! adapters don't implement the standard HomePlug AV name requests). These functions pick
+//! a [`DeviceDriver`] based on the OUI each device reports in `CM_STA_CAP`.
+
+extern crate std;
+
+use crate::homeplug::*;
+use crate::*;
+use core::time::Duration;
+use log::{debug, info};
+use std::collections::HashSet;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+mod driver;
+pub use driver::*;
+
+/// Everything a scan learns about one station
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceInfo {
+    /// Name of the interface the station was reached through
+    pub interface: String,
+    pub address: EtherAddr,
+    /// `None` if the station didn't answer `CM_STA_CAP`
+    pub version: Option<StationVersion>,
+    pub oui: OUI,
+    /// Ethernet devices bridged by the station
+    pub bridged: Vec<EtherAddr>,
+    /// Average PHY rates to the other power-line stations, from `CM_NW_STATS`
+    pub rates: Vec<PhyRate>,
+    /// User assigned name
+    pub hfid: Option<String>,
+    /// Manufacturer assigned (hardware) name
+    pub manufacturer: Option<String>,
+    /// Firmware name or version
+    pub firmware: Option<String>,
+}
+
+/// Average coded PHY rates between a station and one of its peers, in Mbps
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhyRate {
+    pub peer: EtherAddr,
+    pub tx: u16,
+    pub rx: u16,
+}
+
+/// Interfaces named in `names`, or all that are up and not loopback if `names` is `None`
+pub fn select_interfaces<T: EtherInterface>(
+    interfaces: impl IntoIterator<Item = T>,
+    names: Option<&HashSet<String>>,
+) -> Vec<T> {
+    interfaces
+        .into_iter()
+        .filter(|interface| {
+            let selected = names.map_or_else(
+                || interface.is_up() && !interface.is_loopback(),
+                |names| names.contains(interface.name()),
+            );
+            if !selected {
+                debug!("{interface}: Skipped Interface");
+            }
+            selected
+        })
+        .collect()
+}
+
+/// Names are padded with NULs to their fixed size
+fn name_to_string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// Query a single station through `socket`, an open `HOMEPLUG_AV` socket on `interface`
+///
+/// Malformed replies leave the fields they'd have filled empty, only socket errors fail.
+pub fn device_info<T: EtherInterface>(
+    interface: &T,
+    socket: &mut T::Socket,
+    addr: EtherAddr,
+) -> Result<DeviceInfo, RequestError<T::Error>> {
+    let mut b = [0; ETHERNET_MTU];
+    let mut info = DeviceInfo {
+        interface: interface.name().to_string(),
+        address: addr,
+        version: None,
+        oui: OUI::default(),
+        bridged: Vec::new(),
+        rates: Vec::new(),
+        hfid: None,
+        manufacturer: None,
+        firmware: None,
+    };
+
+    let reply = send_request(socket, &mut b, addr, StationCapabilitiesRequest);
+    if let Some(m) = malformed(addr, reply)? {
+        info!("[{addr:?}] {m:?}");
+        info.oui = m.oui();
+        info.version = Some(m.version());
+    }
+    let reply = send_request(socket, &mut b, addr, BridgeInfoRequest);
+    if let Some(m) = malformed(addr, reply)? {
+        info!("[{addr:?}] {m:?}");
+        info.bridged = m.destinations().collect();
+    }
+    // Not every station implements CM_NW_STATS, so any failure just means no rates. Stations
+    // that didn't answer CM_STA_CAP aren't asked, rather than waiting for them again.
+    if info.version.is_some() {
+        match send_request(socket, &mut b, addr, NetworkStatsRequest) {
+            Ok(Some(m)) => {
+                info!("[{addr:?}] {m:?}");
+                let rates = m.stations().map(|station| PhyRate {
+                    peer: station.addr(),
+                    tx: station.tx_rate(),
+                    rx: station.rx_rate(),
+                });
+                info.rates = rates.collect();
+            }
+            Ok(None) => debug!("[{addr:?}] No PHY rates"),
+            Err(err) => info!("[{addr:?}] No PHY rates ({err})"),
+        }
+    }
+    let driver = driver::<T>(info.oui);
+    info.hfid = malformed(addr, optional(driver.get_name(interface, addr)))?;
+    info.manufacturer = malformed(addr, optional(driver.hardware_name(interface, addr)))?;
+    info.firmware = malformed(addr, optional(driver.firmware_version(interface, addr)))?;
+    Ok(info)
+}
+
+/// Treat anything but a failed request as missing information
+fn optional<T, E>(result: Result<T, DriverError<E>>) -> Result<Option<T>, RequestError<E>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(DriverError::Request(err)) => Err(err),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Treat a malformed reply from `addr` like a missing one, so one station can't fail a scan
+fn malformed<T, E>(
+    addr: EtherAddr,
+    result: Result<Option<T>, RequestError<E>>,
+) -> Result<Option<T>, RequestError<E>> {
+    match result {
+        Err(RequestError::Parse(err)) => {
+            info!("[{addr:?}] Ignoring malformed reply ({err})");
+            Ok(None)
+        }
+        result => result,
+    }
+}
+
+/// Discover and query every station reachable through `interface`, ordered by address
+///
+/// Stations listed by the devices that answer discovery are queried too, even if they
+/// didn't answer themselves.
+pub fn scan_interface<T: EtherInterface>(
+    interface: &T,
+) -> Result<Vec<DeviceInfo>, RequestError<T::Error>> {
+    let mut socket = interface.open(EtherType::HOMEPLUG_AV)?;
+
+    let mut stations = HashSet::new();
+    discover_devices(&mut socket, |addr, msg| {
+        info!("[{addr:?}] {msg:?}");
+        stations.insert(addr);
+        stations.extend(msg.stations().map(|station| station.addr()));
+    })?;
+    info!("{interface}: Discovered {} stations", stations.len());
+
+    let mut stations: Vec<EtherAddr> = stations.into_iter().collect();
+    stations.sort_by_key(|addr| addr.0);
+    stations
+        .into_iter()
+        .map(|addr| device_info(interface, &mut socket, addr))
+        .collect()
+}
+
+/// Find the interface `addr` is reachable through, along with its OUI
+pub fn find_device<T: EtherInterface>(
+    interfaces: impl IntoIterator<Item = T>,
+    addr: EtherAddr,
+) -> Result<Option<(T, OUI)>, RequestError<T::Error>> {
+    debug!("{addr:?} searching for interface");
+    for interface in interfaces {
+        let mut s = interface.open(EtherType::HOMEPLUG_AV)?;
+        let mut b = [0; ETHERNET_MTU];
+        if let Some(m) = send_request(&mut s, &mut b, addr, StationCapabilitiesRequest)? {
+            debug!("{addr:?} found on {interface}");
+            return Ok(Some((interface, m.oui())));
+        }
+    }
+    debug!("{addr:?} not found on any selected interface");
+    Ok(None)
+}
+
+/// Set the user assigned name (HFID) of a device
+///
+/// Names are truncated to 64 bytes. A device that refuses the name is reported as
+/// [`DriverError::Rejected`], and one that doesn't answer as [`DriverError::NoResponse`].
+pub fn set_device_name<T: EtherInterface>(
+    interface: &T,
+    addr: EtherAddr,
+    oui: OUI,
+    name: &str,
+) -> Result<(), DriverError<T::Error>> {
+    let mut hfid = [0u8; 64];
+    hfid.iter_mut()
+        .zip(name.trim().as_bytes())
+        .for_each(|(dest, src)| *dest = *src);
+
+    driver::<T>(oui).set_name(interface, addr, &hfid)
+}
+
+/// Set the network password of a device, returning the new NID if it was accepted
+#[cfg(feature = "sha2")]
+pub fn set_device_password<T: EtherInterface>(
+    interface: &T,
+    addr: EtherAddr,
+    oui: OUI,
+    password: &str,
+    security: SecurityLevel,
+) -> Result<Option<[u8; 7]>, RequestError<T::Error>> {
+    let nmk = generate_nmk(password);
+    let nid = generate_nid(nmk, security);
+    let result = driver::<T>(oui).set_key(interface, addr, nmk, nid);
+    Ok(optional(result)?.map(|_| nid))
+}
+
+/// How often [`simple_connect`] checks whether the device has joined a network
+const SIMPLE_CONNECT_POLL: Duration = Duration::from_millis(500);
+
+/// NID of the network a device is a member of, from `CM_NW_INFO`
+pub fn network_id<T: EtherInterface>(
+    interface: &T,
+    addr: EtherAddr,
+) -> Result<Option<[u8; 7]>, RequestError<T::Error>> {
+    let mut s = interface.open(EtherType::HOMEPLUG_AV)?;
+    let mut b = [0; ETHERNET_MTU];
+    let info = send_request(&mut s, &mut b, addr, NetworkInfoRequest)?;
+    Ok(info.and_then(|info| info.networks().next().map(|network| network.nid())))
+}
+
+/// Start Simple Connect pairing on a device, and wait up to `timeout` for it to join a network
+///
+/// The button of the adapter to pair with must be pressed (or triggered) within the same two
+/// minute window. Returns the NID of the newly joined network, or `None` if the device
+/// couldn't start pairing or its network didn't change in time.
+pub fn simple_connect<T: EtherInterface>(
+    interface: &T,
+    addr: EtherAddr,
+    oui: OUI,
+    timeout: Duration,
+) -> Result<Option<[u8; 7]>, RequestError<T::Error>> {
+    let before = network_id(interface, addr)?;
+    if optional(driver::<T>(oui).simple_connect(interface, addr))?.is_none() {
+        return Ok(None);
+    }
+    info!("{addr:?} pairing (NID {before:02x?})");
+    let start = std::time::Instant::now();
+    loop {
+        let nid = network_id(interface, addr)?;
+        if nid.is_some() && nid != before {
+            info!("{addr:?} joined NID {nid:02x?}");
+            return Ok(nid);
+        }
+        if start.elapsed() >= timeout {
+            debug!("{addr:?} didn't join a network");
+            return Ok(None);
+        }
+        std::thread::sleep(SIMPLE_CONNECT_POLL);
+    }
+}
+
+/// Error from [`set_remote_device_password`]
+#[cfg(all(feature = "sha2", feature = "crypto", feature = "getrandom"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RemotePasswordError<E> {
+    Request(RequestError<E>),
+    /// The operating system's secure random number generator isn't available
+    Random(getrandom::Error),
+}
+#[cfg(all(feature = "sha2", feature = "crypto", feature = "getrandom"))]
+impl<E> From<RequestError<E>> for RemotePasswordError<E> {
+    fn from(err: RequestError<E>) -> Self {
+        RemotePasswordError::Request(err)
+    }
+}
+#[cfg(all(feature = "sha2", feature = "crypto", feature = "getrandom"))]
+impl<E: core::fmt::Display> core::fmt::Display for RemotePasswordError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            RemotePasswordError::Request(err) => write!(f, "{}", err),
+            RemotePasswordError::Random(err) => write!(f, "No secure random numbers ({})", err),
+        }
+    }
+}
+
+/// Set the network password of a device that isn't on this host's network, using the device
+/// password (DPW) on its label, returning the new NID if it was accepted
+///
+/// Each interface is tried in turn, until one reaches the device. The IV and nonces come from
+/// the operating system's secure random number generator, and nothing is sent without it.
+#[cfg(all(feature = "sha2", feature = "crypto", feature = "getrandom"))]
+pub fn set_remote_device_password<T: EtherInterface>(
+    interfaces: impl IntoIterator<Item = T>,
+    addr: EtherAddr,
+    dpw: &str,
+    password: &str,
+    security: SecurityLevel,
+) -> Result<Option<[u8; 7]>, RemotePasswordError<T::Error>> {
+    for interface in interfaces {
+        let mut s = interface
+            .open(EtherType::HOMEPLUG_AV)
+            .map_err(RequestError::Socket)?;
+        let random = random_bytes().map_err(RemotePasswordError::Random)?;
+        if let Some(nid) =
+            set_remote_network_password(&mut s, addr, dpw, password, security, random)?
+        {
+            info!("{addr:?} joined through {interface}");
+            return Ok(Some(nid));
+        }
+    }
+    Ok(None)
+}
+
+/// Random bytes from the operating system's secure random number generator
+#[cfg(all(feature = "sha2", feature = "crypto", feature = "getrandom"))]
+fn random_bytes<const N: usize>() -> Result<[u8; N], getrandom::Error> {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::homeplug::emulator::*;
+    use crate::memory::*;
+    use core::convert::TryFrom;
+
+    const HOST: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x01]);
+    const QCA: EtherAddr = EtherAddr([0x00, 0xb0, 0x52, 0x11, 0x22, 0x33]);
+    const BCM: EtherAddr = EtherAddr([0x00, 0x1f, 0x84, 0x44, 0x55, 0x66]);
+    const ST: EtherAddr = EtherAddr([0x00, 0x80, 0xe1, 0x77, 0x88, 0x99]);
+
+    /// Answer every request with a confirmation that has no payload, until idle for 500ms
+    fn malformed_station(socket: &mut MemorySocket) {
+        let mut buffer = [0; ETHERNET_MTU];
+        let mut reply = [0; ETHERNET_MTU];
+        let timeout = Some(Duration::from_millis(500));
+        while let Some((addr, data)) = socket.recvfrom(&mut buffer, timeout).unwrap() {
+            let msg = UnknownMessage::try_from(data).unwrap();
+            let size = set_header(&mut reply, msg.mmv(), msg.mmtype().cnf(), msg.oui());
+            socket.sendto(addr, &reply[..size]).unwrap();
+        }
+    }
+
+    #[test]
+    fn malformed_replies() {
+        const BAD: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0xbd]);
+        let wire = MemoryWire::new();
+        let mut qca = DeviceState::qualcomm(QCA);
+        qca.stations = std::vec![BAD];
+        let _qca = Emulator::new(qca)
+            .spawn(&wire.interface("qca", QCA))
+            .unwrap();
+        let mut socket = wire
+            .interface("bad", BAD)
+            .open(EtherType::HOMEPLUG_AV)
+            .unwrap();
+        let bad = std::thread::spawn(move || malformed_station(&mut socket));
+
+        // The malformed station is reported without details, rather than failing the scan
+        let devices = scan_interface(&wire.interface("host", HOST)).unwrap();
+        bad.join().unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].address, QCA);
+        assert!(devices[0].version.is_some());
+        assert_eq!(devices[1].address, BAD);
+        assert_eq!(devices[1].version, None);
+        assert_eq!(devices[1].hfid, None);
+        assert!(devices[1].bridged.is_empty());
+    }
+
+    #[test]
+    fn inventory() {
+        let wire = MemoryWire::new();
+        let mut qca = DeviceState::qualcomm(QCA);
+        qca.hfid_user = "Upstairs".into();
+        qca.stations = std::vec![BCM, ST];
+        qca.phy_rates = std::vec![(BCM, 120, 117), (ST, 9, 8)];
+        let qca = Emulator::new(qca);
+        let bcm = Emulator::new(DeviceState::broadcom(BCM));
+        let _qca = qca.spawn(&wire.interface("qca", QCA)).unwrap();
+        let _bcm = bcm.spawn(&wire.interface("bcm", BCM)).unwrap();
+        let st = Emulator::new(DeviceState::st(ST));
+        let _st = st.spawn(&wire.interface("st", ST)).unwrap();
+        let other = MemoryWire::new().interface("other", HOST);
+        let host = wire.interface("host", HOST);
+
+        let (interface, oui) = find_device([other, host.clone()], BCM).unwrap().unwrap();
+        assert_eq!((interface.name(), oui), ("host", OUI::BROADCOM));
+        set_device_name(&interface, BCM, oui, "Lounge").unwrap();
+        bcm.state().locked = true;
+        assert!(matches!(
+            set_device_name(&interface, BCM, oui, "Kitchen"),
+            Err(DriverError::Rejected)
+        ));
+        bcm.state().locked = false;
+        assert!(matches!(
+            driver(oui).reset(&interface, BCM),
+            Err(DriverError::Unsupported)
+        ));
+        driver(OUI::QUALCOMM).reset(&interface, QCA).unwrap();
+        assert_eq!(
+            driver(OUI::default()).get_name(&interface, QCA).unwrap(),
+            "Upstairs"
+        );
+
+        let devices = scan_interface(&host).unwrap();
+        let names: Vec<_> = devices
+            .iter()
+            .map(|device| (device.address, device.hfid.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (BCM, Some("Lounge")),
+                (ST, Some("")),
+                (QCA, Some("Upstairs"))
+            ]
+        );
+        assert_eq!(devices[1].oui, OUI::ST);
+        assert_eq!(devices[1].manufacturer.as_deref(), Some("ST GreenPHY"));
+        assert_eq!(
+            devices[2].firmware.as_deref(),
+            Some(qca.state().firmware.as_str())
+        );
+        let rates = [
+            PhyRate {
+                peer: BCM,
+                tx: 120,
+                rx: 117,
+            },
+            PhyRate {
+                peer: ST,
+                tx: 9,
+                rx: 8,
+            },
+        ];
+        assert_eq!(devices[2].rates, rates);
+        assert!(devices[0].rates.is_empty());
+    }
+
+    #[test]
+    fn drivers() {
+        let wire = MemoryWire::new();
+        let mut qca = DeviceState::qualcomm(QCA);
+        let tx = qualcomm::TxLinkCounters {
+            mpdu_acked: 10,
+            pb_passed: 40,
+            ..Default::default()
+        };
+        let rx = qualcomm::RxLinkCounters {
+            mpdu_acked: 12,
+            pb_failed: 3,
+            ..Default::default()
+        };
+        qca.links = std::vec![(BCM, tx, rx)];
+        let mut st = DeviceState::st(ST);
+        let st_tx = TxLinkStatistics {
+            msdus: 5,
+            mpdus_acked: 7,
+            ..Default::default()
+        };
+        let st_rx = RxLinkStatistics {
+            octets: 0x4000,
+            fec_errors: 2,
+            ..Default::default()
+        };
+        st.link_statistics = std::vec![(QCA, st_tx, st_rx)];
+        let emulators = [
+            (QCA, OUI::QUALCOMM, Emulator::new(qca)),
+            (
+                BCM,
+                OUI::BROADCOM,
+                Emulator::new(DeviceState::broadcom(BCM)),
+            ),
+            (ST, OUI::ST, Emulator::new(st)),
+        ];
+        let _handles: Vec<EmulatorHandle> = emulators
+            .iter()
+            .map(|(addr, _, emulator)| emulator.spawn(&wire.interface("dev", *addr)).unwrap())
+            .collect();
+        let host = wire.interface("host", HOST);
+        let nmk = [0x42; 16];
+        let nid = [1, 2, 3, 4, 5, 6, 0x07];
+
+        for (addr, oui, emulator) in &emulators {
+            let driver = driver::<MemoryInterface>(*oui);
+            driver.set_key(&host, *addr, nmk, nid).unwrap();
+            assert_eq!(emulator.state().nmk, nmk, "{}", driver.chipset());
+        }
+        // Qualcomm devices pick the NID themselves, so only simple security can be honoured
+        let secure = [1, 2, 3, 4, 5, 6, 0x17];
+        assert!(matches!(
+            driver(OUI::QUALCOMM).set_key(&host, QCA, nmk, secure),
+            Err(DriverError::Unsupported)
+        ));
+
+        let stats = driver(OUI::QUALCOMM).link_stats(&host, QCA, BCM).unwrap();
+        let expected = LinkStatistics::Qualcomm {
+            tx: Some(tx),
+            rx: Some(rx),
+        };
+        assert_eq!(stats, expected);
+        assert!(matches!(
+            driver(OUI::QUALCOMM).link_stats(&host, QCA, ST),
+            Err(DriverError::Rejected)
+        ));
+        // Other chipsets use the standard CM_LINK_STATS
+        let stats = driver(OUI::ST).link_stats(&host, ST, QCA).unwrap();
+        let expected = LinkStatistics::HomePlug {
+            tx: Some(st_tx),
+            rx: Some(st_rx),
+        };
+        assert_eq!(stats, expected);
+        assert!(matches!(
+            driver(OUI::BROADCOM).link_stats(&host, BCM, QCA),
+            Err(DriverError::Rejected)
+        ));
+        // Messages a device doesn't implement are answered with an error, whichever driver sent them
+        assert!(matches!(
+            HomePlugDriver.get_name(&host, BCM),
+            Err(DriverError::Rejected)
+        ));
+        assert!(matches!(
+            QualcommDriver.firmware_version(&host, BCM),
+            Err(DriverError::Rejected)
+        ));
+
+        // Devices that refuse a change are reported as such, rather than as having accepted it
+        for (addr, oui, emulator) in &emulators {
+            emulator.state().locked = true;
+            let driver = driver::<MemoryInterface>(*oui);
+            let chipset = driver.chipset();
+            let result = driver.set_name(&host, *addr, &[b'x'; 64]);
+            assert!(matches!(result, Err(DriverError::Rejected)), "{}", chipset);
+            let result = driver.set_key(&host, *addr, [0x24; 16], nid);
+            assert!(matches!(result, Err(DriverError::Rejected)), "{}", chipset);
+            assert_eq!(emulator.state().nmk, nmk, "{}", chipset);
+            let result = driver.simple_connect(&host, *addr);
+            assert!(matches!(result, Err(DriverError::Rejected)), "{}", chipset);
+        }
+        let result = HomePlugDriver.simple_connect(&host, ST);
+        assert!(matches!(result, Err(DriverError::Rejected)));
+    }
+
+    #[test]
+    fn pairing() {
+        let wire = MemoryWire::new();
+        let nid = [1, 2, 3, 4, 5, 6, 7];
+        let mut qca = DeviceState::qualcomm(QCA);
+        qca.pairing = Some(([0x11; 16], nid));
+        let qca = Emulator::new(qca);
+        let mut st = DeviceState::st(ST);
+        st.pairing = Some(([0x22; 16], nid));
+        let _qca = qca.spawn(&wire.interface("qca", QCA)).unwrap();
+        let _st = Emulator::new(st).spawn(&wire.interface("st", ST)).unwrap();
+        let host = wire.interface("host", HOST);
+
+        let timeout = Duration::from_secs(1);
+        let joined = simple_connect(&host, QCA, OUI::QUALCOMM, timeout).unwrap();
+        assert_eq!(joined, Some(nid));
+        assert_eq!(qca.state().nmk, [0x11; 16]);
+        assert_eq!(
+            network_id(&host, ST).unwrap(),
+            Some(DeviceState::st(ST).nid)
+        );
+        assert_eq!(
+            simple_connect(&host, ST, OUI::ST, timeout).unwrap(),
+            Some(nid)
+        );
+
+        // Nothing to pair with this time
+        assert_eq!(
+            simple_connect(&host, QCA, OUI::QUALCOMM, timeout).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "sha2", feature = "crypto", feature = "getrandom"))]
+    fn remote_password() {
+        let wire = MemoryWire::new();
+        let mut st = DeviceState::st(ST);
+        st.dak = generate_dak("ABCD-EFGH-IJKL-MNOP");
+        let st = Emulator::new(st);
+        let _st = st.spawn(&wire.interface("st", ST)).unwrap();
+        let host = wire.interface("host", HOST);
+        let security = SecurityLevel::SIMPLE;
+
+        let nid = set_remote_device_password([host.clone()], ST, "HomePlugAV", "Secret", security);
+        assert_eq!(nid.unwrap(), None);
+        assert_eq!(st.state().nmk, NMK_HomePlugAV);
+
+        let dpw = "ABCD-EFGH-IJKL-MNOP";
+        let nid = set_remote_device_password([host], ST, dpw, "Secret", security).unwrap();
+        assert_eq!(nid, Some(generate_nid(generate_nmk("Secret"), security)));
+        assert_eq!(st.state().nmk, generate_nmk("Secret"));
+    }
+}
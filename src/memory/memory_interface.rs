@@ -0,0 +1,71 @@
+extern crate std;
+
+use crate::{memory::*, *};
+use std::io::Result;
+use std::string::String;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct MemoryInterface {
+    wire: MemoryWire,
+    name: String,
+    address: EtherAddr,
+    up: Arc<AtomicBool>,
+}
+impl MemoryInterface {
+    pub(crate) fn new(wire: MemoryWire, name: String, address: EtherAddr) -> MemoryInterface {
+        MemoryInterface {
+            wire,
+            name,
+            address,
+            up: Arc::new(AtomicBool::new(true)),
+        }
+    }
+    pub fn wire(&self) -> &MemoryWire {
+        &self.wire
+    }
+    pub fn set_up(&self, up: bool) {
+        self.up.store(up, Ordering::Relaxed);
+    }
+}
+impl EtherInterface for MemoryInterface {
+    type Error = std::io::Error;
+    type Socket = MemorySocket;
+    fn open(&self, ethertype: EtherType) -> Result<MemorySocket> {
+        Ok(MemorySocket::new(
+            self.wire.clone(),
+            self.address,
+            ethertype,
+        ))
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn address(&self) -> EtherAddr {
+        self.address
+    }
+    fn is_up(&self) -> bool {
+        self.up.load(Ordering::Relaxed)
+    }
+    fn is_loopback(&self) -> bool {
+        false
+    }
+}
+impl std::fmt::Debug for MemoryInterface {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let status = if self.is_up() { "up" } else { "down" };
+        write!(
+            f,
+            "{:?} - {} ({} memory)",
+            self.address(),
+            self.name(),
+            status
+        )
+    }
+}
+impl std::fmt::Display for MemoryInterface {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,173 @@
+extern crate std;
+
+use crate::{memory::*, *};
+use std::format;
+use std::io::{Error, Result};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub struct MemorySocket {
+    wire: MemoryWire,
+    id: usize,
+    address: EtherAddr,
+    ethertype: EtherType,
+    queue: Arc<Queue>,
+}
+impl MemorySocket {
+    pub(crate) fn new(wire: MemoryWire, address: EtherAddr, ethertype: EtherType) -> MemorySocket {
+        let (id, queue) = wire.attach(address, ethertype);
+        MemorySocket {
+            wire,
+            id,
+            address,
+            ethertype,
+            queue,
+        }
+    }
+    pub fn address(&self) -> EtherAddr {
+        self.address
+    }
+    pub fn ethertype(&self) -> EtherType {
+        self.ethertype
+    }
+}
+impl EtherSocket for MemorySocket {
+    type Error = Error;
+    fn sendto(&mut self, destination: EtherAddr, data: &[u8]) -> Result<()> {
+        self.wire.transmit(self.id, destination, data);
+        Ok(())
+    }
+    fn recvfrom<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<Option<(EtherAddr, &'a [u8])>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut frames = self.queue.frames.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            // The wire keeps delivery times non-decreasing along the queue, so only the head matters
+            let ready_at = frames.front().map(|frame| frame.deliver_at);
+            if let Some(ready_at) = ready_at {
+                if ready_at <= now {
+                    let frame = frames.pop_front().unwrap();
+                    let (source, size) = deliver(frame, buffer)?;
+                    return Ok(Some((source, &buffer[..size])));
+                }
+            }
+            let wake_at = match (ready_at, deadline) {
+                (Some(ready_at), Some(deadline)) => Some(ready_at.min(deadline)),
+                (ready_at, deadline) => ready_at.or(deadline),
+            };
+            if let Some(deadline) = deadline {
+                if deadline <= now {
+                    return Ok(None);
+                }
+            }
+            frames = match wake_at {
+                Some(wake_at) => {
+                    let wait = wake_at.saturating_duration_since(now);
+                    self.queue.available.wait_timeout(frames, wait).unwrap().0
+                }
+                None => self.queue.available.wait(frames).unwrap(),
+            };
+        }
+    }
+}
+
+/// Copy a received frame into `buffer`, returning its source and size
+fn deliver(frame: Frame, buffer: &mut [u8]) -> Result<(EtherAddr, usize)> {
+    if frame.data.len() > buffer.len() {
+        let msg = format!(
+            "Packet from {:?} too big ({}>{})",
+            frame.source,
+            frame.data.len(),
+            buffer.len()
+        );
+        return Err(Error::other(msg));
+    }
+    buffer[..frame.data.len()].copy_from_slice(&frame.data);
+    Ok((frame.source, frame.data.len()))
+}
+
+impl Drop for MemorySocket {
+    fn drop(&mut self) {
+        self.wire.detach(self.id);
+    }
+}
+impl core::fmt::Debug for MemorySocket {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "MemorySocket({:?} {:?})", self.address, self.ethertype)
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use self::tokio_socket::*;
+
+#[cfg(feature = "tokio")]
+mod tokio_socket {
+    use super::*;
+
+    impl MemorySocket {
+        /// Wrap this socket for use from tokio tasks
+        pub fn into_async(self) -> AsyncMemorySocket {
+            AsyncMemorySocket { inner: self }
+        }
+    }
+
+    /// [`MemorySocket`] that waits for frames without blocking the tokio runtime
+    #[derive(Debug)]
+    pub struct AsyncMemorySocket {
+        inner: MemorySocket,
+    }
+    impl AsyncMemorySocket {
+        pub fn get_ref(&self) -> &MemorySocket {
+            &self.inner
+        }
+    }
+    impl AsyncEtherSocket for AsyncMemorySocket {
+        type Error = Error;
+        async fn sendto(&mut self, destination: EtherAddr, data: &[u8]) -> Result<()> {
+            self.inner.sendto(destination, data)
+        }
+        async fn recvfrom<'a>(
+            &mut self,
+            buffer: &'a mut [u8],
+            timeout: Option<Duration>,
+        ) -> Result<Option<(EtherAddr, &'a [u8])>> {
+            let queue = self.inner.queue.clone();
+            let deadline = timeout.map(|timeout| Instant::now() + timeout);
+            loop {
+                let now = Instant::now();
+                let ready_at = {
+                    let mut frames = queue.frames.lock().unwrap();
+                    match frames.front().map(|frame| frame.deliver_at) {
+                        Some(ready_at) if ready_at <= now => {
+                            let frame = frames.pop_front().unwrap();
+                            let (source, size) = deliver(frame, buffer)?;
+                            return Ok(Some((source, &buffer[..size])));
+                        }
+                        ready_at => ready_at,
+                    }
+                };
+                if let Some(deadline) = deadline {
+                    if deadline <= now {
+                        return Ok(None);
+                    }
+                }
+                let wake_at = match (ready_at, deadline) {
+                    (Some(ready_at), Some(deadline)) => Some(ready_at.min(deadline)),
+                    (ready_at, deadline) => ready_at.or(deadline),
+                };
+                let notified = queue.notify.notified();
+                match wake_at {
+                    Some(wake_at) => {
+                        let wake_at = tokio::time::Instant::from_std(wake_at);
+                        let _ = tokio::time::timeout_at(wake_at, notified).await;
+                    }
+                    None => notified.await,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+extern crate std;
+
+use crate::{memory::*, *};
+use std::collections::VecDeque;
+use std::string::String;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Instant;
+use std::vec::Vec;
+
+pub(crate) struct Frame {
+    pub(crate) deliver_at: Instant,
+    pub(crate) source: EtherAddr,
+    pub(crate) data: Vec<u8>,
+}
+
+#[derive(Default)]
+pub(crate) struct Queue {
+    pub(crate) frames: Mutex<VecDeque<Frame>>,
+    pub(crate) available: Condvar,
+    #[cfg(feature = "tokio")]
+    pub(crate) notify: tokio::sync::Notify,
+}
+
+struct Port {
+    id: usize,
+    address: EtherAddr,
+    ethertype: EtherType,
+    queue: Arc<Queue>,
+}
+
+struct WireState {
+    next_id: usize,
+    ports: Vec<Port>,
+    loss: f64,
+    latency: Duration,
+    rng: u64,
+}
+impl WireState {
+    /// xorshift64* - good enough to decide which frames get lost
+    fn random(&mut self) -> f64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let value = self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Virtual ethernet segment that any number of [`MemoryInterface`]s can attach to
+///
+/// Frames sent by a socket are delivered to every other socket on the wire that is
+/// open on the same ethertype and either owns the destination address, or the
+/// destination is a broadcast/multicast address.
+#[derive(Clone)]
+pub struct MemoryWire {
+    state: Arc<Mutex<WireState>>,
+}
+impl MemoryWire {
+    pub fn new() -> MemoryWire {
+        MemoryWire {
+            state: Arc::new(Mutex::new(WireState {
+                next_id: 0,
+                ports: Vec::new(),
+                loss: 0.0,
+                latency: Duration::from_secs(0),
+                rng: 0x853c_49e6_748f_ea9b,
+            })),
+        }
+    }
+
+    /// Create a new interface attached to this wire
+    pub fn interface(&self, name: &str, address: EtherAddr) -> MemoryInterface {
+        MemoryInterface::new(self.clone(), String::from(name), address)
+    }
+
+    /// Probability (0.0 - 1.0) that any single delivery is dropped
+    pub fn set_loss(&self, probability: f64) {
+        self.state.lock().unwrap().loss = probability.clamp(0.0, 1.0);
+    }
+
+    /// Seed the generator used to decide which frames are dropped
+    pub fn set_seed(&self, seed: u64) {
+        self.state.lock().unwrap().rng = seed | 1;
+    }
+
+    /// Delay between a frame being sent and it becoming available to receivers
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.lock().unwrap().latency = latency;
+    }
+
+    pub(crate) fn attach(&self, address: EtherAddr, ethertype: EtherType) -> (usize, Arc<Queue>) {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        let queue = Arc::new(Queue::default());
+        state.ports.push(Port {
+            id,
+            address,
+            ethertype,
+            queue: queue.clone(),
+        });
+        (id, queue)
+    }
+
+    pub(crate) fn detach(&self, id: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.ports.retain(|port| port.id != id);
+    }
+
+    pub(crate) fn transmit(&self, from: usize, destination: EtherAddr, data: &[u8]) {
+        let mut state = self.state.lock().unwrap();
+        let (source, ethertype) = match state.ports.iter().find(|port| port.id == from) {
+            Some(port) => (port.address, port.ethertype),
+            None => return,
+        };
+        let deliver_at = Instant::now() + state.latency;
+        let mut targets = Vec::new();
+        for port in &state.ports {
+            if port.id == from || port.ethertype != ethertype {
+                continue;
+            }
+            if destination.is_multicast() || destination == port.address {
+                targets.push(port.queue.clone());
+            }
+        }
+        for queue in targets {
+            if state.loss > 0.0 && state.random() < state.loss {
+                continue;
+            }
+            let mut frames = queue.frames.lock().unwrap();
+            // Never deliver before a frame already in flight, even if the latency was lowered
+            let deliver_at = frames
+                .back()
+                .map_or(deliver_at, |last| last.deliver_at.max(deliver_at));
+            frames.push_back(Frame {
+                deliver_at,
+                source,
+                data: data.to_vec(),
+            });
+            drop(frames);
+            queue.available.notify_all();
+            #[cfg(feature = "tokio")]
+            queue.notify.notify_one();
+        }
+    }
+}
+impl Default for MemoryWire {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl core::fmt::Debug for MemoryWire {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let state = self.state.lock().unwrap();
+        write!(
+            f,
+            "MemoryWire(ports={} loss={} latency={:?})",
+            state.ports.len(),
+            state.loss,
+            state.latency
+        )
+    }
+}
@@ -0,0 +1,154 @@
+//! In-memory ethernet segment for exercising the library without hardware
+
+mod memory_interface;
+mod memory_socket;
+mod memory_wire;
+
+pub use memory_interface::*;
+pub use memory_socket::*;
+pub use memory_wire::*;
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::homeplug::*;
+    use crate::*;
+    use std::time::Instant;
+    use std::vec::Vec;
+
+    const A: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x0a]);
+    const B: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x0b]);
+    const C: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x0c]);
+    const SHORT: Option<Duration> = Some(Duration::from_millis(10));
+
+    #[test]
+    fn delivery() {
+        let wire = MemoryWire::new();
+        let mut a = wire.interface("a", A).open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut b = wire.interface("b", B).open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut c = wire.interface("c", C).open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut other = wire.interface("c", C).open(EtherType::MEDIAXTREAM).unwrap();
+        let mut buffer = [0; 1500];
+
+        // Unicast only reaches the owner of the address
+        a.sendto(B, &[1, 2, 3]).unwrap();
+        assert_eq!(
+            b.recvfrom(&mut buffer, SHORT).unwrap(),
+            Some((A, &[1, 2, 3][..]))
+        );
+        assert_eq!(c.recvfrom(&mut buffer, SHORT).unwrap(), None);
+
+        // Broadcast reaches everyone on the same ethertype, except the sender
+        a.sendto(EtherAddr::BROADCAST, &[4]).unwrap();
+        assert_eq!(b.recvfrom(&mut buffer, SHORT).unwrap(), Some((A, &[4][..])));
+        assert_eq!(c.recvfrom(&mut buffer, SHORT).unwrap(), Some((A, &[4][..])));
+        assert_eq!(a.recvfrom(&mut buffer, SHORT).unwrap(), None);
+        assert_eq!(other.recvfrom(&mut buffer, SHORT).unwrap(), None);
+
+        // Closed sockets no longer receive anything
+        drop(c);
+        a.sendto(EtherAddr::BROADCAST, &[5]).unwrap();
+        assert_eq!(b.recvfrom(&mut buffer, SHORT).unwrap(), Some((A, &[5][..])));
+
+        // Frames larger than the receive buffer are reported as errors
+        a.sendto(B, &[0; 64]).unwrap();
+        assert!(b.recvfrom(&mut buffer[..32], SHORT).is_err());
+    }
+
+    #[test]
+    fn loss_and_latency() {
+        let wire = MemoryWire::new();
+        let mut a = wire.interface("a", A).open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut b = wire.interface("b", B).open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut buffer = [0; 1500];
+
+        wire.set_loss(1.0);
+        a.sendto(B, &[1]).unwrap();
+        assert_eq!(b.recvfrom(&mut buffer, SHORT).unwrap(), None);
+
+        wire.set_loss(0.5);
+        for _ in 0..100 {
+            a.sendto(B, &[2]).unwrap();
+        }
+        let mut received = 0;
+        while b.recvfrom(&mut buffer, SHORT).unwrap().is_some() {
+            received += 1;
+        }
+        assert!(received > 20 && received < 80, "received {}", received);
+
+        wire.set_loss(0.0);
+        wire.set_latency(Duration::from_millis(50));
+        let start = Instant::now();
+        a.sendto(B, &[3]).unwrap();
+        assert_eq!(b.recvfrom(&mut buffer, SHORT).unwrap(), None);
+        let received = b
+            .recvfrom(&mut buffer, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(received, Some((A, &[3][..])));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        // Lowering the latency doesn't let later frames overtake earlier ones
+        a.sendto(B, &[4]).unwrap();
+        wire.set_latency(Duration::from_secs(0));
+        a.sendto(B, &[5]).unwrap();
+        let timeout = Some(Duration::from_secs(1));
+        assert_eq!(
+            b.recvfrom(&mut buffer, timeout).unwrap(),
+            Some((A, &[4][..]))
+        );
+        assert_eq!(
+            b.recvfrom(&mut buffer, timeout).unwrap(),
+            Some((A, &[5][..]))
+        );
+    }
+
+    #[test]
+    fn discover() {
+        let wire = MemoryWire::new();
+        let host = wire.interface("host", A);
+        let device = wire.interface("plc", B);
+        let mut device = device.open(EtherType::HOMEPLUG_AV).unwrap();
+        let responder = std::thread::spawn(move || {
+            let mut buffer = [0; 1500];
+            let (addr, _) = device.recvfrom(&mut buffer, None).unwrap().unwrap();
+            // CC_DISCOVER_LIST.CNF with no stations and no networks
+            let reply = [0x01, 0x15, 0x00, 0x00, 0x00, 0x00, 0x00];
+            device.sendto(addr, &reply).unwrap();
+        });
+
+        let mut socket = host.open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut found = Vec::new();
+        discover_devices(&mut socket, |addr, list| {
+            found.push((addr, list.stations().count()));
+        })
+        .unwrap();
+        responder.join().unwrap();
+        assert_eq!(found, [(B, 0)]);
+    }
+
+    #[test]
+    fn malformed_reply() {
+        let wire = MemoryWire::new();
+        let host = wire.interface("host", A);
+        let device = wire.interface("plc", B);
+        let mut device = device.open(EtherType::HOMEPLUG_AV).unwrap();
+        let responder = std::thread::spawn(move || {
+            let mut buffer = [0; 1500];
+            let (addr, _) = device.recvfrom(&mut buffer, None).unwrap().unwrap();
+            // CM_STA_CAP.CNF cut short after the version
+            let reply = [0x01, 0x35, 0x60, 0x00, 0x00, 0x01];
+            device.sendto(addr, &reply).unwrap();
+        });
+
+        let mut socket = host.open(EtherType::HOMEPLUG_AV).unwrap();
+        let mut buffer = [0; 1500];
+        let result = send_request(&mut socket, &mut buffer, B, StationCapabilitiesRequest);
+        responder.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(RequestError::Parse(ParseError::TruncatedPayload { .. }))
+        ));
+    }
+}
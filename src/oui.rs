@@ -1,5 +1,5 @@
 #[repr(transparent)]
-#[derive(Default, PartialEq, Eq, Copy, Clone)]
+#[derive(Default, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct OUI(pub [u8; OUI::SIZE]);
 impl OUI {
     pub const SIZE: usize = 3;
@@ -0,0 +1,165 @@
+//! Packet capture files, for recording traffic and replaying it offline
+//!
+//! Captures are written as pcapng, and both pcap and pcapng can be read back.
+//! Only Ethernet (`LINKTYPE_ETHERNET`) interfaces are supported.
+
+extern crate std;
+
+mod pcap_reader;
+mod pcap_replay_socket;
+mod pcapng_writer;
+
+pub use pcap_reader::*;
+pub use pcap_replay_socket::*;
+pub use pcapng_writer::*;
+
+use crate::*;
+use std::vec::Vec;
+
+/// `LINKTYPE_ETHERNET`
+pub const LINKTYPE_ETHERNET: u16 = 1;
+
+/// Size of the Ethernet header reconstructed in front of each payload
+pub const ETHERNET_HEADER_SIZE: usize = 14;
+
+/// A single Ethernet frame from a capture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// Time since the UNIX epoch
+    pub timestamp: Duration,
+    /// Index of the capture interface the frame was seen on
+    pub interface: u32,
+    pub destination: EtherAddr,
+    pub source: EtherAddr,
+    pub ethertype: EtherType,
+    /// Everything after the Ethernet header
+    pub payload: Vec<u8>,
+}
+impl CapturedFrame {
+    /// Split a raw Ethernet frame into a `CapturedFrame`, skipping a single 802.1Q tag
+    pub fn from_ethernet(timestamp: Duration, interface: u32, frame: &[u8]) -> Option<Self> {
+        if frame.len() < ETHERNET_HEADER_SIZE {
+            return None;
+        }
+        let mut ethertype = EtherType::from_slice(&frame[12..14]);
+        let mut payload = &frame[ETHERNET_HEADER_SIZE..];
+        if ethertype == EtherType::VLAN && payload.len() >= 4 {
+            ethertype = EtherType::from_slice(&payload[2..4]);
+            payload = &payload[4..];
+        }
+        Some(CapturedFrame {
+            timestamp,
+            interface,
+            destination: EtherAddr::from_slice(&frame[0..6]),
+            source: EtherAddr::from_slice(&frame[6..12]),
+            ethertype,
+            payload: payload.to_vec(),
+        })
+    }
+
+    /// The frame with its Ethernet header, as it appeared on the wire
+    pub fn to_ethernet(&self) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(ETHERNET_HEADER_SIZE + self.payload.len());
+        frame.extend_from_slice(&self.destination.0);
+        frame.extend_from_slice(&self.source.0);
+        frame.extend_from_slice(&self.ethertype.0.to_be_bytes());
+        frame.extend_from_slice(&self.payload);
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::homeplug::*;
+    use core::convert::TryFrom;
+    use std::io::Cursor;
+
+    const A: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x0a]);
+    const B: EtherAddr = EtherAddr([0x02, 0, 0, 0, 0, 0x0b]);
+
+    fn frame(timestamp: Duration, interface: u32, payload: &[u8]) -> CapturedFrame {
+        CapturedFrame {
+            timestamp,
+            interface,
+            destination: B,
+            source: A,
+            ethertype: EtherType::HOMEPLUG_AV,
+            payload: payload.to_vec(),
+        }
+    }
+
+    #[test]
+    fn pcapng_round_trip() {
+        let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+        let eth0 = writer.add_interface("eth0").unwrap();
+        let eth1 = writer.add_interface("eth1").unwrap();
+        let frames = [
+            frame(
+                Duration::new(1_600_000_000, 123_456_789),
+                eth0,
+                &[0x01, 0x00, 0x60],
+            ),
+            frame(
+                Duration::new(1_600_000_001, 0),
+                eth1,
+                &[0x01, 0x15, 0x00, 0x00, 0x00],
+            ),
+        ];
+        for frame in &frames {
+            writer.write_frame(frame).unwrap();
+        }
+        let unknown = frame(Duration::new(1_600_000_002, 0), 2, &[0x01]);
+        let err = writer.write_frame(&unknown).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let data = writer.into_inner();
+
+        let mut reader = PcapReader::new(Cursor::new(data)).unwrap();
+        assert_eq!(reader.next_frame().unwrap().as_ref(), Some(&frames[0]));
+        assert_eq!(reader.next_frame().unwrap().as_ref(), Some(&frames[1]));
+        assert_eq!(reader.next_frame().unwrap(), None);
+        assert_eq!(reader.interface_name(eth0), Some("eth0"));
+        assert_eq!(reader.interface_name(eth1), Some("eth1"));
+    }
+
+    #[test]
+    fn pcap_replay() {
+        // Classic big-endian pcap with nanosecond timestamps
+        let mut data = Vec::new();
+        data.extend_from_slice(&0xa1b2_3c4du32.to_be_bytes());
+        data.extend_from_slice(&[0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff]);
+        data.extend_from_slice(&(LINKTYPE_ETHERNET as u32).to_be_bytes());
+        let frames = [
+            frame(
+                Duration::new(10, 500),
+                0,
+                &[0x01, 0x15, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ),
+            CapturedFrame {
+                ethertype: EtherType::IPV4,
+                ..frame(Duration::new(11, 0), 0, &[0x45])
+            },
+        ];
+        for frame in &frames {
+            let ethernet = frame.to_ethernet();
+            data.extend_from_slice(&(frame.timestamp.as_secs() as u32).to_be_bytes());
+            data.extend_from_slice(&frame.timestamp.subsec_nanos().to_be_bytes());
+            data.extend_from_slice(&(ethernet.len() as u32).to_be_bytes());
+            data.extend_from_slice(&(ethernet.len() as u32).to_be_bytes());
+            data.extend_from_slice(&ethernet);
+        }
+
+        let reader = PcapReader::new(Cursor::new(data)).unwrap();
+        let mut socket = PcapReplaySocket::from_reader(reader, EtherType::HOMEPLUG_AV).unwrap();
+        let mut buffer = [0; 1500];
+        let (source, data) = socket.recvfrom(&mut buffer, None).unwrap().unwrap();
+        assert_eq!(source, A);
+        let msg = UnknownMessage::try_from(data).unwrap();
+        assert_eq!(msg.mmtype(), MMType::CC_DISCOVER_LIST.cnf());
+        // The IPv4 frame is filtered out, so the replay is finished
+        assert_eq!(socket.recvfrom(&mut buffer, None).unwrap(), None);
+
+        socket.sendto(B, &[1, 2, 3]).unwrap();
+        assert_eq!(socket.sent(), &[(B, std::vec![1, 2, 3])]);
+    }
+}
@@ -0,0 +1,318 @@
+extern crate std;
+
+use crate::pcap::*;
+use std::io::{Error, ErrorKind, Read, Result};
+use std::string::String;
+use std::vec;
+use std::vec::Vec;
+
+const PCAP_MICROSECONDS: u32 = 0xa1b2_c3d4;
+const PCAP_NANOSECONDS: u32 = 0xa1b2_3c4d;
+
+const BLOCK_SHB: u32 = 0x0a0d_0d0a;
+const BLOCK_IDB: u32 = 0x0000_0001;
+const BLOCK_SPB: u32 = 0x0000_0003;
+const BLOCK_EPB: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+
+const OPT_ENDOFOPT: u16 = 0;
+const OPT_IF_NAME: u16 = 2;
+const OPT_IF_TSRESOL: u16 = 9;
+
+/// Refuse blocks larger than this, rather than allocating whatever a corrupt file asks for
+const MAX_BLOCK_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Pcap,
+    PcapNg,
+}
+
+#[derive(Debug)]
+struct Interface {
+    linktype: u16,
+    name: Option<String>,
+    /// Timestamp units per second
+    resolution: u64,
+}
+
+/// Reads Ethernet frames from a pcap or pcapng capture
+///
+/// The format and byte order are detected from the file header. Frames from interfaces
+/// that aren't Ethernet are skipped.
+pub struct PcapReader<R: Read> {
+    reader: R,
+    format: Format,
+    big_endian: bool,
+    interfaces: Vec<Interface>,
+}
+impl<R: Read> PcapReader<R> {
+    pub fn new(mut reader: R) -> Result<PcapReader<R>> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        let mut pcap = PcapReader {
+            reader,
+            format: Format::Pcap,
+            big_endian: false,
+            interfaces: Vec::new(),
+        };
+        if u32::from_le_bytes(magic) == BLOCK_SHB {
+            pcap.format = Format::PcapNg;
+            pcap.read_section_header()?;
+            return Ok(pcap);
+        }
+        let resolution = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAP_MICROSECONDS, _) => 1_000_000,
+            (PCAP_NANOSECONDS, _) => 1_000_000_000,
+            (_, PCAP_MICROSECONDS) => {
+                pcap.big_endian = true;
+                1_000_000
+            }
+            (_, PCAP_NANOSECONDS) => {
+                pcap.big_endian = true;
+                1_000_000_000
+            }
+            _ => return Err(invalid("Not a pcap or pcapng file")),
+        };
+        let mut header = [0; 20];
+        pcap.reader.read_exact(&mut header)?;
+        pcap.interfaces.push(Interface {
+            linktype: pcap.u32(&header[16..20]) as u16,
+            name: None,
+            resolution,
+        });
+        Ok(pcap)
+    }
+
+    /// Name of capture interface `interface`, if the file recorded one
+    pub fn interface_name(&self, interface: u32) -> Option<&str> {
+        self.interfaces.get(interface as usize)?.name.as_deref()
+    }
+
+    /// Read the next Ethernet frame, or `None` at the end of the file
+    pub fn next_frame(&mut self) -> Result<Option<CapturedFrame>> {
+        match self.format {
+            Format::Pcap => self.next_pcap_frame(),
+            Format::PcapNg => self.next_pcapng_frame(),
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn next_pcap_frame(&mut self) -> Result<Option<CapturedFrame>> {
+        loop {
+            let mut header = [0; 16];
+            if !self.read_or_eof(&mut header)? {
+                return Ok(None);
+            }
+            let seconds = self.u32(&header[0..4]) as u64;
+            let fraction = self.u32(&header[4..8]) as u64;
+            let length = self.u32(&header[8..12]) as usize;
+            let data = self.read_vec(length)?;
+            let interface = &self.interfaces[0];
+            if interface.linktype != LINKTYPE_ETHERNET {
+                continue;
+            }
+            let timestamp = Duration::from_secs(seconds) + duration(fraction, interface.resolution);
+            if let Some(frame) = CapturedFrame::from_ethernet(timestamp, 0, &data) {
+                return Ok(Some(frame));
+            }
+        }
+    }
+
+    fn next_pcapng_frame(&mut self) -> Result<Option<CapturedFrame>> {
+        loop {
+            let mut header = [0; 8];
+            if !self.read_or_eof(&mut header)? {
+                return Ok(None);
+            }
+            let block_type = self.u32(&header[0..4]);
+            if block_type == BLOCK_SHB {
+                // A new section, possibly with a different byte order
+                self.read_section_header()?;
+                continue;
+            }
+            let length = self.u32(&header[4..8]) as usize;
+            if length < 12 || !length.is_multiple_of(4) {
+                return Err(invalid("Invalid pcapng block length"));
+            }
+            let block = self.read_vec(length - 8)?;
+            let body = &block[..block.len() - 4];
+            if let Some(frame) = self.parse_block(block_type, body)? {
+                return Ok(Some(frame));
+            }
+        }
+    }
+
+    /// Handle a pcapng block, returning a frame if it contained one
+    fn parse_block(&mut self, block_type: u32, body: &[u8]) -> Result<Option<CapturedFrame>> {
+        let (interface, timestamp, data) = match block_type {
+            BLOCK_IDB => {
+                if body.len() < 8 {
+                    return Err(invalid("Truncated interface description"));
+                }
+                let mut interface = Interface {
+                    linktype: self.u16(&body[0..2]),
+                    name: None,
+                    resolution: 1_000_000,
+                };
+                for (code, value) in self.options(&body[8..]) {
+                    match (code, value) {
+                        (OPT_IF_NAME, name) => {
+                            let name = String::from_utf8_lossy(name);
+                            interface.name = Some(name.trim_end_matches('\0').into());
+                        }
+                        (OPT_IF_TSRESOL, &[resolution]) => {
+                            interface.resolution = match resolution {
+                                r if r & 0x80 == 0 => 10u64.checked_pow(r as u32),
+                                r => 1u64.checked_shl((r & 0x7f) as u32),
+                            }
+                            .ok_or_else(|| invalid("Unsupported timestamp resolution"))?;
+                        }
+                        _ => {}
+                    }
+                }
+                self.interfaces.push(interface);
+                return Ok(None);
+            }
+            BLOCK_EPB => {
+                if body.len() < 20 {
+                    return Err(invalid("Truncated packet block"));
+                }
+                let interface = self.u32(&body[0..4]);
+                let timestamp =
+                    (self.u32(&body[4..8]) as u64) << 32 | self.u32(&body[8..12]) as u64;
+                let length = self.u32(&body[12..16]) as usize;
+                let data = body
+                    .get(20..20 + length)
+                    .ok_or_else(|| invalid("Truncated packet data"))?;
+                (interface, Some(timestamp), data)
+            }
+            BLOCK_SPB => {
+                // Simple packets have no timestamp, and always belong to the first interface
+                let length = body.len().min(match body.get(0..4) {
+                    Some(length) => 4 + self.u32(length) as usize,
+                    None => return Err(invalid("Truncated packet block")),
+                });
+                (0, None, &body[4..length])
+            }
+            _ => return Ok(None),
+        };
+        let info = self
+            .interfaces
+            .get(interface as usize)
+            .ok_or_else(|| invalid("Packet for unknown interface"))?;
+        if info.linktype != LINKTYPE_ETHERNET {
+            return Ok(None);
+        }
+        let timestamp = timestamp.map_or(Duration::ZERO, |t| duration(t, info.resolution));
+        Ok(CapturedFrame::from_ethernet(timestamp, interface, data))
+    }
+
+    /// Read the rest of a section header, whose block type has already been read
+    fn read_section_header(&mut self) -> Result<()> {
+        let mut header = [0; 8];
+        self.reader.read_exact(&mut header)?;
+        self.big_endian = match u32::from_le_bytes([header[4], header[5], header[6], header[7]]) {
+            BYTE_ORDER_MAGIC => false,
+            magic if magic.swap_bytes() == BYTE_ORDER_MAGIC => true,
+            _ => return Err(invalid("Invalid pcapng byte order magic")),
+        };
+        let length = self.u32(&header[0..4]) as usize;
+        if length < 28 || !length.is_multiple_of(4) {
+            return Err(invalid("Invalid pcapng section header length"));
+        }
+        self.read_vec(length - 12)?;
+        // Interface indices are local to each section
+        self.interfaces.clear();
+        Ok(())
+    }
+
+    fn options<'a>(&self, mut options: &'a [u8]) -> impl Iterator<Item = (u16, &'a [u8])> + 'a {
+        let big_endian = self.big_endian;
+        core::iter::from_fn(move || {
+            if options.len() < 4 {
+                return None;
+            }
+            let (code, length) = if big_endian {
+                let code = u16::from_be_bytes([options[0], options[1]]);
+                (code, u16::from_be_bytes([options[2], options[3]]))
+            } else {
+                let code = u16::from_le_bytes([options[0], options[1]]);
+                (code, u16::from_le_bytes([options[2], options[3]]))
+            };
+            let length = length as usize;
+            let value = options.get(4..4 + length)?;
+            if code == OPT_ENDOFOPT {
+                return None;
+            }
+            options = options.get(4 + ((length + 3) & !3)..).unwrap_or(&[]);
+            Some((code, value))
+        })
+    }
+
+    /// Fill `buffer`, returning `false` if the file ended cleanly before it
+    fn read_or_eof(&mut self, buffer: &mut [u8]) -> Result<bool> {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match self.reader.read(&mut buffer[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => return Err(Error::from(ErrorKind::UnexpectedEof)),
+                Ok(size) => filled += size,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_vec(&mut self, length: usize) -> Result<Vec<u8>> {
+        if length > MAX_BLOCK_SIZE {
+            return Err(invalid("Capture block too large"));
+        }
+        let mut data = vec![0; length];
+        self.reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    fn u16(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+}
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = Result<CapturedFrame>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+impl<R: Read> core::fmt::Debug for PcapReader<R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "PcapReader({:?} {:?})", self.format, self.interfaces)
+    }
+}
+
+/// Convert `units` at `resolution` units per second into a duration
+fn duration(units: u64, resolution: u64) -> Duration {
+    let nanos = units as u128 * 1_000_000_000 / resolution as u128;
+    Duration::from_nanos(nanos as u64)
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
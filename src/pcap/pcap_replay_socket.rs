@@ -0,0 +1,89 @@
+extern crate std;
+
+use crate::pcap::*;
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, Read, Result};
+
+/// An [`EtherSocket`] that receives the frames of a capture, for analysing traces offline
+///
+/// Frames are delivered in capture order without waiting, and `recvfrom` returns `None`
+/// once the capture is exhausted. Sent frames are recorded rather than transmitted.
+pub struct PcapReplaySocket {
+    ethertype: EtherType,
+    frames: VecDeque<CapturedFrame>,
+    sent: Vec<(EtherAddr, Vec<u8>)>,
+}
+impl PcapReplaySocket {
+    /// Replay the frames of type `ethertype` from `frames`
+    pub fn new(frames: impl IntoIterator<Item = CapturedFrame>, ethertype: EtherType) -> Self {
+        PcapReplaySocket {
+            ethertype,
+            frames: frames
+                .into_iter()
+                .filter(|frame| frame.ethertype == ethertype)
+                .collect(),
+            sent: Vec::new(),
+        }
+    }
+
+    /// Read a whole capture, and replay the frames of type `ethertype`
+    pub fn from_reader<R: Read>(reader: PcapReader<R>, ethertype: EtherType) -> Result<Self> {
+        let frames = reader.collect::<Result<Vec<CapturedFrame>>>()?;
+        Ok(Self::new(frames, ethertype))
+    }
+
+    pub fn ethertype(&self) -> EtherType {
+        self.ethertype
+    }
+
+    /// Number of frames not yet received
+    pub fn remaining(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The next frame `recvfrom` will return, with its capture metadata
+    pub fn peek(&self) -> Option<&CapturedFrame> {
+        self.frames.front()
+    }
+
+    /// Everything passed to `sendto` so far
+    pub fn sent(&self) -> &[(EtherAddr, Vec<u8>)] {
+        &self.sent
+    }
+}
+impl EtherSocket for PcapReplaySocket {
+    type Error = Error;
+    fn sendto(&mut self, destination: EtherAddr, data: &[u8]) -> Result<()> {
+        self.sent.push((destination, data.to_vec()));
+        Ok(())
+    }
+    fn recvfrom<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        _timeout: Option<Duration>,
+    ) -> Result<Option<(EtherAddr, &'a [u8])>> {
+        let frame = match self.frames.pop_front() {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+        let size = frame.payload.len();
+        if size > buffer.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                std::format!("Frame of {} bytes doesn't fit in buffer", size),
+            ));
+        }
+        buffer[..size].copy_from_slice(&frame.payload);
+        Ok(Some((frame.source, &buffer[..size])))
+    }
+}
+impl core::fmt::Debug for PcapReplaySocket {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "PcapReplaySocket({:?} remaining={})",
+            self.ethertype,
+            self.frames.len()
+        )
+    }
+}
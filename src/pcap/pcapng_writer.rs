@@ -0,0 +1,120 @@
+extern crate std;
+
+use crate::pcap::*;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::vec::Vec;
+
+const BLOCK_SHB: u32 = 0x0a0d_0d0a;
+const BLOCK_IDB: u32 = 0x0000_0001;
+const BLOCK_EPB: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+
+const OPT_ENDOFOPT: u16 = 0;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_NAME: u16 = 2;
+const OPT_IF_TSRESOL: u16 = 9;
+
+/// Timestamps are written with nanosecond resolution
+const TSRESOL_NANOSECONDS: u8 = 9;
+
+/// Writes Ethernet frames to a pcapng capture, readable by Wireshark
+///
+/// Blocks are written in native byte order, which readers detect from the section header.
+pub struct PcapNgWriter<W: Write> {
+    writer: W,
+    interfaces: u32,
+}
+impl<W: Write> PcapNgWriter<W> {
+    /// Start a new capture, writing the section header
+    pub fn new(writer: W) -> Result<PcapNgWriter<W>> {
+        let mut pcapng = PcapNgWriter {
+            writer,
+            interfaces: 0,
+        };
+        let mut body = Vec::new();
+        body.extend_from_slice(&BYTE_ORDER_MAGIC.to_ne_bytes());
+        body.extend_from_slice(&1u16.to_ne_bytes());
+        body.extend_from_slice(&0u16.to_ne_bytes());
+        // Section length is unknown
+        body.extend_from_slice(&(-1i64).to_ne_bytes());
+        let application = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+        push_option(&mut body, OPT_SHB_USERAPPL, application.as_bytes());
+        push_option(&mut body, OPT_ENDOFOPT, &[]);
+        pcapng.write_block(BLOCK_SHB, &body)?;
+        Ok(pcapng)
+    }
+
+    /// Describe a capture interface, returning its index for [`CapturedFrame::interface`]
+    pub fn add_interface(&mut self, name: &str) -> Result<u32> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&LINKTYPE_ETHERNET.to_ne_bytes());
+        body.extend_from_slice(&0u16.to_ne_bytes());
+        // No snapshot length limit
+        body.extend_from_slice(&0u32.to_ne_bytes());
+        push_option(&mut body, OPT_IF_NAME, name.as_bytes());
+        push_option(&mut body, OPT_IF_TSRESOL, &[TSRESOL_NANOSECONDS]);
+        push_option(&mut body, OPT_ENDOFOPT, &[]);
+        self.write_block(BLOCK_IDB, &body)?;
+        self.interfaces += 1;
+        Ok(self.interfaces - 1)
+    }
+
+    /// Write a frame, reconstructing its Ethernet header
+    ///
+    /// `frame.interface` must have been returned by [`add_interface`](Self::add_interface),
+    /// otherwise this fails with [`ErrorKind::InvalidInput`].
+    pub fn write_frame(&mut self, frame: &CapturedFrame) -> Result<()> {
+        if frame.interface >= self.interfaces {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Unknown capture interface",
+            ));
+        }
+        let ethernet = frame.to_ethernet();
+        let timestamp = frame.timestamp.as_nanos() as u64;
+        let mut body = Vec::with_capacity(20 + ethernet.len() + 3);
+        body.extend_from_slice(&frame.interface.to_ne_bytes());
+        body.extend_from_slice(&((timestamp >> 32) as u32).to_ne_bytes());
+        body.extend_from_slice(&(timestamp as u32).to_ne_bytes());
+        body.extend_from_slice(&(ethernet.len() as u32).to_ne_bytes());
+        body.extend_from_slice(&(ethernet.len() as u32).to_ne_bytes());
+        body.extend_from_slice(&ethernet);
+        pad(&mut body);
+        self.write_block(BLOCK_EPB, &body)
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> Result<()> {
+        let length = (12 + body.len()) as u32;
+        self.writer.write_all(&block_type.to_ne_bytes())?;
+        self.writer.write_all(&length.to_ne_bytes())?;
+        self.writer.write_all(body)?;
+        self.writer.write_all(&length.to_ne_bytes())
+    }
+}
+impl<W: Write> core::fmt::Debug for PcapNgWriter<W> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "PcapNgWriter(interfaces={})", self.interfaces)
+    }
+}
+
+fn push_option(body: &mut Vec<u8>, code: u16, value: &[u8]) {
+    body.extend_from_slice(&code.to_ne_bytes());
+    body.extend_from_slice(&(value.len() as u16).to_ne_bytes());
+    body.extend_from_slice(value);
+    pad(body);
+}
+
+/// Blocks and option values are padded to 32 bits
+fn pad(body: &mut Vec<u8>) {
+    while !body.len().is_multiple_of(4) {
+        body.push(0);
+    }
+}
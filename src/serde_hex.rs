@@ -0,0 +1,131 @@
+//! Serde support for addresses, written as colon separated hex in human readable formats
+
+use super::{EtherAddr, OUI};
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::{Error, SeqAccess, Unexpected, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Longest string needed, for an `EtherAddr`
+const MAX_TEXT: usize = EtherAddr::SIZE * 3 - 1;
+
+fn serialize<S: Serializer, const N: usize>(
+    bytes: &[u8; N],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if !serializer.is_human_readable() {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for byte in bytes {
+            tuple.serialize_element(byte)?;
+        }
+        return tuple.end();
+    }
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut text = [b':'; MAX_TEXT];
+    for (i, byte) in bytes.iter().enumerate() {
+        text[i * 3] = DIGITS[(byte >> 4) as usize];
+        text[i * 3 + 1] = DIGITS[(byte & 0xf) as usize];
+    }
+    let text = core::str::from_utf8(&text[..N * 3 - 1]).unwrap();
+    serializer.serialize_str(text)
+}
+
+struct HexVisitor<const N: usize>(PhantomData<[u8; N]>);
+impl<'de, const N: usize> Visitor<'de> for HexVisitor<N> {
+    type Value = [u8; N];
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} colon separated hex bytes", N)
+    }
+    fn visit_str<E: Error>(self, value: &str) -> Result<[u8; N], E> {
+        let invalid = || E::invalid_value(Unexpected::Str(value), &self);
+        let mut bytes = [0; N];
+        let mut parts = value.split([':', '-']);
+        for byte in bytes.iter_mut() {
+            let part = parts
+                .next()
+                .filter(|part| part.len() == 2)
+                .ok_or_else(invalid)?;
+            *byte = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+        }
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(bytes)
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; N], A::Error> {
+        let mut bytes = [0; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+        }
+        Ok(bytes)
+    }
+}
+
+fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(HexVisitor(PhantomData))
+    } else {
+        deserializer.deserialize_tuple(N, HexVisitor(PhantomData))
+    }
+}
+
+impl Serialize for EtherAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+impl<'de> Deserialize<'de> for EtherAddr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(EtherAddr)
+    }
+}
+
+impl Serialize for OUI {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+impl<'de> Deserialize<'de> for OUI {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(OUI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::homeplug::*;
+    use std::vec;
+
+    #[test]
+    fn json() {
+        let addr = EtherAddr::QUALCOMM_LOCALCAST;
+        let text = serde_json::to_string(&addr).unwrap();
+        assert_eq!(text, r#""00:b0:52:00:00:01""#);
+        assert_eq!(serde_json::from_str::<EtherAddr>(&text).unwrap(), addr);
+        assert!(serde_json::from_str::<EtherAddr>(r#""00:b0:52:00:00""#).is_err());
+        assert!(serde_json::from_str::<OUI>(r#""00:b0:52:00""#).is_err());
+
+        let bridge = OwnedBridgeInfo {
+            is_bridge: true,
+            tei: 3,
+            destinations: vec![addr],
+        };
+        let text = serde_json::to_string(&bridge).unwrap();
+        assert_eq!(
+            text,
+            r#"{"is_bridge":true,"tei":3,"destinations":["00:b0:52:00:00:01"]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<OwnedBridgeInfo>(&text).unwrap(),
+            bridge
+        );
+    }
+}